@@ -0,0 +1,121 @@
+//! Benchmarks of the paths that run for every received byte or drawn frame,
+//! fed with buffers as large as long captures get
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use ratatui::{backend::TestBackend, Terminal};
+use serialport::SerialPort;
+use std::io::Write;
+use tuicom::app::App;
+use tuicom::dummy::DummySerial;
+use tuicom::link::Link;
+use tuicom::screen::{wrapped_rows, Display, Rx};
+
+/// 1 MiB of log-like text
+fn capture() -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut n = 0u32;
+    while out.len() < 1 << 20 {
+        writeln!(out, "[{n:08}] sensor temp={}.{} ok", n % 97, n % 10).unwrap();
+        n += 1;
+    }
+    out
+}
+
+fn rx_ingestion(c: &mut Criterion) {
+    let data = capture();
+    c.bench_function("rx recv 1 MiB in 4 KiB reads", |b| {
+        b.iter_batched(
+            || (Rx::new(), DummySerial::new(115200)),
+            |(mut rx, mut port)| {
+                for chunk in data.chunks(4096) {
+                    port.write_all(chunk).unwrap();
+                    rx.recv(&mut port).unwrap();
+                }
+                rx
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn hex_conversion(c: &mut Criterion) {
+    let data = capture();
+    c.bench_function("switch 1 MiB to hex", |b| {
+        b.iter_batched(
+            || {
+                let mut d = Display::new();
+                for &b in &data {
+                    d.push_byte(b);
+                }
+                d
+            },
+            |mut d| {
+                d.switch_hex();
+                d
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("push 64 KiB in hex", |b| {
+        b.iter_batched(
+            || {
+                let mut d = Display::new();
+                d.switch_hex();
+                d
+            },
+            |mut d| {
+                for &b in &data[..1 << 16] {
+                    d.push_byte(b);
+                }
+                d
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn line_indexing(c: &mut Criterion) {
+    let text = String::from_utf8(capture()).unwrap();
+    c.bench_function("wrap 1 MiB of lines at 80 columns", |b| {
+        b.iter(|| {
+            black_box(&text)
+                .lines()
+                .map(|l| wrapped_rows(l, 80))
+                .sum::<usize>()
+        })
+    });
+    let mut display = Display::new();
+    for &b in text.as_bytes() {
+        display.push_byte(b);
+    }
+    c.bench_function("rows of 1 MiB of indexed lines at 80 columns", |b| {
+        b.iter(|| black_box(&display).rows(80, true))
+    });
+}
+
+fn draw_preparation(c: &mut Criterion) {
+    let data = capture();
+    let mut port = DummySerial::new(115200);
+    let mut app = App::new(
+        port.try_clone().unwrap(),
+        Link::new("dummy", port.baud_rate().unwrap()),
+    );
+    for chunk in data.chunks(4096) {
+        port.write_all(chunk).unwrap();
+        app.rx.recv(&mut port).unwrap();
+    }
+    let mut terminal = Terminal::new(TestBackend::new(200, 60)).unwrap();
+    c.bench_function("draw a 200x60 frame over 1 MiB of RX", |b| {
+        b.iter(|| {
+            terminal.draw(|f| tuicom::ui::draw(f, &mut app)).unwrap();
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    rx_ingestion,
+    hex_conversion,
+    line_indexing,
+    draw_preparation
+);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Sending no more than the device acknowledged: files and pastes go out a
+//! unit at a time, with at most a window of them unacknowledged, for devices
+//! whose UART buffers overflow otherwise
+use regex::bytes::Regex;
+
+pub struct AckGate {
+    /// what the device answers every unit with, like `ok` or `\x06`
+    pattern: Regex,
+    /// units sent without waiting for their acknowledgement
+    pub window: usize,
+    in_flight: usize,
+    /// what arrived since the last acknowledgement
+    seen: Vec<u8>,
+}
+
+impl AckGate {
+    /// longest unit of a file, one ends earlier at a line break
+    pub const BLOCK: usize = 64;
+    /// received bytes kept while looking for an acknowledgement
+    const MAX_SEEN: usize = 256;
+
+    pub fn new(pattern: Regex, window: usize) -> Self {
+        Self {
+            pattern,
+            window: window.max(1),
+            in_flight: 0,
+            seen: Vec::new(),
+        }
+    }
+    /// counts the acknowledgements in newly received `bytes`
+    pub fn scan(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        self.seen.extend_from_slice(bytes);
+        let mut end = 0;
+        for m in self.pattern.find_iter(&self.seen) {
+            self.in_flight = self.in_flight.saturating_sub(1);
+            end = m.end();
+        }
+        let end = end.max(self.seen.len().saturating_sub(Self::MAX_SEEN));
+        self.seen.drain(..end);
+    }
+    pub fn can_send(&self) -> bool {
+        self.in_flight < self.window
+    }
+    /// a unit went out, its acknowledgement is awaited
+    pub fn sent(&mut self) {
+        self.in_flight += 1;
+    }
+    /// forgets the units awaited, when a send starts or is dropped
+    pub fn reset(&mut self) {
+        self.in_flight = 0;
+        self.seen.clear();
+    }
+    /// the window is full and sends wait
+    pub fn is_waiting(&self) -> bool {
+        !self.can_send()
+    }
+}
@@ -1,8 +1,59 @@
-use crate::screen::{Rx, Tx};
-use crossterm::event::{Event, KeyCode, KeyEvent};
-use serialport::SerialPort;
+use crate::ack::AckGate;
+use crate::capture::{timestamp, BootLog, Journal, LogFormat, SessionLog, Snapshots};
+use crate::clipboard;
+use crate::command::{is_register, Buffer, ByteFormat, Command, Lines, Range};
+use crate::decode::{parse_hex, DecoderKind, LineSettings};
+use crate::diff::{self, DiffView};
+use crate::eol::LineStats;
+use crate::error_log::ErrorLog;
+use crate::fuzz::Fuzzer;
+use crate::gcode::{self, Job};
+use crate::highlight::Highlights;
+use crate::hooks::{self, Hooks};
+use crate::idle::{IdleRules, Nudge};
+use crate::line_edit::{LineEdit, LineEnding};
+use crate::link::{self, is_disconnect, Link};
+use crate::login::{self, AutoLogin};
+use crate::notify::{self, Bell};
+use crate::paste::{self, Paste};
+use crate::perf::FrameTimes;
+use crate::periodic::{self, Keepalive, Periodic, Template};
+use crate::ports;
+use crate::preset::Presets;
+use crate::profile::{self, Layout, Startup};
+use crate::prompt::Prompt;
+use crate::region::View;
+use crate::screen::overlay::{
+    Confirm, Field, Form, HexEditor, Input, Outcome, OverlayStack, Pager, Picker, Reply, TextArea,
+};
+use crate::screen::{hex_row_bytes, wrapped_rows, ByteFilter, Rx, Tx};
+use crate::search::Search;
+use crate::secret::{self, Redacting, Secrets};
+use crate::session::{self, Session};
+use crate::sweep::Sweep;
+use crate::template::{Check, Endian, FrameTemplate};
+use crate::theme::{ColorDepth, Theme};
+use crate::timeline::{self, TimelineView};
+use crate::toast::Toasts;
+use crate::trace::{self, Trace};
+use crate::watch::Watches;
+use crate::watchdog::Watchdog;
+use crate::waveform::Waveform;
+use crate::window::Window;
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::Rect;
+use regex::Regex;
+use serialport::{ClearBuffer, SerialPort};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use std::io;
-use std::time::{Duration, Instant};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Control {
@@ -19,122 +70,3178 @@ impl Control {
 pub enum Mode {
     Normal,
     Insert,
-    Config,
-    WannaQuit,
-    BaudInput(String),
+    SearchInput {
+        input: String,
+        backward: bool,
+        error: Option<String>,
+    },
+    CommandInput {
+        input: String,
+        error: Option<String>,
+    },
+    Visual(Visual),
 }
 
 impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Insert => "insert",
+            Self::SearchInput { .. } => "search",
+            Self::CommandInput { .. } => "command",
+            Self::Visual(v) if v.linewise => "visual line",
+            Self::Visual(_) => "visual",
+        }
+    }
     pub fn is_insert(&self) -> bool {
         matches!(self, Self::Insert)
     }
     pub fn is_normal(&self) -> bool {
         matches!(self, Self::Normal)
     }
-    pub fn is_config(&self) -> bool {
-        matches!(self, Self::Config)
+}
+
+/// What Ctrl+C does, as terminals people come from disagree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtrlC {
+    /// sends 0x03 to the device in INSERT mode, like picocom
+    Send,
+    /// copies the selection
+    Copy,
+    Quit,
+}
+
+impl CtrlC {
+    pub const ALL: &'static [Self] = &[Self::Send, Self::Copy, Self::Quit];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Send => "send",
+            Self::Copy => "copy",
+            Self::Quit => "quit",
+        }
+    }
+}
+
+impl fmt::Display for CtrlC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for CtrlC {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name() == s)
+            .ok_or_else(|| format!("unknown Ctrl+C action `{s}`, expected send, copy or quit"))
+    }
+}
+
+/// Prefix key for tuicom commands in INSERT mode, like screen's Ctrl+A, so
+/// everything else including Esc goes to the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscapeKey(char);
+
+impl EscapeKey {
+    pub fn matches(self, key: KeyEvent) -> bool {
+        key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char(self.0)
+    }
+    /// the control code the key stands for, sent when it's pressed twice
+    pub fn byte(self) -> u8 {
+        self.0 as u8 - b'a' + 1
+    }
+}
+
+impl fmt::Display for EscapeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "C-{}", self.0)
+    }
+}
+
+/// parses `C-a`, `ctrl-a` or `^A`
+impl FromStr for EscapeKey {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let lower = s.to_ascii_lowercase();
+        let key = ["c-", "ctrl-", "ctrl+", "^"]
+            .iter()
+            .find_map(|prefix| lower.strip_prefix(prefix))
+            .ok_or_else(|| format!("expected a control key like `C-a`, got `{s}`"))?;
+        match key.as_bytes() {
+            [c @ b'a'..=b'z'] => Ok(Self(*c as char)),
+            _ => Err(format!(
+                "only Ctrl with a letter can be the escape key, got `{s}`"
+            )),
+        }
+    }
+}
+
+/// What an overlay on the stack was opened for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialog {
+    Quit,
+    Baud,
+    Frame,
+    Errors,
+    Port,
+    Device,
+    EditTx,
+    Settings,
+    SendFile,
+    Templates,
+    TemplateFields,
+    Diff,
+    Password,
+    Secret,
+    Paste,
+    HexEdit,
+    Region,
+    Trace,
+    Notes,
+    Timeline,
+}
+
+/// Pane receiving pane-specific commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Tx,
+    Rx,
+}
+
+impl Focus {
+    pub const ALL: &'static [Self] = &[Self::Tx, Self::Rx];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&f| f == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
     }
-    pub fn wanna_quit(&self) -> bool {
-        matches!(self, Self::WannaQuit)
+    pub fn prev(self) -> Self {
+        let i = Self::ALL.iter().position(|&f| f == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
     }
 }
 
 pub struct App {
     pub serial: Box<dyn SerialPort>,
+    pub link: Link,
+    /// ports offered by the port picker
+    ports: Vec<String>,
     pub tx: Tx,
     pub rx: Rx,
     pub mode: Mode,
+    /// modal dialogs over the panes, the top one gets the keys
+    pub overlays: OverlayStack<Dialog>,
+    pub focus: Focus,
+    /// height of the TX pane in percent of the space shared with RX
+    pub split: u16,
+    pub periodic: Option<Periodic>,
+    pub keepalive: Option<Keepalive>,
+    pub idle: IdleRules,
+    pub fuzzer: Fuzzer,
+    /// colors of decoded frames by their fields
+    pub highlights: Highlights,
+    pub watchdog: Option<Watchdog>,
+    pub hooks: Hooks,
+    /// plot of numbers in RX lines, shown below RX while set
+    pub waveform: Option<Waveform>,
+    /// number of a line the waveform plots
+    pub wave_field: usize,
+    pub watches: Watches,
+    pub prompt: Option<Prompt>,
+    /// logs in to the console from the profile's `[login]`
+    pub login: Option<AutoLogin>,
+    /// passwords sent this run, masked in files
+    pub secrets: Secrets,
+    /// masks secrets and redaction rules in RX on its way to the logs
+    redacting: Redacting,
+    /// keyring entry of the secret being asked for
+    secret_name: Option<String>,
+    /// multi-line text pasted into TX, until the paste dialog says how to send it
+    pasted: Option<String>,
+    pub paste_options: paste::Options,
+    /// pasted lines being sent
+    pub paste: Option<Paste>,
+    /// the next paste is hex text to send as bytes, armed by `:hexpaste`
+    pub hex_paste: bool,
+    /// last frame sent from the hex editor, without its checksum, which `x`
+    /// opens again
+    hex_frame: Vec<u8>,
+    hex_check: Option<(Check, Endian)>,
+    /// the profile's commands sent on connecting
+    pub startup: Option<Startup>,
+    /// bytes shown by `:view` and how, switched in its pager
+    region: Option<(Vec<u8>, View)>,
+    /// baud sweep shown over the panes, taking the keys
+    pub sweep: Option<Sweep>,
+    /// G-code file being sent with `:gcode`
+    pub gcode: Option<Job>,
+    /// G-code lines are numbered and checksummed, for Marlin
+    pub gcode_checksums: bool,
+    pub snapshots: Option<Snapshots>,
+    /// logs written this session by kind, for the summary
+    pub files: Vec<(&'static str, PathBuf)>,
+    pub boot_log: Option<BootLog>,
+    /// everything received goes here while it's on
+    pub log: Option<SessionLog>,
+    /// file `O` logs to, named after the port and time if not given
+    pub log_path: Option<PathBuf>,
+    pub log_format: LogFormat,
+    /// longest time logged data waits before it's on disk
+    pub log_flush: Duration,
+    /// size the log is rotated at
+    pub log_max_size: Option<u64>,
+    /// frame templates `t` builds requests from
+    pub templates: Vec<FrameTemplate>,
+    /// index of the template whose fields are being filled in
+    template: Option<usize>,
+    pub theme: Theme,
+    pub color_depth: ColorDepth,
+    pub ctrl_c: CtrlC,
+    /// what a BEL from the device does
+    pub bell: Bell,
+    /// when the bell last rang or flashed
+    pub bell_at: Option<Instant>,
+    pub escape: Option<EscapeKey>,
+    /// the escape key was pressed, the next key is a command
+    escaped: bool,
+    /// area of the RX text on screen, updated on every draw
+    pub rx_area: Rect,
+    pub selection: Option<Selection>,
+    pub search: Option<Search>,
+    /// area of the TX text on screen, updated on every draw
+    pub tx_area: Rect,
+    /// TX history entry selected for re-sending
+    pub tx_selected: Option<usize>,
+    pub tx_scroll: Scroll,
+    pub rx_scroll: Scroll,
+    /// other views of RX, shown in its pane in its place
+    pub windows: Vec<Window>,
+    pub presets: Presets,
+    /// names of the presets turned on, in order
+    pub active_presets: Vec<String>,
+    /// line ending last detected in RX, offered to be matched once
+    eol_offered: Option<LineEnding>,
+    /// index of the window shown, `None` for RX itself
+    pub window: Option<usize>,
+    /// RX lines, or records with a decoder, arrived since RX last followed the
+    /// end or since the mark
+    pub rx_new: usize,
+    /// set with `m`, `rx_new` counts from it even while following
+    pub rx_marked: bool,
+    /// offset of the byte under the cursor of the RX hex view, while it's on
+    pub byte_cursor: Option<usize>,
+    /// other end of the byte selection, set with `v` while the byte cursor is on
+    pub byte_anchor: Option<usize>,
+    /// INSERT mode edits a line that is sent on ENTER, rather than sending every key
+    pub line_mode: bool,
+    pub line_edit: LineEdit,
+    /// pane whose scroll bar is being dragged
+    bar_drag: Option<Focus>,
+    /// first key of a two key command, like the `g` of `gg`
+    pending: Option<char>,
+    /// count typed before a command, like the 20 of `20j`
+    count: Option<u32>,
+    /// register selected with `"x` for the next yank
+    register: Option<char>,
+    /// yanked text by register name
+    pub registers: HashMap<char, String>,
+    /// written with `:notes`, saved with the session
+    pub notes: String,
+    /// first and last line of the last visual selection, for `'<,'>`
+    visual_marks: Option<(usize, usize)>,
+    /// last action, repeated by `.`
+    last_action: Option<Action>,
+    /// keys typed since entering INSERT mode
+    typed: Vec<u8>,
+    pub toasts: Toasts,
+    pub frame_times: FrameTimes,
+    pub trace: Trace,
+    pub errors: ErrorLog,
+    pub started: Instant,
+    /// feedback for the last command, shown in the status line until the next key
+    pub message: Option<String>,
     cursor: Cursor,
 }
 
 impl App {
-    pub fn new(serial: Box<dyn SerialPort>) -> Self {
+    /// smallest share of the screen a pane can be resized to, in percent
+    const MIN_SPLIT: i16 = 10;
+    /// largest count prefix, so typos can't make commands run for ages
+    const MAX_COUNT: u32 = 99_999;
+    /// NORMAL mode keys `.` repeats, commands that change something rather than move around
+    const REPEATABLE: &'static [KeyCode] = &[
+        KeyCode::Char('h'),
+        KeyCode::Char('H'),
+        KeyCode::Char('l'),
+        KeyCode::Char('c'),
+        KeyCode::Char('C'),
+        KeyCode::Char('F'),
+        KeyCode::Char('T'),
+        KeyCode::Char('d'),
+        KeyCode::Char('p'),
+        KeyCode::Char('W'),
+        KeyCode::Char('w'),
+    ];
+    /// lines scrolled per mouse wheel step
+    const WHEEL_LINES: i32 = 3;
+    /// BELs closer together than this ring once
+    const BELL_GAP: Duration = Duration::from_millis(200);
+    /// how long the screen flashes for a BEL
+    const BELL_FLASH: Duration = Duration::from_millis(150);
+
+    pub fn new(serial: Box<dyn SerialPort>, link: Link) -> Self {
         Self {
             serial,
+            link,
+            ports: Vec::new(),
             tx: Tx::new(),
             rx: Rx::new(),
             mode: Mode::Normal,
+            overlays: OverlayStack::new(),
+            focus: Focus::Rx,
+            split: 50,
+            periodic: None,
+            keepalive: None,
+            idle: IdleRules::default(),
+            fuzzer: Fuzzer::new(None),
+            highlights: Highlights::default(),
+            watchdog: None,
+            hooks: Hooks::default(),
+            waveform: None,
+            wave_field: 0,
+            watches: Watches::default(),
+            prompt: None,
+            login: None,
+            secrets: Secrets::default(),
+            redacting: Redacting::default(),
+            secret_name: None,
+            pasted: None,
+            paste_options: paste::Options::default(),
+            paste: None,
+            hex_paste: false,
+            hex_frame: Vec::new(),
+            hex_check: None,
+            startup: None,
+            region: None,
+            sweep: None,
+            gcode: None,
+            gcode_checksums: false,
+            snapshots: None,
+            files: Vec::new(),
+            boot_log: None,
+            log: None,
+            log_path: None,
+            log_format: LogFormat::Raw,
+            log_flush: Journal::INTERVAL,
+            log_max_size: None,
+            templates: Vec::new(),
+            template: None,
+            theme: Theme::default(),
+            color_depth: ColorDepth::detect(),
+            ctrl_c: CtrlC::Send,
+            bell: Bell::Ring,
+            bell_at: None,
+            escape: None,
+            escaped: false,
+            rx_area: Rect::default(),
+            selection: None,
+            search: None,
+            tx_area: Rect::default(),
+            tx_selected: None,
+            tx_scroll: Scroll::default(),
+            rx_scroll: Scroll::default(),
+            windows: Vec::new(),
+            window: None,
+            presets: Presets::default(),
+            active_presets: Vec::new(),
+            eol_offered: None,
+            rx_new: 0,
+            rx_marked: false,
+            byte_cursor: None,
+            byte_anchor: None,
+            line_mode: false,
+            line_edit: LineEdit::default(),
+            bar_drag: None,
+            pending: None,
+            count: None,
+            register: None,
+            registers: HashMap::new(),
+            notes: String::new(),
+            visual_marks: None,
+            last_action: None,
+            typed: Vec::new(),
+            toasts: Toasts::default(),
+            frame_times: FrameTimes::new(),
+            trace: Trace::new(),
+            errors: ErrorLog::new(),
+            started: Instant::now(),
+            message: None,
             cursor: Cursor::Normal,
         }
     }
-    pub fn update(&mut self, event: Option<Event>) -> Result<Control, io::Error> {
+    /// handles the input events of one frame, then reads and writes the port
+    /// once, errors along the way are logged rather than fatal
+    pub fn update(&mut self, events: Vec<Event>) -> Result<Control, io::Error> {
         let mut ctl = Control::Continue;
         let mut key_pressed = false;
-        if let Some(e) = event {
+        for error in self.errors.take_fresh() {
+            self.trace.record(trace::Kind::Error, error);
+        }
+        let mode = self.mode.name();
+        for e in events {
+            if ctl.exit() {
+                break;
+            }
             match e {
                 Event::Key(k) => {
-                    ctl = self.handle_key(k)?;
-                    key_pressed = true
+                    key_pressed = true;
+                    match self.handle_key(k) {
+                        Ok(c) => ctl = c,
+                        Err(e) => self.io_error("write", e),
+                    }
+                }
+                Event::Mouse(m) => {
+                    if let Err(e) = self.handle_mouse(m) {
+                        self.errors.push("clipboard", e);
+                    }
+                }
+                Event::Paste(text) => {
+                    key_pressed = true;
+                    match self.handle_paste(text) {
+                        Ok(c) => ctl = c,
+                        Err(e) => self.io_error("write", e),
+                    }
                 }
                 _ => (),
             }
         }
-        self.rx.recv(self.serial.as_mut())?;
+        if self.mode.name() != mode {
+            let change = format!("{mode} -> {}", self.mode.name());
+            self.trace.record(trace::Kind::Mode, change);
+        }
+        if self.link.is_down() {
+            if self.link.is_due() {
+                self.reconnect();
+            }
+            self.cursor.update(key_pressed);
+            return Ok(ctl);
+        }
+        if let Err(e) = self.tx.flush(self.serial.as_mut()) {
+            self.io_error("write", e);
+        }
+        if let Some(file) = self.tx.take_sent_file() {
+            self.toasts.info(format!("{file} sent"));
+        }
+        // a gated periodic send stays due until the device is ready
+        let held = self
+            .prompt
+            .as_ref()
+            .is_some_and(|p| p.gate && !p.is_ready(self.tx.last_write));
+        if let Some(bytes) = self
+            .periodic
+            .as_mut()
+            .filter(|_| !held)
+            .and_then(Periodic::poll)
+        {
+            if let Err(e) = self.tx.send_bytes(&bytes, self.serial.as_mut()) {
+                self.io_error("periodic", e);
+            }
+        }
+        // the next pasted line waits for the last one to be written
+        let ready = self
+            .prompt
+            .as_ref()
+            .is_some_and(|p| p.is_ready(self.tx.last_write));
+        let acked = self.tx.ack.as_ref().is_none_or(AckGate::can_send);
+        if let Some(line) = self
+            .paste
+            .as_mut()
+            .filter(|_| self.tx.pending() == 0 && acked)
+            .and_then(|p| p.poll(ready))
+        {
+            if let Some(ack) = &mut self.tx.ack {
+                ack.sent();
+            }
+            if let Err(e) = self.tx.send_raw(&line, self.serial.as_mut()) {
+                self.io_error("paste", e);
+            }
+        }
+        if let Some(line) = self.gcode.as_mut().and_then(Job::poll) {
+            if let Err(e) = self.tx.send_raw(&line, self.serial.as_mut()) {
+                self.io_error("gcode", e);
+            }
+        }
+        if let Some(job) = self.gcode.take_if(|job| job.is_done()) {
+            let secs = job.started.elapsed().as_secs();
+            self.toasts.info(format!(
+                "{} sent in {}:{:02}, {} errors",
+                job.name,
+                secs / 60,
+                secs % 60,
+                job.errors
+            ));
+        }
+        if let Some(paste) = self.paste.take_if(|p| p.is_done()) {
+            self.toasts.info(match paste.startup {
+                true => format!("sent {} startup commands", paste.total),
+                false => format!("pasted {} lines", paste.total),
+            });
+        }
+        // bytes held back by a transfer, shaping or the queue are traffic about to
+        // go out, keepalives would only pile up behind them
+        let sending = self.tx.pending() > 0
+            || self.tx.progress().is_some()
+            || self.paste.is_some()
+            || self.gcode.is_some();
+        let last_traffic = self.tx.last_write.max(self.rx.last_recv);
+        if let Some(bytes) = self
+            .keepalive
+            .as_mut()
+            .filter(|_| !sending)
+            .and_then(|k| k.poll(last_traffic))
+        {
+            if let Err(e) = self.tx.send_bytes(&bytes, self.serial.as_mut()) {
+                self.io_error("keepalive", e);
+            }
+        }
+        self.nudge();
+        if let Some(ending) = self
+            .rx
+            .line_stats
+            .detected()
+            .filter(|&e| self.eol_offered != Some(e))
+        {
+            self.eol_offered = Some(ending);
+            if !self.is_eol(ending) {
+                let name = ending.name().to_uppercase();
+                self.toasts.info(format!(
+                    "RX lines end with {name}, :eol sets TX and RX to match"
+                ));
+            }
+        }
+        // written once the data waited long enough, even if no more arrives
+        if let Some(Err(e)) = self.log.as_mut().map(SessionLog::poll) {
+            self.errors.push("log", e);
+        }
+        if let Some(Err(e)) = self.boot_log.as_mut().map(BootLog::poll) {
+            self.errors.push("boot log", e);
+        }
+        if let Some(bytes) = self.fuzzer.poll() {
+            if let Err(e) = self.fuzzer.log_case(&self.tx.wire(&bytes)) {
+                self.errors.push("fuzz log", e);
+            }
+            if let Err(e) = self.tx.send_bytes(&bytes, self.serial.as_mut()) {
+                self.io_error("fuzz", e);
+            }
+        }
+        let was_hit = self.rx.hit.is_some();
+        let records = self.rx.records.len();
+        let mut newlines = 0;
+        match self.rx.recv(self.serial.as_mut()) {
+            Ok(0) => (),
+            Ok(_) => {
+                newlines = self.rx.received().iter().filter(|&&b| b == b'\n').count();
+                let logged = match self.boot_log.is_some() || self.log.is_some() {
+                    true => self.redacting.push(&self.secrets, self.rx.received()),
+                    false => Vec::new(),
+                };
+                if let Some(boot_log) = &mut self.boot_log {
+                    if let Err(e) = boot_log.push(&logged) {
+                        self.errors.push("boot log", e);
+                    }
+                }
+                if let Some(log) = &mut self.log {
+                    let logged = match log.format {
+                        LogFormat::Text => self.rx.filter.apply(&logged),
+                        _ => Cow::Borrowed(&logged[..]),
+                    };
+                    match log.push(&logged).and_then(|_| log.rotate()) {
+                        Ok(Some(rotated)) => {
+                            let vars = vec![
+                                ("TUICOM_LOG", log.path.display().to_string()),
+                                ("TUICOM_ROTATED", rotated.display().to_string()),
+                            ];
+                            self.files.push(("log", rotated));
+                            self.hook(hooks::Event::Rotate, vars);
+                        }
+                        Ok(None) => (),
+                        Err(e) => {
+                            self.errors.push("log", e);
+                            self.log = None;
+                        }
+                    }
+                }
+                if let Some(waveform) = &mut self.waveform {
+                    waveform.push(self.rx.received());
+                }
+                self.watches.scan(self.rx.received());
+                if let Some(sweep) = &mut self.sweep {
+                    sweep.push(self.rx.received());
+                }
+                if let Some(prompt) = &mut self.prompt {
+                    prompt.scan(self.rx.received());
+                }
+                if let Some(ack) = &mut self.tx.ack {
+                    ack.scan(self.rx.received());
+                }
+                let replies = match &mut self.gcode {
+                    Some(job) => job.scan(self.rx.received()),
+                    None => Vec::new(),
+                };
+                for reply in replies {
+                    match reply {
+                        gcode::Reply::Rejected(e) | gcode::Reply::Error(e) => {
+                            self.errors.push("gcode", e)
+                        }
+                        gcode::Reply::Halted(e) => {
+                            self.gcode = None;
+                            self.toasts.error(format!("G-code stopped: {e}"));
+                            self.errors.push("gcode", e);
+                        }
+                        gcode::Reply::Ok | gcode::Reply::Resend(_) => (),
+                    }
+                }
+                if let Some(step) = self.login.as_mut().and_then(|l| l.scan(self.rx.received())) {
+                    self.login_step(step);
+                }
+                let vars = [("TUICOM_PORT", self.link.port.clone())];
+                if let Err(e) = self.hooks.scan(self.rx.received(), &vars) {
+                    self.errors.push("hook", e);
+                }
+                if let Some(quiet) = self.watchdog.as_mut().and_then(Watchdog::feed) {
+                    self.toasts
+                        .info(format!("RX back after {}s", quiet.as_secs()));
+                }
+            }
+            Err(e) => self.io_error("read", e),
+        }
+        if let Some(baud) = self.sweep.as_mut().and_then(Sweep::poll) {
+            self.sweep_to(baud);
+        }
+        if std::mem::take(&mut self.rx.bells) > 0 {
+            self.ring();
+        }
+        if let Some(dog) = &mut self.watchdog {
+            if dog.poll() {
+                let text = format!("no RX data for {}s", dog.timeout.as_secs());
+                let mut res = notify::bell();
+                if dog.notify {
+                    res = res.and_then(|_| notify::notify(&format!("tuicom: {text}")));
+                }
+                if let Err(e) = res {
+                    self.errors.push("notify", e);
+                }
+                self.toasts.error(text);
+                let silence = dog.timeout.as_secs().to_string();
+                self.hook(hooks::Event::Silence, vec![("TUICOM_SILENCE", silence)]);
+            }
+        }
+        if let Some(snapshots) = &self.snapshots {
+            if snapshots.is_due(self.rx.last_recv) {
+                self.snapshot("idle");
+            }
+        }
+        for record in self.rx.records.iter().skip(records).filter(|r| r.error) {
+            self.errors.push("decode", &record.text);
+        }
+        self.rx_new += match self.rx.decoder() {
+            Some(_) => self.rx.records.len().saturating_sub(records),
+            None => newlines,
+        };
+        if self.rx_scroll.is_following() && !self.rx_marked {
+            self.rx_new = 0;
+        }
+        if let Some(hit) = self.rx.hit.as_ref().filter(|_| !was_hit) {
+            let pattern = self.rx.breakpoints[hit.breakpoint].to_string();
+            self.toasts
+                .info(format!("breakpoint /{pattern}/ hit, SPACE resumes"));
+            self.hook(hooks::Event::Break, vec![("TUICOM_PATTERN", pattern)]);
+        }
+        for failure in self.hooks.reap() {
+            self.errors.push("hook", failure);
+        }
         self.cursor.update(key_pressed);
         Ok(ctl)
     }
+    /// stops whatever is being sent: the queue, a file transfer, and what the
+    /// OS hasn't put on the line yet where the port lets it be discarded
+    fn abort_tx(&mut self) {
+        let file = self.tx.progress().and_then(|p| p.file);
+        let dropped = self.tx.drop_queue();
+        let buffered = self.serial.bytes_to_write().unwrap_or(0);
+        let mut message = match file {
+            Some(file) => format!("aborted {file}, dropped {dropped} queued bytes"),
+            None => format!("dropped {dropped} queued bytes"),
+        };
+        if let Some(paste) = self.paste.take() {
+            message += &format!(
+                ", stopped pasting after {}/{} lines",
+                paste.sent(),
+                paste.total
+            );
+        }
+        if let Some(job) = self.gcode.take() {
+            message += &format!(
+                ", stopped {} after {}/{} lines",
+                job.name,
+                job.acked(),
+                job.total()
+            );
+        }
+        match self.serial.clear(ClearBuffer::Output) {
+            Ok(()) if buffered > 0 => message += &format!(" and {buffered} in the OS buffer"),
+            Ok(()) => (),
+            Err(e) => {
+                message += ", the OS buffer couldn't be discarded";
+                self.errors.push("abort", e);
+            }
+        }
+        self.message = Some(message);
+    }
+    /// answers a BEL from the device as `--bell` says, once however many came
+    /// in a burst
+    fn ring(&mut self) {
+        if self.bell == Bell::Off || self.bell_at.is_some_and(|at| at.elapsed() < Self::BELL_GAP) {
+            return;
+        }
+        self.bell_at = Some(Instant::now());
+        if self.bell == Bell::Ring {
+            if let Err(e) = notify::bell() {
+                self.errors.push("bell", e);
+            }
+        }
+    }
+    /// the screen is flashing for a BEL
+    pub fn is_flashing(&self) -> bool {
+        self.bell == Bell::Flash
+            && self
+                .bell_at
+                .is_some_and(|at| at.elapsed() < Self::BELL_FLASH)
+    }
+    /// logs a port error, noticing when it means the port is gone
+    fn io_error(&mut self, source: &'static str, e: io::Error) {
+        if !self.link.is_down() && is_disconnect(&e) {
+            self.link.remember(self.serial.as_ref());
+            self.link.lost(&e);
+            self.trace
+                .record(trace::Kind::Lost, format!("{}: {e}", self.link.port));
+            self.toasts
+                .error(format!("{} disconnected", self.link.port));
+            self.hook(
+                hooks::Event::Disconnect,
+                vec![("TUICOM_ERROR", e.to_string())],
+            );
+            self.snapshot("disconnect");
+        }
+        self.errors.push(source, e);
+    }
+    /// whether TX and RX are set for lines ending with `ending`
+    fn is_eol(&self, ending: LineEnding) -> bool {
+        self.tx.line_ending == ending
+            && self.tx.lf_crlf == (ending == LineEnding::CrLf)
+            && self.rx.cr_lines == (ending == LineEnding::Cr)
+    }
+    /// sets TX and RX for lines ending with `ending`: what line mode ends
+    /// lines with, whether LF goes out as CR + LF, and whether a CR breaks
+    /// RX lines
+    fn set_eol(&mut self, ending: LineEnding) {
+        self.tx.line_ending = ending;
+        self.tx.lf_crlf = ending == LineEnding::CrLf;
+        self.rx.cr_lines = ending == LineEnding::Cr;
+    }
+    /// closes window `i`, showing RX if it was shown
+    fn close_window(&mut self, i: usize) {
+        self.windows.remove(i);
+        self.window = match self.window {
+            Some(shown) if shown == i => {
+                self.rx_scroll.follow();
+                None
+            }
+            Some(shown) if shown > i => Some(shown - 1),
+            shown => shown,
+        };
+    }
+    /// bytes dropped by the presets that are on
+    fn preset_drops(&self) -> Vec<RangeInclusive<u8>> {
+        self.active_presets
+            .iter()
+            .filter_map(|name| self.presets.get(name))
+            // checked when the presets were loaded
+            .filter_map(|preset| preset.parts().ok())
+            .flat_map(|parts| parts.drop)
+            .collect()
+    }
+    /// turns the preset `name` on, adding what it has to what's there, or off,
+    /// taking it away again
+    pub fn toggle_preset(&mut self, name: &str) -> Result<(), io::Error> {
+        let Some(preset) = self.presets.get(name) else {
+            let e = format!("unknown preset `{name}`");
+            return Err(io::Error::new(io::ErrorKind::NotFound, e));
+        };
+        // checked when the presets were loaded
+        let parts = preset
+            .parts()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut ranges = self.rx.filter.ranges().to_vec();
+        if let Some(i) = self.active_presets.iter().position(|p| p == name) {
+            self.active_presets.remove(i);
+            for watch in parts.watches {
+                let watches = &mut self.watches.watches;
+                if let Some(i) = watches.iter().position(|w| {
+                    w.label == watch.label && w.pattern.as_str() == watch.pattern.as_str()
+                }) {
+                    watches.remove(i);
+                }
+            }
+            for range in parts.drop {
+                if let Some(i) = ranges.iter().position(|r| *r == range) {
+                    ranges.remove(i);
+                }
+            }
+            for kind in parts.windows {
+                if let Some(i) = self.windows.iter().position(|w| w.kind == kind) {
+                    self.close_window(i);
+                }
+            }
+            if parts.decoder.is_some() && self.rx.decoder_kind() == parts.decoder {
+                self.set_decoder(None)?;
+            }
+            self.message = Some(format!("preset {name} off"));
+        } else {
+            self.active_presets.push(name.to_owned());
+            self.watches.watches.extend(parts.watches);
+            ranges.extend(parts.drop);
+            self.windows
+                .extend(parts.windows.into_iter().map(Window::new));
+            if parts.decoder.is_some() {
+                self.set_decoder(parts.decoder)?;
+            }
+            self.message = Some(format!("preset {name} on"));
+        }
+        self.rx.filter = ByteFilter::new(ranges);
+        Ok(())
+    }
+    /// shows the next window in the RX pane, or the previous one, RX itself
+    /// coming before the first
+    fn switch_window(&mut self, next: bool) {
+        let n = self.windows.len() + 1;
+        let current = self.window.map_or(0, |i| i + 1);
+        let i = match next {
+            true => (current + 1) % n,
+            false => (current + n - 1) % n,
+        };
+        self.window = i.checked_sub(1);
+        self.rx_scroll.follow();
+    }
+    /// writes the trace to `path`, headed by what a bug report needs to know
+    fn export_trace(&mut self, path: PathBuf) {
+        let header = [
+            format!("tuicom {}", env!("CARGO_PKG_VERSION")),
+            format!("saved {}", timestamp(SystemTime::now())),
+            format!("{} at {}", self.link.port, self.link.settings),
+        ];
+        match self.trace.export(&path, &header) {
+            Ok(()) => self.message = Some(format!("trace written to {}", path.display())),
+            Err(e) => self.errors.push("trace", e),
+        }
+    }
+    /// saves RX to the snapshot directory if snapshots are on and something new arrived
+    fn snapshot(&mut self, reason: &str) {
+        let Some(snapshots) = &mut self.snapshots else {
+            return;
+        };
+        let name = profile::default_name(&self.link.port);
+        let rx = self.secrets.redact(&self.rx.contents()).into_owned();
+        match snapshots.save(&name, reason, &rx, self.rx.last_recv) {
+            Ok(Some(path)) => {
+                self.toasts.info(format!("RX saved to {}", path.display()));
+                self.files.push(("snapshot", path));
+            }
+            Ok(None) => (),
+            Err(e) => self.errors.push("snapshot", e),
+        }
+    }
+    /// starts logging RX to `log_path`, or stops it
+    pub fn toggle_log(&mut self) {
+        self.flush_logs();
+        if let Some(log) = self.log.take() {
+            self.message = Some(format!("stopped logging to {}", log.path.display()));
+            return;
+        }
+        let path = self.next_log_path();
+        match SessionLog::open(path.clone(), self.log_format, self.log_flush) {
+            Ok(mut log) => {
+                log.max_size = self.log_max_size;
+                self.message = Some(format!("logging RX to {}", path.display()));
+                if !self.files.iter().any(|(_, p)| *p == path) {
+                    self.files.push(("log", path.clone()));
+                }
+                self.log_path = Some(path);
+                self.log = Some(log);
+            }
+            Err(e) => self.errors.push("log", e),
+        }
+    }
+    /// `log_path`, or a file named after the port and the time if there's none
+    pub fn next_log_path(&self) -> PathBuf {
+        self.log_path.clone().unwrap_or_else(|| {
+            let name = profile::default_name(&self.link.port);
+            PathBuf::from(format!("{name}-{}.log", timestamp(SystemTime::now())))
+        })
+    }
+    /// writes out the RX held back for redaction, before a log is closed
+    pub fn flush_logs(&mut self) {
+        let held = self.redacting.finish(&self.secrets);
+        if let Some(boot_log) = &mut self.boot_log {
+            if let Err(e) = boot_log.push(&held) {
+                self.errors.push("boot log", e);
+            }
+        }
+        if let Some(log) = &mut self.log {
+            if let Err(e) = log.push(&held).and_then(|_| log.flush()) {
+                self.errors.push("log", e);
+            }
+        }
+    }
+    /// runs the hooks for `event`, telling them about the port besides `vars`
+    pub fn hook(&mut self, event: hooks::Event, mut vars: Vec<(&str, String)>) {
+        vars.push(("TUICOM_PORT", self.link.port.clone()));
+        if let Err(e) = self.hooks.fire(event, &vars) {
+            self.errors.push("hook", e);
+        }
+    }
+    /// acts on the idle rules due, and raises DTR again after a pulse
+    fn nudge(&mut self) {
+        if self.idle.dtr_up_at.is_some_and(|up| up <= Instant::now()) {
+            self.idle.dtr_up_at = None;
+            if let Err(e) = self.serial.write_data_terminal_ready(true) {
+                self.errors.push("idle", e);
+            }
+        }
+        for nudge in self.idle.poll(self.rx.last_recv) {
+            match nudge {
+                Nudge::Send(bytes) => {
+                    if let Err(e) = self.tx.send_bytes(&bytes, self.serial.as_mut()) {
+                        self.io_error("idle", e);
+                    }
+                }
+                Nudge::PulseDtr => match self.serial.write_data_terminal_ready(false) {
+                    Ok(()) => self.idle.dtr_up_at = Some(Instant::now() + IdleRules::PULSE),
+                    Err(e) => self.errors.push("idle", e),
+                },
+                Nudge::Hook(command) => {
+                    let vars = [("TUICOM_PORT", self.link.port.clone())];
+                    if let Err(e) = self.hooks.run(command, hooks::Event::Idle, &vars) {
+                        self.errors.push("idle", e);
+                    }
+                }
+            }
+        }
+    }
+    /// the port was opened: runs the connect hooks and starts the boot log
+    pub fn connected(&mut self) {
+        // it may be another device
+        self.rx.line_stats = LineStats::default();
+        self.eol_offered = None;
+        self.trace.record(
+            trace::Kind::Open,
+            format!("{} at {}", self.link.port, self.link.settings),
+        );
+        self.hook(hooks::Event::Connect, Vec::new());
+        if let Some(login) = &mut self.login {
+            login.rearm();
+        }
+        if let Some(startup) = self.startup.as_ref().filter(|s| !s.commands.is_empty()) {
+            let options = paste::Options {
+                line_ending: self.tx.line_ending,
+                delay: Duration::from_millis(startup.delay),
+                wait_prompt: startup.wait_prompt && self.prompt.is_some(),
+                ..paste::Options::default()
+            };
+            let lines = startup
+                .commands
+                .iter()
+                .map(|c| [c.as_bytes(), options.line_ending.bytes()].concat())
+                .collect();
+            let mut paste = Paste::new(lines, &options);
+            paste.startup = true;
+            self.paste = Some(paste);
+            if let Some(ack) = &mut self.tx.ack {
+                ack.reset();
+            }
+        }
+        if let Some(boot_log) = &mut self.boot_log {
+            match boot_log.start(&profile::default_name(&self.link.port)) {
+                Ok(path) => self.files.push(("boot log", path)),
+                Err(e) => self.errors.push("boot log", e),
+            }
+        }
+    }
+    fn login_step(&mut self, step: login::Step) {
+        let res = match step {
+            login::Step::SendUser(user) => self.tx.send_bytes(&user, self.serial.as_mut()),
+            login::Step::SendPassword(password) => self.send_password(&password),
+            login::Step::AskPassword => {
+                let entry = self.login.as_ref().and_then(|l| l.keyring.clone());
+                let stored = match entry.as_deref().map(secret::get) {
+                    Some(Ok(stored)) => stored,
+                    Some(Err(e)) => {
+                        self.errors.push("keyring", e);
+                        None
+                    }
+                    None => None,
+                };
+                match stored {
+                    Some(password) => self.send_password(&password),
+                    None => {
+                        let hint = match entry {
+                            Some(_) => "sent when the device asks, kept in the OS keyring",
+                            None => "sent when the device asks, kept until tuicom exits",
+                        };
+                        self.overlays.push(
+                            Dialog::Password,
+                            Input::new("Login", "Password").hint(hint).masked(),
+                        );
+                        Ok(())
+                    }
+                }
+            }
+            login::Step::Failed => {
+                // a stored password that was wrong is asked for again next time
+                if let Some(entry) = self.login.as_ref().and_then(|l| l.keyring.clone()) {
+                    if let Err(e) = secret::forget(&entry) {
+                        self.errors.push("keyring", e);
+                    }
+                }
+                self.toasts
+                    .error("login failed, log in by hand or reconnect to try again");
+                Ok(())
+            }
+            login::Step::Reprompted => {
+                self.toasts
+                    .error("login asked for again, log in by hand or reconnect to try again");
+                Ok(())
+            }
+        };
+        if let Err(e) = res {
+            self.io_error("login", e);
+        }
+    }
+    fn send_password(&mut self, password: &str) -> Result<(), io::Error> {
+        if let Some(login) = &mut self.login {
+            login.answer(password);
+        }
+        self.send_secret(password)
+    }
+    /// sends `secret` followed by enter, masking it in files from now on
+    fn send_secret(&mut self, secret: &str) -> Result<(), io::Error> {
+        self.secrets.add(secret);
+        let bytes = [secret.as_bytes(), login::ENTER].concat();
+        self.tx.send_secret(&bytes, self.serial.as_mut())
+    }
+    /// sends the keyring secret `name`, asking for it when it isn't stored yet
+    fn use_secret(&mut self, name: &str) -> Result<(), io::Error> {
+        match secret::get(name) {
+            Ok(Some(secret)) => return self.send_secret(&secret),
+            Ok(None) => (),
+            Err(e) => self.errors.push("keyring", e),
+        }
+        self.secret_name = Some(name.to_owned());
+        self.overlays.push(
+            Dialog::Secret,
+            Input::new("Secret", name)
+                .hint("stored in the OS keyring, then sent")
+                .masked(),
+        );
+        Ok(())
+    }
+    /// tries to reopen the port right away
+    fn reconnect(&mut self) {
+        match self.link.open() {
+            Ok(port) => {
+                self.serial = port;
+                self.link.down = None;
+                self.link.reconnects += 1;
+                self.toasts
+                    .info(format!("reconnected to {}", self.link.port));
+                self.trace.record(
+                    trace::Kind::Reconnect,
+                    format!("{} is back", self.link.port),
+                );
+                self.connected();
+            }
+            Err(e) => {
+                self.trace
+                    .record(trace::Kind::Reconnect, format!("{}: {e}", self.link.port));
+                self.link.failed(e);
+            }
+        }
+    }
+    /// opens `port` instead of the current one, keeping the line settings
+    fn switch_port(&mut self, port: String) {
+        if !self.link.is_down() {
+            self.link.remember(self.serial.as_ref());
+        }
+        let previous = std::mem::replace(&mut self.link.port, port);
+        match self.link.open() {
+            Ok(port) => {
+                self.serial = port;
+                self.link.down = None;
+                self.link.description = ports::friendly_name(&self.link.port);
+                self.toasts.info(format!("switched to {}", self.link.port));
+                self.connected();
+            }
+            Err(e) => {
+                self.toasts
+                    .error(format!("can't open {}: {e}", self.link.port));
+                self.link.port = previous;
+            }
+        }
+    }
+    /// opens the port picker, listing the ports there are now
+    fn pick_port(&mut self) {
+        match ports::names() {
+            Ok(ports) => {
+                self.ports = ports;
+                let items = ports::labels(&self.ports)
+                    .into_iter()
+                    .zip(&self.ports)
+                    .map(|(label, p)| {
+                        if *p == self.link.port {
+                            format!("{label} (current)")
+                        } else {
+                            label
+                        }
+                    })
+                    .collect();
+                self.overlays.push(
+                    Dialog::Port,
+                    Picker::new("Switch Port", items).action('r', "refresh"),
+                );
+            }
+            Err(e) => self.errors.push("ports", e),
+        }
+    }
+    /// opens the dialog for the port and its line settings, listing the ports there are now
+    fn open_settings(&mut self) {
+        if !self.link.is_down() {
+            self.link.remember(self.serial.as_ref());
+        }
+        let mut ports = match ports::names() {
+            Ok(ports) => ports,
+            Err(e) => {
+                self.errors.push("ports", e);
+                Vec::new()
+            }
+        };
+        if !ports.contains(&self.link.port) {
+            ports.insert(0, self.link.port.clone());
+        }
+        let settings = self.link.settings;
+        let fields = vec![
+            Field::new("Port", self.link.port.clone())
+                .choices(ports)
+                .accept(|c| !c.is_control())
+                .refresh(ports::names),
+            Field::new("Baud rate", settings.baud_rate.to_string())
+                .choices(link::BAUD_RATES.iter().map(u32::to_string).collect())
+                .accept(|c| c.is_ascii_digit()),
+            Field::new(
+                "Data bits",
+                link::name_of(link::DATA_BITS, settings.data_bits),
+            )
+            .choices(link::names(link::DATA_BITS)),
+            Field::new("Parity", link::name_of(link::PARITIES, settings.parity))
+                .choices(link::names(link::PARITIES)),
+            Field::new(
+                "Stop bits",
+                link::name_of(link::STOP_BITS, settings.stop_bits),
+            )
+            .choices(link::names(link::STOP_BITS)),
+            Field::new(
+                "Flow control",
+                link::name_of(link::FLOW_CONTROLS, self.link.flow_control),
+            )
+            .choices(link::names(link::FLOW_CONTROLS)),
+            Field::new("Line ending", self.tx.line_ending.name()).choices(
+                LineEnding::ALL
+                    .iter()
+                    .map(|e| e.name().to_owned())
+                    .collect(),
+            ),
+        ];
+        self.overlays
+            .push(Dialog::Settings, Form::new("Serial Settings", fields));
+    }
+    /// reconfigures the port with the settings dialog's values, reopening it
+    /// when it's another one or gone
+    fn apply_settings(&mut self, fields: &[String]) -> Result<(), String> {
+        let [port, baud, data_bits, parity, stop_bits, flow_control, line_ending] = fields else {
+            return Err(String::from("missing settings"));
+        };
+        let settings = LineSettings {
+            baud_rate: baud
+                .parse()
+                .ok()
+                .filter(|&b| b > 0)
+                .ok_or("invalid baud rate")?,
+            data_bits: link::parse_name(link::DATA_BITS, data_bits).ok_or("invalid data bits")?,
+            parity: link::parse_name(link::PARITIES, parity).ok_or("invalid parity")?,
+            stop_bits: link::parse_name(link::STOP_BITS, stop_bits).ok_or("invalid stop bits")?,
+        };
+        let flow_control =
+            link::parse_name(link::FLOW_CONTROLS, flow_control).ok_or("invalid flow control")?;
+        // not a port setting, it applies whatever happens to the port
+        self.tx.line_ending = line_ending.parse()?;
+        if *port == self.link.port && !self.link.is_down() {
+            self.link
+                .configure(self.serial.as_mut(), settings, flow_control)
+                .map_err(|e| e.to_string())?;
+            self.toasts.info(format!("{port} set to {settings}"));
+            return Ok(());
+        }
+        let previous = (
+            std::mem::replace(&mut self.link.port, port.clone()),
+            std::mem::replace(&mut self.link.settings, settings),
+            std::mem::replace(&mut self.link.flow_control, flow_control),
+        );
+        match self.link.open() {
+            Ok(serial) => {
+                self.serial = serial;
+                if self.link.down.take().is_some() && self.link.port == previous.0 {
+                    self.link.reconnects += 1;
+                }
+                self.link.description = ports::friendly_name(&self.link.port);
+                self.toasts.info(format!("opened {port} at {settings}"));
+                self.connected();
+                Ok(())
+            }
+            Err(e) => {
+                (self.link.port, self.link.settings, self.link.flow_control) = previous;
+                Err(format!("can't open {port}: {e}"))
+            }
+        }
+    }
+    /// starts sending the G-code file at `path`
+    fn start_gcode(&mut self, path: &Path) {
+        if let Some(job) = &self.gcode {
+            self.message = Some(format!("{} is being sent, X stops it", job.name));
+            return;
+        }
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.message = Some(format!("can't open {}: {e}", path.display()));
+                return;
+            }
+        };
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        let job = Job::new(name, &text, self.gcode_checksums);
+        if job.total() == 0 {
+            self.message = Some(format!("no G-code in {}", path.display()));
+            return;
+        }
+        self.gcode = Some(job);
+    }
+    /// text pasted into the terminal: several lines pasted into TX open the
+    /// paste dialog, anything else is taken as if it was typed
+    fn handle_paste(&mut self, text: String) -> Result<Control, io::Error> {
+        if self.overlays.is_empty() && std::mem::take(&mut self.hex_paste) {
+            match paste::hex(&text) {
+                Ok(bytes) => {
+                    self.tx.send_raw(&bytes, self.serial.as_mut())?;
+                    self.tx.record(&bytes);
+                    self.message = Some(format!("sent {} pasted bytes", bytes.len()));
+                    self.last_action = Some(Action::Send(bytes));
+                }
+                Err(e) => self.message = Some(format!("hex paste: {e}")),
+            }
+            return Ok(Control::Continue);
+        }
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        if self.overlays.is_empty() && self.mode.is_insert() && text.trim_end().contains('\n') {
+            self.open_paste(text);
+            return Ok(Control::Continue);
+        }
+        let mut ctl = Control::Continue;
+        for c in text.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                c => KeyCode::Char(c),
+            };
+            ctl = self.handle_key(KeyEvent::new(code, KeyModifiers::NONE))?;
+            if ctl.exit() {
+                break;
+            }
+        }
+        Ok(ctl)
+    }
+    fn open_paste(&mut self, text: String) {
+        let options = &self.paste_options;
+        let fields = vec![
+            Field::new("Comments", options.comment.as_deref().unwrap_or("keep")).choices(
+                std::iter::once("keep")
+                    .chain(paste::COMMENTS.iter().copied())
+                    .map(String::from)
+                    .collect(),
+            ),
+            Field::new(
+                "Blank lines",
+                if options.strip_blank { "strip" } else { "keep" },
+            )
+            .choices(vec![String::from("keep"), String::from("strip")]),
+            Field::new("Line ending", options.line_ending.name()).choices(
+                LineEnding::ALL
+                    .iter()
+                    .map(|e| e.name().to_owned())
+                    .collect(),
+            ),
+            Field::new("Delay (ms)", options.delay.as_millis().to_string())
+                .accept(|c| c.is_ascii_digit()),
+            Field::new(
+                "Wait for prompt",
+                if options.wait_prompt { "yes" } else { "no" },
+            )
+            .choices(vec![String::from("no"), String::from("yes")]),
+        ];
+        let title = format!("Paste {} lines", text.trim_end().lines().count());
+        self.pasted = Some(text);
+        self.overlays.push(Dialog::Paste, Form::new(title, fields));
+    }
+    /// starts sending the pasted lines the way the paste dialog says
+    fn start_paste(&mut self, fields: &[String]) -> Result<(), String> {
+        let [comment, blank, line_ending, delay, wait_prompt] = fields else {
+            return Err(String::from("missing paste options"));
+        };
+        let options = paste::Options {
+            comment: (comment != "keep").then(|| comment.clone()),
+            strip_blank: blank == "strip",
+            line_ending: line_ending.parse()?,
+            delay: Duration::from_millis(delay.parse().map_err(|_| "invalid delay")?),
+            wait_prompt: wait_prompt == "yes",
+        };
+        if options.wait_prompt && self.prompt.is_none() {
+            return Err(String::from("there's no --prompt to wait for"));
+        }
+        let Some(text) = self.pasted.take() else {
+            return Ok(());
+        };
+        let lines = options.lines(&text);
+        if lines.is_empty() {
+            self.message = Some(String::from("nothing left to paste"));
+        } else {
+            self.paste = Some(Paste::new(lines, &options));
+            if let Some(ack) = &mut self.tx.ack {
+                ack.reset();
+            }
+        }
+        self.paste_options = options;
+        Ok(())
+    }
     fn handle_key(&mut self, key: KeyEvent) -> Result<Control, io::Error> {
         use KeyCode as K;
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let pending = self.pending.take();
+        let count = self.count.take();
+        let n = count.unwrap_or(1) as i32;
+        let register = self.register.take();
+        self.message = None;
+        if let Some(overlay) = self.overlays.top_mut() {
+            return match overlay.handle_key(key) {
+                Outcome::Continue => Ok(Control::Continue),
+                Outcome::Cancel => {
+                    self.overlays.pop();
+                    Ok(Control::Continue)
+                }
+                Outcome::Submit(reply) => {
+                    let (dialog, mut overlay) = self.overlays.pop().unwrap();
+                    match self.submit(dialog, reply)? {
+                        Ok(ctl) => Ok(ctl),
+                        Err(e) => {
+                            overlay.set_error(e);
+                            self.overlays.push_boxed(dialog, overlay);
+                            Ok(Control::Continue)
+                        }
+                    }
+                }
+            };
+        }
+        if let Some(escape) = self.escape.filter(|_| self.mode.is_insert()) {
+            if std::mem::take(&mut self.escaped) {
+                return self.handle_escaped_key(key, escape);
+            }
+            if escape.matches(key) {
+                self.escaped = true;
+                return Ok(Control::Continue);
+            }
+        }
+        if ctrl && key.code == K::Char('c') {
+            return self.ctrl_c();
+        }
+        if self.sweep.is_some() && !ctrl {
+            self.handle_sweep_key(key.code);
+            return Ok(Control::Continue);
+        }
+        if self.byte_cursor.is_some()
+            && self.mode.is_normal()
+            && !ctrl
+            && pending.is_none()
+            && self.handle_byte_cursor_key(key.code, count)
+        {
+            return Ok(Control::Continue);
+        }
+        if self.mode.is_normal() && !ctrl && Self::REPEATABLE.contains(&key.code) {
+            self.last_action = Some(Action::Key(key));
+        }
         match &mut self.mode {
+            Mode::Insert if self.line_mode => self.handle_line_key(key, pending)?,
             Mode::Insert => match key.code {
+                K::Esc if self.escape.is_some() => self.type_control(0x1B)?,
                 K::Esc => self.leave_insert(),
+                K::Char(c) if pending == Some('r') => {
+                    if let Some(text) = self.registers.get(&c).cloned() {
+                        if text.len() > Tx::LARGE_SEND {
+                            self.tx.begin_transfer(self.serial.as_ref());
+                        }
+                        for b in text.bytes() {
+                            self.type_byte(b)?;
+                        }
+                    }
+                }
+                K::Char('r') if ctrl && self.escape.is_none() => self.pending = Some('r'),
+                K::Char(c @ 'a'..='z') if ctrl && self.escape.is_some() => {
+                    self.type_control(c as u8 - b'a' + 1)?
+                }
                 K::Char(c) => {
                     let mut buf = [0; 4];
                     for &b in c.encode_utf8(&mut buf).as_bytes() {
-                        self.tx.send(b, self.serial.as_mut())?;
+                        self.type_byte(b)?;
                     }
                 }
-                K::Tab => self.tx.send(b'\t', self.serial.as_mut())?,
-                K::Enter => self.tx.send(b'\n', self.serial.as_mut())?,
+                K::Tab => self.type_byte(b'\t')?,
+                // hex input has no newline to end a line with
+                K::Enter if self.tx.is_hex() => self.tx.end_line(),
+                K::Enter => self.type_byte(b'\n')?,
                 _ => (),
             },
             Mode::Normal => match key.code {
-                K::Esc | KeyCode::Char('q') => self.mode = Mode::WannaQuit,
+                K::Char(c) if pending == Some('"') && is_register(c) => {
+                    self.register = Some(c);
+                    self.count = count;
+                }
+                K::Esc if self.selection.is_some() => self.selection = None,
+                K::Esc if self.search.is_some() => self.search = None,
+                K::Esc if self.tx_selected.is_some() => self.tx_selected = None,
+                K::Up if self.focus == Focus::Tx => self.select_tx(-n),
+                K::Down if self.focus == Focus::Tx => self.select_tx(n),
+                K::Enter if self.tx_selected.is_some() => {
+                    let entry = self.selected_tx_entry();
+                    for _ in 0..n {
+                        self.tx.send_raw(&entry, self.serial.as_mut())?;
+                    }
+                    self.tx.record(&entry);
+                    self.last_action = Some(Action::Send(entry));
+                }
+                K::Char('e') if self.tx_selected.is_some() => {
+                    let entry = self.selected_tx_entry();
+                    self.overlays.push(
+                        Dialog::EditTx,
+                        Input::new("Edit and Send", "Send")
+                            .hint("escapes like \\r, \\n and \\xNN work, ENTER sends")
+                            .value(periodic::escape(&entry))
+                            .size(60, 20),
+                    );
+                }
+                K::Char('r') => match self.tx.last_user().map(<[u8]>::to_vec) {
+                    Some(entry) => {
+                        // as it went out the first time, line ending included
+                        for _ in 0..n {
+                            self.tx.send_raw(&entry, self.serial.as_mut())?;
+                        }
+                        self.message = Some(format!("sent again: {}", periodic::escape(&entry)));
+                        self.last_action = Some(Action::Send(entry));
+                    }
+                    None => self.message = Some(String::from("nothing sent yet")),
+                },
+                K::Esc | KeyCode::Char('q') => self.overlays.push(
+                    Dialog::Quit,
+                    Confirm::new("Quit", "Are you sure you want to quit?"),
+                ),
+                K::Char('y') if self.selection.is_some() => self.yank_to(register)?,
+                K::Char('y') if pending == Some('y') => {
+                    let (_, rows) = self.rx_lines();
+                    let from = self.rx_visible(&rows).0;
+                    self.yank_lines(from, from + n as usize - 1, register)?;
+                }
+                K::Char('y') => {
+                    self.pending = Some('y');
+                    self.count = count;
+                    self.register = register;
+                }
+                K::Char('"') => {
+                    self.pending = Some('"');
+                    self.count = count;
+                }
+                K::Char('v') => self.enter_visual(false),
+                K::Char('V') => self.enter_visual(true),
+                K::Char(':') => {
+                    self.mode = Mode::CommandInput {
+                        input: String::new(),
+                        error: None,
+                    }
+                }
                 K::Char('i') => self.enter_insert(),
+                K::Char('.') => self.repeat(n)?,
+                K::Tab => self.focus = self.focus.next(),
+                K::BackTab => self.focus = self.focus.prev(),
+                K::Up if ctrl => self.resize_split(-5),
+                K::Down if ctrl => self.resize_split(5),
+                K::Char('=') => self.split = 50,
+                K::Char(c @ '0'..='9') if count.is_some() || c != '0' => {
+                    let digit = c.to_digit(10).unwrap();
+                    self.count = Some((count.unwrap_or(0) * 10 + digit).min(Self::MAX_COUNT));
+                    self.register = register;
+                }
+                K::Char('j') => self.scroll_mut(self.focus).scroll_by(n),
+                K::Char('k') => self.scroll_mut(self.focus).scroll_by(-n),
+                K::Char('g') if pending == Some('g') => self.rx_scroll.scroll_to_top(),
+                K::Char('t') if pending == Some('g') => self.switch_window(true),
+                K::Char('T') if pending == Some('g') => self.switch_window(false),
+                K::Char('g') => self.pending = Some('g'),
+                K::Char('G') => self.rx_scroll.follow(),
+                K::Char(c @ ('/' | '?')) => {
+                    self.mode = Mode::SearchInput {
+                        input: String::new(),
+                        backward: c == '?',
+                        error: None,
+                    }
+                }
+                K::Char('n') => self.search_next(false, n as usize),
+                K::Char('N') => self.search_next(true, n as usize),
+                K::Char('d') if ctrl => self.scroll_half_pages(n),
+                K::Char('u') if ctrl => self.scroll_half_pages(-n),
+                K::Char('f') if ctrl => self.scroll_half_pages(2 * n),
+                K::Char('b') if ctrl => self.scroll_half_pages(-2 * n),
                 K::Char('h') => self.rx.display.switch_hex(),
                 K::Char('H') => self.tx.display.switch_hex(),
                 K::Char('l') => self.tx.lf_crlf = !self.tx.lf_crlf,
-                K::Char('c') => self.rx.display.clear(),
-                K::Char('C') => self.tx.display.clear(),
-                K::Char('b') => self.mode = Mode::BaudInput(String::with_capacity(8)),
+                K::Char('m') => {
+                    self.rx_marked = true;
+                    self.rx_new = 0;
+                    self.message = Some(String::from("mark set, M removes it"));
+                }
+                K::Char('M') => self.rx_marked = false,
+                K::Char('B') => self.start_byte_cursor(),
+                K::Char('O') => self.toggle_log(),
+                K::Char('U') => self.overlays.push(
+                    Dialog::SendFile,
+                    Input::new("Send File", "File")
+                        .hint("sent as it is, X cancels")
+                        .size(60, 20),
+                ),
+                K::Char('L') => {
+                    self.line_mode = !self.line_mode;
+                    self.message = Some(match self.line_mode {
+                        true => format!("line mode, lines end with {}", self.tx.line_ending),
+                        false => String::from("keys are sent as they are typed"),
+                    });
+                }
+                K::Char('D') if !self.watches.is_empty() => {
+                    self.watches.shown = !self.watches.shown
+                }
+                K::Char('c') => {
+                    self.rx.clear();
+                    self.rx_new = 0;
+                    if let Some(waveform) = &mut self.waveform {
+                        waveform.clear();
+                    }
+                    self.rx_scroll.follow();
+                    if let Some(search) = &mut self.search {
+                        search.line = None;
+                    }
+                }
+                K::Char('C') => {
+                    self.tx.clear();
+                    self.tx_selected = None;
+                    self.tx_scroll.follow();
+                }
+                K::Char('b') => self.overlays.push(
+                    Dialog::Baud,
+                    Input::new("Baud Rate", "Baud rate")
+                        .accept(|c| c.is_ascii_digit())
+                        .size(20, 20),
+                ),
+                K::Char('F') => self.fuzzer.toggle(),
+                K::Char('X') => self.abort_tx(),
+                K::Char('R') if self.link.is_down() => self.reconnect(),
+                K::Char('P') => self.pick_port(),
+                K::Char('w') => {
+                    let display = match self.focus {
+                        Focus::Tx => &mut self.tx.display,
+                        Focus::Rx => &mut self.rx.display,
+                    };
+                    display.wrap = !display.wrap;
+                }
+                K::Char('W') => {
+                    self.waveform = match self.waveform {
+                        Some(_) => None,
+                        None => Some(Waveform::new(self.wave_field)),
+                    }
+                }
+                K::F(12) => self.frame_times.shown = !self.frame_times.shown,
+                K::Char('S') => self.open_settings(),
+                K::Char('x') => self.overlays.push(
+                    Dialog::HexEdit,
+                    HexEditor::new("Compose Frame", self.hex_frame.clone()).check(self.hex_check),
+                ),
+                K::Char('I') => self.overlays.push(
+                    Dialog::Device,
+                    Pager::new("Device", self.link.describe(self.serial.as_ref())),
+                ),
+                K::Char('E') => self.overlays.push(
+                    Dialog::Errors,
+                    Pager::new("Errors", self.errors.lines()).action('c', "clear"),
+                ),
+                K::Char(' ') => self.rx.resume(),
+                K::Char('T') => self.rx.toggle_term(),
+                K::Char('d') => self.set_decoder(DecoderKind::cycle(self.rx.decoder_kind()))?,
+                K::Char('f') => {
+                    if let Some(hint) = self.rx.decoder().and_then(|d| d.encode_hint()) {
+                        self.overlays.push(
+                            Dialog::Frame,
+                            Input::new("Send Frame", "Frame").hint(hint).size(50, 20),
+                        );
+                    }
+                }
+                K::Char('t') => {
+                    if self.templates.is_empty() {
+                        self.message = Some(String::from("no frame templates"));
+                    } else {
+                        let names = self.templates.iter().map(|t| t.name.clone()).collect();
+                        self.overlays
+                            .push(Dialog::Templates, Picker::new("Frame Templates", names));
+                    }
+                }
+                K::Char('p') => {
+                    if let Some(p) = &mut self.periodic {
+                        p.toggle();
+                    }
+                }
 
                 _ => (),
             },
-            Mode::WannaQuit => match key.code {
-                K::Esc | K::Char('n' | 'q') => self.mode = Mode::Normal,
-                K::Char('y') => return Ok(Control::Exit),
-                _ => (),
-            },
-            Mode::BaudInput(buf) => match key.code {
+            Mode::Visual(v) => {
+                let v = *v;
+                self.handle_visual_key(key.code, v, pending, count, register)?;
+            }
+            Mode::CommandInput { input, error } => match key.code {
                 K::Esc => self.mode = Mode::Normal,
-                K::Char(c @ '0'..='9') => buf.push(c),
-                K::Enter => {
-                    self.serial.set_baud_rate(buf.parse().unwrap())?;
-                    self.mode = Mode::Normal;
+                K::Char(c) => input.push(c),
+                K::Backspace => {
+                    input.pop();
                 }
+                K::Enter => match input.parse::<Command>() {
+                    Ok(command) => {
+                        self.mode = Mode::Normal;
+                        self.run_command(command)?;
+                    }
+                    Err(e) => *error = Some(e),
+                },
+                _ => (),
+            },
+            Mode::SearchInput {
+                input,
+                backward,
+                error,
+            } => match key.code {
+                K::Esc => self.mode = Mode::Normal,
+                K::Char(c) => input.push(c),
                 K::Backspace => {
-                    buf.pop();
+                    input.pop();
                 }
+                K::Enter => match Regex::new(input) {
+                    Ok(regex) => {
+                        self.search = Some(Search::new(regex, *backward));
+                        self.mode = Mode::Normal;
+                        self.search_next(false, 1);
+                    }
+                    Err(e) => *error = Some(e.to_string()),
+                },
                 _ => (),
             },
-            _ => (),
         }
         Ok(Control::Continue)
     }
 
+    /// acts on the reply of a dialog, an `Err` rejects it and keeps the dialog open
+    fn submit(
+        &mut self,
+        dialog: Dialog,
+        reply: Reply,
+    ) -> Result<Result<Control, String>, io::Error> {
+        match (dialog, reply) {
+            (Dialog::Quit, Reply::Yes) => return Ok(Ok(Control::Exit)),
+            (Dialog::Errors, Reply::Action('c')) => self.errors.clear(),
+            (Dialog::Trace, Reply::Action('c')) => self.trace.clear(),
+            (Dialog::Notes, Reply::Text(notes)) => self.notes = notes,
+            (Dialog::Trace, Reply::Action('w')) => {
+                let name = profile::default_name(&self.link.port);
+                let path = format!("{name}-{}-trace.log", timestamp(SystemTime::now()));
+                self.export_trace(PathBuf::from(path));
+            }
+            (Dialog::Region, Reply::Action(key)) => {
+                if let Some((bytes, view)) = self.region.take() {
+                    let view = match key {
+                        'd' => {
+                            let current = match view {
+                                View::Decoded(kind) => Some(kind),
+                                _ => None,
+                            };
+                            DecoderKind::cycle(current)
+                                .or(DecoderKind::ALL.first().copied())
+                                .map_or(view, View::Decoded)
+                        }
+                        _ => View::KEYS
+                            .iter()
+                            .find(|&&(k, _)| k == key)
+                            .map_or(view, |&(_, v)| v),
+                    };
+                    self.show_region(bytes, view);
+                }
+            }
+            (Dialog::Port, Reply::Action('r')) => self.pick_port(),
+            (Dialog::Port, Reply::Choice(i)) => {
+                let port = self.ports[i].clone();
+                self.switch_port(port);
+            }
+            (Dialog::Settings, Reply::Fields(fields)) => {
+                if let Err(e) = self.apply_settings(&fields) {
+                    return Ok(Err(e));
+                }
+            }
+            (Dialog::Password, Reply::Text(password)) => {
+                if let Some(entry) = self.login.as_ref().and_then(|l| l.keyring.clone()) {
+                    if let Err(e) = secret::set(&entry, &password) {
+                        self.errors.push("keyring", e);
+                    }
+                }
+                self.send_password(&password)?;
+            }
+            (
+                Dialog::HexEdit,
+                Reply::Frame {
+                    bytes,
+                    check,
+                    frame,
+                },
+            ) => {
+                if frame.is_empty() {
+                    return Ok(Err(String::from("nothing to send")));
+                }
+                self.hex_frame = bytes;
+                self.hex_check = check;
+                self.resend(frame)?;
+            }
+            (Dialog::Paste, Reply::Fields(fields)) => {
+                if let Err(e) = self.start_paste(&fields) {
+                    return Ok(Err(e));
+                }
+            }
+            (Dialog::Secret, Reply::Text(secret)) => {
+                if let Some(name) = self.secret_name.take() {
+                    if let Err(e) = secret::set(&name, &secret) {
+                        self.errors.push("keyring", e);
+                    }
+                }
+                self.send_secret(&secret)?;
+            }
+            (Dialog::SendFile, Reply::Text(path)) => {
+                let path = PathBuf::from(path.trim());
+                let bytes = match std::fs::read(&path) {
+                    Ok(bytes) => bytes,
+                    Err(e) => return Ok(Err(e.to_string())),
+                };
+                let name = path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |n| n.to_string_lossy().into_owned(),
+                );
+                self.toasts
+                    .info(format!("sending {name}, {} bytes", bytes.len()));
+                self.tx.send_file(name, &bytes, self.serial.as_mut())?;
+            }
+            (Dialog::Baud, Reply::Text(baud)) => match baud.parse() {
+                Ok(0) | Err(_) => return Ok(Err(String::from("invalid baud rate"))),
+                Ok(baud) => {
+                    if let Err(e) = self.set_baud(baud) {
+                        return Ok(Err(format!("invalid baud rate: {e}")));
+                    }
+                    self.toasts.info(format!("baud rate set to {baud}"));
+                }
+            },
+            (Dialog::EditTx, Reply::Text(input)) => match input.parse::<Template>() {
+                Ok(template) => {
+                    // the entry is edited as it went out, so it goes out as is
+                    let bytes = template.render(0);
+                    self.tx.send_raw(&bytes, self.serial.as_mut())?;
+                    self.tx.record(&bytes);
+                    self.tx_selected = None;
+                    self.last_action = Some(Action::Send(bytes));
+                }
+                Err(e) => return Ok(Err(e.to_string())),
+            },
+            (Dialog::Templates, Reply::Choice(i)) => {
+                let template = &self.templates[i];
+                let fields = template
+                    .inputs()
+                    .map(|f| {
+                        Field::new(
+                            format!("{} ({})", f.name, f.kind),
+                            f.default.clone().unwrap_or_default(),
+                        )
+                        .accept(|c| !c.is_control())
+                    })
+                    .collect::<Vec<_>>();
+                if fields.is_empty() {
+                    // nothing to fill in, the frame is fixed
+                    return self.send_template(i, &[]);
+                }
+                let title = template.name.clone();
+                self.template = Some(i);
+                self.overlays
+                    .push(Dialog::TemplateFields, Form::new(title, fields));
+            }
+            (Dialog::TemplateFields, Reply::Fields(values)) => {
+                if let Some(i) = self.template {
+                    return self.send_template(i, &values);
+                }
+            }
+            (Dialog::Frame, Reply::Text(input)) => {
+                let Some(decoder) = self.rx.decoder() else {
+                    return Ok(Ok(Control::Continue));
+                };
+                match decoder.encode(&input) {
+                    Ok(bytes) => {
+                        self.tx.send_raw(&bytes, self.serial.as_mut())?;
+                        self.tx.record(&bytes);
+                        self.last_action = Some(Action::Send(bytes));
+                    }
+                    Err(e) => return Ok(Err(e)),
+                }
+            }
+            _ => (),
+        }
+        Ok(Ok(Control::Continue))
+    }
+    /// builds the frame of template `i` from the values of its fields and sends it
+    fn send_template(
+        &mut self,
+        i: usize,
+        values: &[String],
+    ) -> Result<Result<Control, String>, io::Error> {
+        match self.templates[i].build(values) {
+            Ok(bytes) => {
+                // a binary frame, mapping LF would break it and its checksum
+                self.tx.send_raw(&bytes, self.serial.as_mut())?;
+                self.tx.record(&bytes);
+                self.last_action = Some(Action::Send(bytes));
+                Ok(Ok(Control::Continue))
+            }
+            Err(e) => Ok(Err(e)),
+        }
+    }
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<(), io::Error> {
+        let area = self.rx_area;
+        let pos = (mouse.column, mouse.row);
+        let over_bar = Focus::ALL
+            .iter()
+            .copied()
+            .find(|&f| contains(self.scroll(f).bar, pos));
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if over_bar.is_some() => {
+                self.bar_drag = over_bar;
+                self.selection = None;
+                self.scroll_mut(over_bar.unwrap()).drag_to(pos.1);
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.bar_drag.is_some() => {
+                self.scroll_mut(self.bar_drag.unwrap()).drag_to(pos.1);
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.bar_drag.is_some() => {
+                self.bar_drag = None;
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let delta = if mouse.kind == MouseEventKind::ScrollUp {
+                    -Self::WHEEL_LINES
+                } else {
+                    Self::WHEEL_LINES
+                };
+                let pane = if contains(area, pos) {
+                    Focus::Rx
+                } else {
+                    Focus::Tx
+                };
+                self.scroll_mut(pane).scroll_by(delta);
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.selection = contains(area, pos).then(|| Selection::new(pos));
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(sel) = self.selection.as_mut().filter(|s| s.dragging) {
+                    // keep dragging past the edges of the pane
+                    sel.head = (
+                        pos.0.clamp(area.left(), area.right().saturating_sub(1)),
+                        pos.1.clamp(area.top(), area.bottom().saturating_sub(1)),
+                    );
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => match &mut self.selection {
+                Some(sel) if sel.anchor == sel.head => self.selection = None,
+                Some(sel) => {
+                    sel.dragging = false;
+                    let n = sel.text.chars().count();
+                    self.yank()?;
+                    self.toasts.info(format!("copied {n} characters"));
+                }
+                None => (),
+            },
+            _ => (),
+        }
+        Ok(())
+    }
+    pub fn scroll(&self, pane: Focus) -> &Scroll {
+        match pane {
+            Focus::Tx => &self.tx_scroll,
+            Focus::Rx => &self.rx_scroll,
+        }
+    }
+    pub fn scroll_mut(&mut self, pane: Focus) -> &mut Scroll {
+        match pane {
+            Focus::Tx => &mut self.tx_scroll,
+            Focus::Rx => &mut self.rx_scroll,
+        }
+    }
+    /// count and first key of a command being typed, shown like vim's showcmd
+    pub fn pending_keys(&self) -> String {
+        let mut keys = match self.escape.filter(|_| self.escaped) {
+            Some(escape) => escape.to_string(),
+            None => String::new(),
+        };
+        keys.extend(self.register.map(|r| format!("\"{r}")));
+        if let Some(count) = self.count {
+            keys.push_str(&count.to_string());
+        }
+        keys.extend(self.pending);
+        keys
+    }
+    /// scrolls the focused pane by half its height per step, like vim's Ctrl+D;
+    /// two steps are a whole page even when the height is odd
+    pub fn scroll_half_pages(&mut self, steps: i32) {
+        let scroll = self.scroll_mut(self.focus);
+        let rows = steps * scroll.bar.height as i32 / 2;
+        scroll.scroll_by(if rows == 0 { steps.signum() } else { rows });
+    }
+    /// jumps `count` matches of the current search ahead, in the opposite direction if `reverse`
+    pub fn search_next(&mut self, reverse: bool, count: usize) {
+        let Some(mut search) = self.search.take() else {
+            return;
+        };
+        let (lines, rows) = self.rx_lines();
+        let visible = self.rx_visible(&rows);
+        if let Some(line) = search.next(&lines, visible, reverse, count) {
+            self.rx_scroll.scroll_to(rows[line]);
+        }
+        self.search = Some(search);
+    }
+    /// RX lines as searched and yanked, with the row of the pane each starts at
+    fn rx_lines(&self) -> (Vec<&str>, Vec<usize>) {
+        // decoded records are one row each, the terminal view has no lines to work with
+        if self.rx.decoder().is_some() {
+            let lines = self.rx.records.iter().map(|r| r.text.as_str()).collect();
+            (lines, (0..self.rx.records.len()).collect())
+        } else if self.rx.is_term() {
+            (Vec::new(), Vec::new())
+        } else {
+            let display = &self.rx.display;
+            let mut rows = display.rows(self.rx_area.width, display.wrap).to_vec();
+            rows.pop();
+            (display.lines().collect(), rows)
+        }
+    }
+    /// first and last RX line in view, given the rows from `rx_lines`
+    fn rx_visible(&self, rows: &[usize]) -> (usize, usize) {
+        let top = self.rx_scroll.offset();
+        let bottom = top + self.rx_scroll.bar.height as usize;
+        (
+            rows.partition_point(|&r| r <= top).saturating_sub(1),
+            rows.partition_point(|&r| r < bottom).saturating_sub(1),
+        )
+    }
+    /// yanks RX lines `from..=to` into `register` and the clipboard
+    fn yank_lines(
+        &mut self,
+        from: usize,
+        to: usize,
+        register: Option<char>,
+    ) -> Result<(), io::Error> {
+        let (lines, _) = self.rx_lines();
+        let (from, to) = (from.min(to), from.max(to));
+        let Some(lines) = lines.get(from..=to.min(lines.len().saturating_sub(1))) else {
+            self.message = Some(String::from("nothing to yank"));
+            return Ok(());
+        };
+        let mut text = lines.join("\n");
+        text.push('\n');
+        let n = lines.len();
+        self.store(register, text)?;
+        self.message = Some(match n {
+            1 => String::from("1 line yanked"),
+            n => format!("{n} lines yanked"),
+        });
+        Ok(())
+    }
+    /// discards what the OS buffers for the port, like stale data from before a
+    /// device reset
+    fn flush_buffers(&mut self, buffer: Buffer) {
+        let (clear, rx, tx) = match buffer {
+            Buffer::Rx => (ClearBuffer::Input, true, false),
+            Buffer::Tx => (ClearBuffer::Output, false, true),
+            Buffer::All => (ClearBuffer::All, true, true),
+        };
+        let mut counts = Vec::new();
+        if rx {
+            counts.push(format!("{} RX", self.serial.bytes_to_read().unwrap_or(0)));
+        }
+        if tx {
+            counts.push(format!("{} TX", self.serial.bytes_to_write().unwrap_or(0)));
+        }
+        match self.serial.clear(clear) {
+            Ok(()) => {
+                let counts = counts.join(" and ");
+                self.message = Some(format!("discarded {counts} bytes buffered by the OS"));
+            }
+            Err(e) => self.message = Some(format!("can't flush: {e}")),
+        }
+    }
+    /// puts yanked text into a register, the unnamed register and the clipboard
+    fn store(&mut self, register: Option<char>, text: String) -> Result<(), io::Error> {
+        clipboard::copy(&text)?;
+        if let Some(r) = register.filter(|&r| r != '"') {
+            self.registers.insert(r, text.clone());
+        }
+        self.registers.insert('"', text);
+        Ok(())
+    }
+    fn run_command(&mut self, command: Command) -> Result<(), io::Error> {
+        let range = match &command {
+            Command::Yank { range, .. } | Command::Write { range, .. } => *range,
+            Command::YankBytes { format, register } => return self.yank_bytes(*format, *register),
+            Command::WriteBytes { path } => return self.write_bytes(path),
+            Command::SaveSession { path } => {
+                self.save_session(path);
+                return Ok(());
+            }
+            Command::Diff { path } => {
+                match diff::load(path) {
+                    Ok(other) => {
+                        let name = path.display().to_string();
+                        let view = DiffView::new(
+                            &name,
+                            &diff::lines(&other),
+                            &diff::lines(&self.rx.contents()),
+                        );
+                        self.overlays.push(Dialog::Diff, view);
+                    }
+                    Err(e) => self.message = Some(format!("can't open {}: {e}", path.display())),
+                }
+                return Ok(());
+            }
+            Command::Timeline { path } => {
+                match timeline::load(path) {
+                    Ok(other) => {
+                        let arrivals = self
+                            .rx
+                            .display
+                            .arrivals()
+                            .iter()
+                            .map(|&(offset, time)| (offset, session::to_millis(time)))
+                            .collect::<Vec<_>>();
+                        let rx = timeline::lines(&self.rx.contents(), &arrivals);
+                        let name = path.file_name().unwrap_or(path.as_os_str());
+                        let view = TimelineView::new(&name.to_string_lossy(), rx, other);
+                        self.overlays.push(Dialog::Timeline, view);
+                    }
+                    Err(e) => self.message = Some(format!("can't open {}: {e}", path.display())),
+                }
+                return Ok(());
+            }
+            Command::LoadSession { path } => {
+                if let Err(e) = Session::load(path).and_then(|s| self.restore_session(s)) {
+                    self.message = Some(format!("can't open {}: {e}", path.display()));
+                }
+                return Ok(());
+            }
+            Command::Flush { buffer } => {
+                self.flush_buffers(*buffer);
+                return Ok(());
+            }
+            Command::Secret { name } => return self.use_secret(name),
+            Command::GCode { path } => {
+                self.start_gcode(path);
+                return Ok(());
+            }
+            Command::View { range: None, view } if self.byte_cursor.is_some() => {
+                match self.selected_bytes() {
+                    Some(bytes) => self.show_region(bytes.to_vec(), *view),
+                    None => self.message = Some(String::from("no bytes selected")),
+                }
+                return Ok(());
+            }
+            Command::View { .. } if self.rx.decoder().is_some() || self.rx.is_term() => {
+                self.message = Some(String::from("regions are read from the text or hex view"));
+                return Ok(());
+            }
+            Command::View { range, .. } => range.unwrap_or(Range::CURRENT),
+            Command::Sweep { rates } => {
+                self.start_sweep(rates);
+                return Ok(());
+            }
+            Command::Window { kind } => {
+                self.windows.push(Window::new(kind.clone()));
+                self.window = Some(self.windows.len() - 1);
+                self.rx_scroll.follow();
+                return Ok(());
+            }
+            Command::CloseWindow => {
+                match self.window {
+                    Some(i) => self.close_window(i),
+                    None => self.message = Some(String::from("RX itself can't be closed")),
+                }
+                return Ok(());
+            }
+            Command::Eol { ending } => {
+                match ending.or(self.rx.line_stats.detected()) {
+                    Some(ending) => {
+                        self.set_eol(ending);
+                        let name = ending.name().to_uppercase();
+                        self.message = Some(format!("TX and RX set to {name} line endings"));
+                    }
+                    None => {
+                        self.message = Some(String::from("no line ending detected yet"));
+                    }
+                }
+                return Ok(());
+            }
+            Command::HexPaste => {
+                self.hex_paste = !self.hex_paste;
+                self.message = Some(String::from(match self.hex_paste {
+                    true => "paste hex text to send it as bytes",
+                    false => "pastes are sent as text again",
+                }));
+                return Ok(());
+            }
+            Command::Preset { name: None } => {
+                let names = self
+                    .presets
+                    .presets
+                    .iter()
+                    .map(|p| match self.active_presets.contains(&p.name) {
+                        true => format!("{} (on)", p.name),
+                        false => p.name.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                self.message = Some(match names.is_empty() {
+                    true => String::from("no presets"),
+                    false => format!("presets: {}", names.join(", ")),
+                });
+                return Ok(());
+            }
+            Command::Preset { name: Some(name) } => {
+                if let Err(e) = self.toggle_preset(name) {
+                    self.message = Some(e.to_string());
+                }
+                return Ok(());
+            }
+            Command::Notes => {
+                self.overlays
+                    .push(Dialog::Notes, TextArea::new("Notes", &self.notes));
+                return Ok(());
+            }
+            Command::Trace { path } => {
+                match path {
+                    Some(path) => self.export_trace(path.clone()),
+                    None => self.overlays.push(
+                        Dialog::Trace,
+                        Pager::new("Trace", self.trace.lines())
+                            .action('w', "save")
+                            .action('c', "clear"),
+                    ),
+                }
+                return Ok(());
+            }
+            Command::Drop { ranges } => {
+                // the presets that are on keep dropping their bytes
+                let mut ranges = ranges.clone();
+                ranges.extend(self.preset_drops());
+                self.rx.filter = ByteFilter::new(ranges);
+                self.message = Some(match self.rx.filter.is_empty() {
+                    true => String::from("showing every byte"),
+                    false => format!("dropping {} from now on", self.rx.filter),
+                });
+                return Ok(());
+            }
+        };
+        let (lines, rows) = self.rx_lines();
+        let context = Lines {
+            current: self.rx_visible(&rows).0,
+            len: lines.len(),
+            visual: self.visual_marks,
+        };
+        let (from, to) = match range.resolve(context) {
+            Ok(range) => range,
+            Err(e) => {
+                self.message = Some(e);
+                return Ok(());
+            }
+        };
+        match command {
+            Command::Yank { register, .. } => self.yank_lines(from, to, register),
+            Command::View { view, .. } => {
+                let display = &self.rx.display;
+                if to >= display.line_count() {
+                    self.message = Some(String::from("nothing to view"));
+                    return Ok(());
+                }
+                let end = match to + 1 < display.line_count() {
+                    true => display.line_offset(to + 1),
+                    false => display.bytes().len(),
+                };
+                let bytes = display.bytes()[display.line_offset(from)..end].to_vec();
+                self.show_region(bytes, view);
+                Ok(())
+            }
+            Command::Write { path, .. } => {
+                let Some(lines) = lines.get(from..=to) else {
+                    self.message = Some(String::from("nothing to write"));
+                    return Ok(());
+                };
+                let mut text = lines.join("\n");
+                text.push('\n');
+                let n = lines.len();
+                std::fs::write(&path, self.secrets.redact(text.as_bytes()))?;
+                self.message = Some(format!("{n} lines written to {}", path.display()));
+                Ok(())
+            }
+            // done above, they don't work on lines
+            Command::YankBytes { .. }
+            | Command::WriteBytes { .. }
+            | Command::SaveSession { .. }
+            | Command::LoadSession { .. }
+            | Command::Diff { .. }
+            | Command::Flush { .. }
+            | Command::Secret { .. }
+            | Command::GCode { .. }
+            | Command::Drop { .. }
+            | Command::Sweep { .. }
+            | Command::Trace { .. }
+            | Command::Window { .. }
+            | Command::CloseWindow
+            | Command::Preset { .. }
+            | Command::Eol { .. }
+            | Command::HexPaste
+            | Command::Notes
+            | Command::Timeline { .. } => Ok(()),
+        }
+    }
+    fn handle_visual_key(
+        &mut self,
+        code: KeyCode,
+        mut v: Visual,
+        pending: Option<char>,
+        count: Option<u32>,
+        register: Option<char>,
+    ) -> Result<(), io::Error> {
+        use KeyCode as K;
+        let n = count.unwrap_or(1) as usize;
+        let (lines, _) = self.rx_lines();
+        let last = lines.len().saturating_sub(1);
+        let line_end = |line: usize| {
+            lines
+                .get(line)
+                .map_or(0, |l| l.chars().count().saturating_sub(1))
+        };
+        let (line, col) = v.cursor;
+        match code {
+            K::Char(c) if pending == Some('"') && is_register(c) => {
+                self.register = Some(c);
+                self.count = count;
+            }
+            K::Char('"') => {
+                self.pending = Some('"');
+                self.count = count;
+            }
+            K::Char(c @ '0'..='9') if count.is_some() || c != '0' => {
+                let digit = c.to_digit(10).unwrap();
+                self.count = Some((count.unwrap_or(0) * 10 + digit).min(Self::MAX_COUNT));
+                self.register = register;
+            }
+            K::Esc => {
+                self.mode = Mode::Normal;
+                return Ok(());
+            }
+            K::Char('v') if !v.linewise => {
+                self.mode = Mode::Normal;
+                return Ok(());
+            }
+            K::Char('V') if v.linewise => {
+                self.mode = Mode::Normal;
+                return Ok(());
+            }
+            K::Char('v') => v.linewise = false,
+            K::Char('V') => v.linewise = true,
+            K::Char('h') | K::Left => v.cursor.1 = col.saturating_sub(n),
+            K::Char('l') | K::Right => v.cursor.1 = (col + n).min(line_end(line)),
+            K::Char('j') | K::Down => {
+                let line = (line + n).min(last);
+                v.cursor = (line, col.min(line_end(line)));
+            }
+            K::Char('k') | K::Up => {
+                let line = line.saturating_sub(n);
+                v.cursor = (line, col.min(line_end(line)));
+            }
+            K::Char('0') | K::Home => v.cursor.1 = 0,
+            K::Char('$') | K::End => v.cursor.1 = line_end(line),
+            K::Char('g') if pending == Some('g') => v.cursor = (0, 0),
+            K::Char('g') => self.pending = Some('g'),
+            K::Char('G') => v.cursor = (last, 0),
+            K::Char('y') => {
+                let text = v.text(&lines);
+                let n = v.bounds().1 .0 - v.bounds().0 .0 + 1;
+                self.visual_marks = Some((v.bounds().0 .0, v.bounds().1 .0));
+                self.mode = Mode::Normal;
+                self.store(register, text)?;
+                self.message = Some(match n {
+                    1 => String::from("1 line yanked"),
+                    n => format!("{n} lines yanked"),
+                });
+                return Ok(());
+            }
+            K::Char(':') => {
+                self.visual_marks = Some((v.bounds().0 .0, v.bounds().1 .0));
+                self.mode = Mode::CommandInput {
+                    input: String::from("'<,'>"),
+                    error: None,
+                };
+                return Ok(());
+            }
+            _ => (),
+        }
+        self.mode = Mode::Visual(v);
+        self.scroll_to_visual_cursor(v);
+        Ok(())
+    }
+    /// enters visual mode at the start of the line at the top of RX
+    fn enter_visual(&mut self, linewise: bool) {
+        let (lines, rows) = self.rx_lines();
+        if lines.is_empty() {
+            self.message = Some(String::from("nothing to select"));
+            return;
+        }
+        let line = self.rx_visible(&rows).0;
+        self.mode = Mode::Visual(Visual {
+            anchor: (line, 0),
+            cursor: (line, 0),
+            linewise,
+        });
+    }
+    /// row of the RX pane a visual position is drawn at
+    fn visual_row(&self, rows: &[usize], (line, col): (usize, usize)) -> usize {
+        let start = rows.get(line).copied().unwrap_or(0);
+        match self.rx.decoder().is_none() && self.rx.display.wrap {
+            true => start + col / self.rx_area.width.max(1) as usize,
+            false => start,
+        }
+    }
+    /// moves the TX history selection by `delta` entries, starting from the newest
+    fn select_tx(&mut self, delta: i32) {
+        let len = self.tx.history.len();
+        if len == 0 {
+            return;
+        }
+        let from = self.tx_selected.unwrap_or(len) as i32;
+        let selected = (from + delta).clamp(0, len as i32 - 1) as usize;
+        self.tx_selected = Some(selected);
+        // keep it in view
+        let lines = self.tx.lines();
+        let row: usize = match self.tx.display.wrap {
+            true => lines[..selected]
+                .iter()
+                .map(|l| wrapped_rows(l, self.tx_area.width))
+                .sum(),
+            false => selected,
+        };
+        let top = self.tx_scroll.offset();
+        let height = self.tx_area.height.max(1) as usize;
+        if row < top {
+            self.tx_scroll.scroll_to(row);
+        } else if row >= top + height {
+            self.tx_scroll.scroll_to(row + 1 - height);
+        }
+    }
+    /// puts the byte cursor on the first byte in view of the RX hex view
+    fn start_byte_cursor(&mut self) {
+        let len = self.rx.display.bytes().len();
+        if !self.rx.is_hex() || self.rx.decoder_kind().is_some() || self.rx.term.is_some() {
+            self.message = Some(String::from(
+                "the byte cursor is for the RX hex view, h switches to it",
+            ));
+        } else if len == 0 {
+            self.message = Some(String::from("nothing received yet"));
+        } else {
+            let per_row = hex_row_bytes(self.rx_area.width);
+            self.byte_cursor = Some((self.rx_scroll.offset() * per_row).min(len - 1));
+        }
+    }
+    /// moves the byte cursor with `h`/`j`/`k`/`l`, returning whether the key was for it
+    fn handle_byte_cursor_key(&mut self, code: KeyCode, count: Option<u32>) -> bool {
+        use KeyCode as K;
+        let n = count.unwrap_or(1) as usize;
+        let (Some(cursor), len) = (self.byte_cursor, self.rx.display.bytes().len()) else {
+            return false;
+        };
+        if len == 0 || !self.rx.is_hex() {
+            self.byte_cursor = None;
+            self.byte_anchor = None;
+            return false;
+        }
+        let per_row = hex_row_bytes(self.rx_area.width);
+        let cursor = match code {
+            K::Esc if self.byte_anchor.is_some() => {
+                self.byte_anchor = None;
+                return true;
+            }
+            K::Esc | K::Char('B') => {
+                self.byte_cursor = None;
+                self.byte_anchor = None;
+                return true;
+            }
+            K::Char('v') => {
+                self.byte_anchor = match self.byte_anchor {
+                    Some(_) => None,
+                    None => Some(cursor),
+                };
+                return true;
+            }
+            K::Char('y') => {
+                if let Err(e) = self.yank_bytes(ByteFormat::Hex, None) {
+                    self.errors.push("clipboard", e);
+                }
+                return true;
+            }
+            K::Char('s') => {
+                if let Some(bytes) = self.selected_bytes().map(<[u8]>::to_vec) {
+                    if let Err(e) = self.resend(bytes) {
+                        self.io_error("write", e);
+                    }
+                }
+                return true;
+            }
+            K::Char('e') => {
+                if let Some(bytes) = self.selected_bytes().map(<[u8]>::to_vec) {
+                    self.overlays.push(
+                        Dialog::HexEdit,
+                        HexEditor::new("Edit and Send", bytes).check(self.hex_check),
+                    );
+                }
+                return true;
+            }
+            K::Char('h') | K::Left => cursor.saturating_sub(n),
+            K::Char('l') | K::Right => cursor + n,
+            K::Char('k') | K::Up => cursor.saturating_sub(n * per_row),
+            K::Char('j') | K::Down => cursor + n * per_row,
+            K::Char('0') if count.is_none() => cursor - cursor % per_row,
+            K::Home => cursor - cursor % per_row,
+            K::Char('$') | K::End => cursor - cursor % per_row + per_row - 1,
+            K::Char('g') => 0,
+            K::Char('G') => len - 1,
+            _ => return false,
+        };
+        let cursor = cursor.min(len - 1);
+        self.byte_cursor = Some(cursor);
+        self.byte_anchor = self.byte_anchor.map(|a| a.min(len - 1));
+        // keep it in view
+        let row = cursor / per_row;
+        let top = self.rx_scroll.offset();
+        let height = self.rx_scroll.bar.height.max(1) as usize;
+        if row < top {
+            self.rx_scroll.scroll_to(row);
+        } else if row >= top + height {
+            self.rx_scroll.scroll_to(row + 1 - height);
+        }
+        true
+    }
+    /// offsets of the bytes selected in the hex view, just the one under the
+    /// cursor without an anchor
+    pub fn byte_selection(&self) -> Option<RangeInclusive<usize>> {
+        let cursor = self.byte_cursor?;
+        let anchor = self.byte_anchor.unwrap_or(cursor);
+        let last = self.rx.display.bytes().len().checked_sub(1)?;
+        Some(cursor.min(anchor).min(last)..=cursor.max(anchor).min(last))
+    }
+    /// listens at every rate in `rates` in turn, the usual ones if it's empty
+    fn start_sweep(&mut self, rates: &[u32]) {
+        let original = match self.serial.baud_rate() {
+            Ok(baud) => baud,
+            Err(e) => {
+                self.message = Some(format!("can't sweep: {e}"));
+                return;
+            }
+        };
+        let sweep = match rates {
+            [] => Sweep::new(link::BAUD_RATES, original),
+            rates => Sweep::new(rates, original),
+        };
+        self.sweep_to(sweep.baud());
+        self.sweep = Some(sweep);
+    }
+    /// sets the port to `baud` for the sweep, dropping what arrived at the
+    /// last rate, `false` when it couldn't
+    fn sweep_to(&mut self, baud: u32) -> bool {
+        let res = self
+            .serial
+            .set_baud_rate(baud)
+            .and_then(|_| self.serial.clear(ClearBuffer::Input));
+        if let Err(e) = &res {
+            self.errors.push("sweep", e);
+        }
+        res.is_ok()
+    }
+    /// `↑`/`↓` pick a rate, `SPACE` stays at it, `ENTER` keeps it and `ESC`
+    /// goes back to the rate from before
+    fn handle_sweep_key(&mut self, code: KeyCode) {
+        use KeyCode as K;
+        let Some(sweep) = &mut self.sweep else {
+            return;
+        };
+        match code {
+            K::Up | K::Char('k') => sweep.select(true),
+            K::Down | K::Char('j') => sweep.select(false),
+            K::Char(' ') => {
+                if let Some(baud) = sweep.hold() {
+                    self.sweep_to(baud);
+                }
+            }
+            K::Enter => {
+                let baud = sweep.rates[sweep.selected].baud;
+                self.sweep = None;
+                // kept for reopening the port and in sessions
+                if self.sweep_to(baud) {
+                    match self.set_baud(baud) {
+                        Ok(()) => self.toasts.info(format!("baud rate set to {baud}")),
+                        Err(e) => self.errors.push("sweep", e),
+                    }
+                }
+            }
+            K::Esc | K::Char('q') => {
+                let baud = sweep.original;
+                self.sweep = None;
+                self.sweep_to(baud);
+                self.message = Some(format!("sweep cancelled, back at {baud}"));
+            }
+            _ => (),
+        }
+    }
+    /// opens a pager on `bytes` seen through `view`, with keys for the others
+    fn show_region(&mut self, bytes: Vec<u8>, view: View) {
+        let title = format!("{} bytes as {view}", bytes.len());
+        let mut pager = Pager::new(title, view.lines(&bytes));
+        for &(key, v) in View::KEYS {
+            pager = pager.action(key, v.name());
+        }
+        self.overlays
+            .push(Dialog::Region, pager.action('d', "decoders"));
+        self.region = Some((bytes, view));
+    }
+    fn selected_bytes(&self) -> Option<&[u8]> {
+        self.byte_selection()
+            .and_then(|range| self.rx.display.bytes().get(range))
+    }
+    fn yank_bytes(&mut self, format: ByteFormat, register: Option<char>) -> Result<(), io::Error> {
+        let Some(bytes) = self.selected_bytes() else {
+            self.message = Some(String::from("no bytes selected, B starts the byte cursor"));
+            return Ok(());
+        };
+        let (n, text) = (bytes.len(), format.format(bytes));
+        self.store(register, text)?;
+        self.message = Some(format!("{n} bytes yanked as {}", format.name()));
+        Ok(())
+    }
+    /// sends bytes taken from RX back out as they are, like a captured frame
+    fn resend(&mut self, bytes: Vec<u8>) -> Result<(), io::Error> {
+        self.tx.send_raw(&bytes, self.serial.as_mut())?;
+        self.tx.record(&bytes);
+        self.message = Some(format!("{} bytes sent", bytes.len()));
+        self.last_action = Some(Action::Send(bytes));
+        Ok(())
+    }
+    fn write_bytes(&mut self, path: &Path) -> Result<(), io::Error> {
+        let Some(bytes) = self.selected_bytes() else {
+            self.message = Some(String::from("no bytes selected, B starts the byte cursor"));
+            return Ok(());
+        };
+        let n = bytes.len();
+        std::fs::write(path, self.secrets.redact(bytes))?;
+        self.message = Some(format!("{n} bytes written to {}", path.display()));
+        Ok(())
+    }
+    fn selected_tx_entry(&self) -> Vec<u8> {
+        self.tx_selected
+            .and_then(|i| self.tx.history.get(i))
+            .cloned()
+            .unwrap_or_default()
+    }
+    fn scroll_to_visual_cursor(&mut self, v: Visual) {
+        let (_, rows) = self.rx_lines();
+        let row = self.visual_row(&rows, v.cursor);
+        let top = self.rx_scroll.offset();
+        let height = self.rx_scroll.bar.height.max(1) as usize;
+        if row < top {
+            self.rx_scroll.scroll_to(row);
+        } else if row >= top + height {
+            self.rx_scroll.scroll_to(row + 1 - height);
+        }
+    }
+    /// screen cells covered by the visual selection, and the cell of its cursor
+    pub fn visual_cells(&self) -> (Vec<ScreenPos>, Option<ScreenPos>) {
+        let Mode::Visual(v) = &self.mode else {
+            return (Vec::new(), None);
+        };
+        let (lines, rows) = self.rx_lines();
+        let top = self.rx_scroll.offset();
+        let height = self.rx_scroll.bar.height as usize;
+        let width = self.rx_area.width.max(1) as usize;
+        // decoded records are drawn after a two character marker and not wrapped
+        let (x0, wrap) = match self.rx.decoder() {
+            Some(_) => (self.rx_area.x + 2, usize::MAX),
+            None if !self.rx.display.wrap => (self.rx_area.x, usize::MAX),
+            None => (self.rx_area.x, width),
+        };
+        let cell = |line: usize, col: usize| {
+            let row = rows[line] + col / wrap;
+            (top..top + height).contains(&row).then(|| {
+                (
+                    x0 + (col % wrap).min(width - 1) as u16,
+                    self.rx_scroll.bar.y + (row - top) as u16,
+                )
+            })
+        };
+        let (start, end) = v.bounds();
+        let mut cells = Vec::new();
+        for line in start.0..=end.0.min(lines.len().saturating_sub(1)) {
+            if rows[line] >= top + height {
+                break;
+            }
+            let len = lines[line].chars().count().max(1);
+            let (from, to) = match v.linewise {
+                true => (0, len - 1),
+                false => (
+                    if line == start.0 { start.1 } else { 0 },
+                    if line == end.0 { end.1 } else { len - 1 },
+                ),
+            };
+            cells.extend((from..=to).filter_map(|col| cell(line, col)));
+        }
+        let cursor = (v.cursor.0 < lines.len())
+            .then(|| cell(v.cursor.0, v.cursor.1))
+            .flatten();
+        (cells, cursor)
+    }
+    /// copies the selected RX text to the clipboard
+    pub fn yank(&mut self) -> Result<(), io::Error> {
+        self.yank_to(None)
+    }
+    fn yank_to(&mut self, register: Option<char>) -> Result<(), io::Error> {
+        match &self.selection {
+            Some(sel) if !sel.text.is_empty() => self.store(register, sel.text.clone()),
+            _ => Ok(()),
+        }
+    }
+    /// switches the RX decoder, applying the line settings its protocol needs
+    pub fn set_decoder(&mut self, kind: Option<DecoderKind>) -> Result<(), io::Error> {
+        if let Some((name, settings)) = kind.and_then(|k| Some((k, k.line_settings()?))) {
+            self.link
+                .configure(self.serial.as_mut(), settings, self.link.flow_control)?;
+            self.toasts
+                .info(format!("{name} needs {settings}, port reconfigured"));
+        }
+        self.rx.set_decoder(kind);
+        Ok(())
+    }
+    /// changes the baud rate, kept for reopening the port
+    fn set_baud(&mut self, baud: u32) -> Result<(), serialport::Error> {
+        let settings = LineSettings {
+            baud_rate: baud,
+            ..self.link.settings
+        };
+        self.link
+            .configure(self.serial.as_mut(), settings, self.link.flow_control)
+    }
+    /// restores the view saved in a profile
+    pub fn apply_layout(&mut self, layout: &Layout) -> Result<(), io::Error> {
+        self.split = layout
+            .split
+            .clamp(Self::MIN_SPLIT as u16, 100 - Self::MIN_SPLIT as u16);
+        if self.tx.is_hex() != layout.tx_hex {
+            self.tx.display.switch_hex();
+        }
+        if self.rx.is_hex() != layout.rx_hex {
+            self.rx.display.switch_hex();
+        }
+        self.tx.display.wrap = layout.tx_wrap;
+        self.rx.display.wrap = layout.rx_wrap;
+        self.tx.lf_crlf = layout.lf_crlf;
+        self.rx.cr_lines = layout.cr_lines;
+        self.tx.line_ending = layout.line_ending;
+        self.line_mode = layout.line_mode;
+        if layout.terminal {
+            self.set_decoder(None)?;
+            if !self.rx.is_term() {
+                self.rx.toggle_term();
+            }
+        } else {
+            // profiles don't keep line settings, so the port stays as it was
+            // opened rather than switching to what the decoder needs
+            self.rx.set_decoder(layout.decoder);
+            let needs = layout.decoder.and_then(|k| Some((k, k.line_settings()?)));
+            if let Some((name, settings)) = needs.filter(|&(_, s)| s != self.link.settings) {
+                self.toasts.info(format!(
+                    "{name} needs {settings}, port left at {}",
+                    self.link.settings
+                ));
+            }
+        }
+        Ok(())
+    }
+    /// current view, to be saved in a profile
+    pub fn layout(&self) -> Layout {
+        Layout {
+            split: self.split,
+            tx_hex: self.tx.is_hex(),
+            rx_hex: self.rx.is_hex(),
+            tx_wrap: self.tx.display.wrap,
+            rx_wrap: self.rx.display.wrap,
+            lf_crlf: self.tx.lf_crlf,
+            cr_lines: self.rx.cr_lines,
+            line_ending: self.tx.line_ending,
+            line_mode: self.line_mode,
+            decoder: self.rx.decoder_kind(),
+            terminal: self.rx.is_term(),
+        }
+    }
+    /// everything that goes into a session file, with secrets masked
+    pub fn session(&self) -> Session {
+        let settings = self.link.settings;
+        let hex = |bytes: &[u8]| session::to_hex(&self.secrets.redact(bytes));
+        Session {
+            port: self.link.port.clone(),
+            baud_rate: settings.baud_rate,
+            data_bits: link::name_of(link::DATA_BITS, settings.data_bits).to_owned(),
+            parity: link::name_of(link::PARITIES, settings.parity).to_owned(),
+            stop_bits: link::name_of(link::STOP_BITS, settings.stop_bits).to_owned(),
+            flow_control: link::name_of(link::FLOW_CONTROLS, self.link.flow_control).to_owned(),
+            layout: self.layout(),
+            rx: hex(&self.rx.contents()),
+            rx_times: self
+                .rx
+                .display
+                .arrivals()
+                .iter()
+                .map(|&(offset, time)| (offset, session::to_millis(time)))
+                .collect(),
+            tx: hex(self.tx.display.bytes()),
+            history: self.tx.history.iter().map(|h| hex(h)).collect(),
+            sent: self.tx.sent.iter().map(|h| hex(h)).collect(),
+            registers: self
+                .registers
+                .iter()
+                .map(|(r, text)| {
+                    let text = self.secrets.redact(text.as_bytes());
+                    (r.to_string(), String::from_utf8_lossy(&text).into_owned())
+                })
+                .collect(),
+            notes: self.notes.clone(),
+        }
+    }
+    fn save_session(&mut self, path: &Path) {
+        match self.session().save(path) {
+            Ok(()) => {
+                self.message = Some(format!("session saved to {}", path.display()));
+                if !self.files.iter().any(|(_, p)| p == path) {
+                    self.files.push(("session", path.to_owned()));
+                }
+            }
+            Err(e) => self.message = Some(format!("can't save {}: {e}", path.display())),
+        }
+    }
+    /// puts RX, TX, the view and the line settings back as they were saved.
+    /// The port stays the one that's open
+    pub fn restore_session(&mut self, session: Session) -> Result<(), String> {
+        let rx = parse_hex(&session.rx)?;
+        let tx = parse_hex(&session.tx)?;
+        let history = session
+            .history
+            .iter()
+            .map(|h| parse_hex(h))
+            .collect::<Result<VecDeque<_>, _>>()?;
+        let sent = session
+            .sent
+            .iter()
+            .map(|h| parse_hex(h))
+            .collect::<Result<VecDeque<_>, _>>()?;
+        self.apply_layout(&session.layout)
+            .map_err(|e| e.to_string())?;
+        let times = session
+            .rx_times
+            .iter()
+            .map(|&(offset, millis)| (offset, session::from_millis(millis)))
+            .collect();
+        self.rx.restore(&rx, times);
+        self.tx.display.restore(&tx, Vec::new());
+        self.tx.history = history;
+        self.tx.sent = sent;
+        self.tx_selected = None;
+        self.registers = session
+            .registers
+            .iter()
+            .filter_map(|(r, text)| Some((r.chars().next()?, text.clone())))
+            .collect();
+        self.notes = session.notes;
+        self.byte_cursor = None;
+        self.byte_anchor = None;
+        self.selection = None;
+        self.visual_marks = None;
+        self.rx_scroll = Scroll::default();
+        self.rx_new = 0;
+        let settings = LineSettings {
+            baud_rate: session.baud_rate,
+            data_bits: link::parse_name(link::DATA_BITS, &session.data_bits)
+                .unwrap_or(self.link.settings.data_bits),
+            parity: link::parse_name(link::PARITIES, &session.parity)
+                .unwrap_or(self.link.settings.parity),
+            stop_bits: link::parse_name(link::STOP_BITS, &session.stop_bits)
+                .unwrap_or(self.link.settings.stop_bits),
+        };
+        let flow_control = link::parse_name(link::FLOW_CONTROLS, &session.flow_control)
+            .unwrap_or(self.link.flow_control);
+        if settings.baud_rate > 0 && !self.link.is_down() {
+            if let Err(e) = self
+                .link
+                .configure(self.serial.as_mut(), settings, flow_control)
+            {
+                self.errors.push("session", e);
+            }
+        }
+        self.message = Some(match session.port == self.link.port {
+            true => format!("session restored, {} bytes received", rx.len()),
+            false => format!(
+                "session of {} restored, {} bytes received",
+                session.port,
+                rx.len()
+            ),
+        });
+        Ok(())
+    }
+    /// moves the divider between TX and RX by `delta` percent
+    pub fn resize_split(&mut self, delta: i16) {
+        self.split =
+            (self.split as i16 + delta).clamp(Self::MIN_SPLIT, 100 - Self::MIN_SPLIT) as u16;
+    }
     pub fn enter_insert(&mut self) {
         self.mode = Mode::Insert;
+        self.focus = Focus::Tx;
         self.cursor = Cursor::insert();
     }
     pub fn leave_insert(&mut self) {
         self.mode = Mode::Normal;
         self.cursor = Cursor::normal();
+        if !self.typed.is_empty() {
+            self.last_action = Some(Action::Type(std::mem::take(&mut self.typed)));
+        }
+    }
+    /// handles the key after the escape key in INSERT mode: the escape key again
+    /// sends it, `Ctrl+R` pastes a register and anything else is a NORMAL mode command
+    fn handle_escaped_key(
+        &mut self,
+        key: KeyEvent,
+        escape: EscapeKey,
+    ) -> Result<Control, io::Error> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            _ if escape.matches(key) => self.type_control(escape.byte())?,
+            KeyCode::Char('r') if ctrl => self.pending = Some('r'),
+            KeyCode::Esc => self.leave_insert(),
+            _ => {
+                self.leave_insert();
+                return self.handle_key(key);
+            }
+        }
+        Ok(Control::Continue)
+    }
+    fn ctrl_c(&mut self) -> Result<Control, io::Error> {
+        match self.ctrl_c {
+            CtrlC::Send if self.mode.is_insert() => self.type_control(0x03)?,
+            CtrlC::Send => {
+                self.message = Some(String::from("Ctrl+C sends 0x03 in INSERT mode, q quits"))
+            }
+            CtrlC::Copy => match &self.mode {
+                Mode::Visual(v) => {
+                    let v = *v;
+                    self.handle_visual_key(KeyCode::Char('y'), v, None, None, None)?;
+                }
+                _ if self.selection.is_some() => {
+                    self.yank_to(None)?;
+                    self.message = Some(String::from("copied"));
+                }
+                _ => self.message = Some(String::from("nothing selected to copy")),
+            },
+            CtrlC::Quit => return Ok(Control::Exit),
+        }
+        Ok(Control::Continue)
+    }
+    /// edits the line in line mode, sending it on ENTER
+    fn handle_line_key(&mut self, key: KeyEvent, pending: Option<char>) -> Result<(), io::Error> {
+        use KeyCode as K;
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let edit = &mut self.line_edit;
+        match key.code {
+            K::Char(c) if pending == Some('r') => {
+                if let Some(text) = self.registers.get(&c) {
+                    text.chars().for_each(|c| edit.insert(c));
+                }
+            }
+            K::Esc if self.escape.is_some() => self.type_control(0x1B)?,
+            K::Esc => self.leave_insert(),
+            K::Char('r') if ctrl && self.escape.is_none() => self.pending = Some('r'),
+            K::Char('u') if ctrl => edit.kill_to_start(),
+            // control codes still go out right away
+            K::Char(c @ 'a'..='z') if ctrl && self.escape.is_some() => {
+                self.type_control(c as u8 - b'a' + 1)?
+            }
+            K::Char(_) if ctrl => (),
+            K::Char(c) => edit.insert(c),
+            K::Tab => edit.insert('\t'),
+            K::Backspace => edit.backspace(),
+            K::Delete => edit.delete(),
+            K::Left => edit.left(),
+            K::Right => edit.right(),
+            K::Home => edit.home(),
+            K::End => edit.end(),
+            K::Up => edit.recall(&self.tx.sent, -1),
+            K::Down => edit.recall(&self.tx.sent, 1),
+            K::Enter => {
+                let line = match self.tx.is_hex() {
+                    true => match parse_hex(&edit.text) {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            self.message = Some(e);
+                            return Ok(());
+                        }
+                    },
+                    false => edit.text.clone().into_bytes(),
+                };
+                self.tx.send_line(&line, self.serial.as_mut())?;
+                self.line_edit.take();
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+    /// sends a control code typed in INSERT mode
+    fn type_control(&mut self, b: u8) -> Result<(), io::Error> {
+        // hex input would take it for a digit that isn't one
+        if self.tx.is_hex() {
+            self.tx.send_bytes(&[b], self.serial.as_mut())
+        } else {
+            self.type_byte(b)
+        }
+    }
+    /// sends a key typed in INSERT mode, remembering it for `.`
+    fn type_byte(&mut self, b: u8) -> Result<(), io::Error> {
+        self.typed.push(b);
+        self.tx.send(b, self.serial.as_mut())
+    }
+    /// repeats the last action `n` times
+    pub fn repeat(&mut self, n: i32) -> Result<(), io::Error> {
+        let Some(action) = self.last_action.clone() else {
+            return Ok(());
+        };
+        for _ in 0..n {
+            match &action {
+                Action::Key(key) => {
+                    self.handle_key(*key)?;
+                }
+                Action::Type(bytes) => {
+                    for &b in bytes {
+                        self.tx.send(b, self.serial.as_mut())?;
+                    }
+                }
+                Action::Send(bytes) => {
+                    self.tx.send_raw(bytes, self.serial.as_mut())?;
+                    self.tx.record(bytes);
+                }
+            }
+        }
+        Ok(())
     }
     pub fn cursor(&self) -> char {
         self.cursor.cursor()
     }
 }
 
+/// Scroll position of a pane
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Scroll {
+    /// first visible line, `None` to follow the end of the buffer
+    top: Option<usize>,
+    /// number of lines at the last draw
+    pub lines: usize,
+    /// column of the scroll bar next to the text, its height is the visible height
+    pub bar: Rect,
+}
+
+impl Scroll {
+    fn max(&self) -> usize {
+        self.lines.saturating_sub(self.bar.height as usize)
+    }
+    /// first visible line
+    pub fn offset(&self) -> usize {
+        self.top.map_or(self.max(), |top| top.min(self.max()))
+    }
+    pub fn is_following(&self) -> bool {
+        self.top.is_none()
+    }
+    pub fn follow(&mut self) {
+        self.top = None;
+    }
+    /// scrolls so `row` is the first visible line, or as close as the end allows
+    pub fn scroll_to(&mut self, row: usize) {
+        self.top = Some(row);
+    }
+    pub fn scroll_to_top(&mut self) {
+        self.top = (self.max() > 0).then_some(0);
+    }
+    /// scrolls by `delta` lines, following the end again once it is reached
+    pub fn scroll_by(&mut self, delta: i32) {
+        let top = (self.offset() as i64 + delta as i64).clamp(0, self.max() as i64) as usize;
+        self.top = (top < self.max()).then_some(top);
+    }
+    /// scrolls so the thumb of the scroll bar is at row `y` of the screen
+    fn drag_to(&mut self, y: u16) {
+        let row = y.saturating_sub(self.bar.y) as usize;
+        let rows = self.bar.height.saturating_sub(1).max(1) as usize;
+        let top = row * self.max() / rows;
+        self.top = (top < self.max()).then_some(top);
+    }
+}
+
+fn contains(area: Rect, (x, y): (u16, u16)) -> bool {
+    (area.left()..area.right()).contains(&x) && (area.top()..area.bottom()).contains(&y)
+}
+
+/// A user-level action `.` can repeat
+#[derive(Debug, Clone)]
+enum Action {
+    /// a NORMAL mode command, like a toggle
+    Key(KeyEvent),
+    /// keys typed in the last INSERT session, sent again as if typed
+    Type(Vec<u8>),
+    /// raw bytes, like a frame built with `f`
+    Send(Vec<u8>),
+}
+
+/// column and row of a cell on screen
+pub type ScreenPos = (u16, u16);
+
+/// Visual mode selection in RX, as line and character in the lines from `App::rx_lines`
+#[derive(Debug, Clone, Copy)]
+pub struct Visual {
+    pub anchor: (usize, usize),
+    pub cursor: (usize, usize),
+    /// selects whole lines, as started with `V`
+    pub linewise: bool,
+}
+
+impl Visual {
+    /// start and end in reading order, inclusive
+    pub fn bounds(&self) -> ((usize, usize), (usize, usize)) {
+        if self.anchor <= self.cursor {
+            (self.anchor, self.cursor)
+        } else {
+            (self.cursor, self.anchor)
+        }
+    }
+    fn text(&self, lines: &[&str]) -> String {
+        let (start, end) = self.bounds();
+        let Some(lines) = lines.get(start.0..=end.0) else {
+            return String::new();
+        };
+        if self.linewise {
+            let mut text = lines.join("\n");
+            text.push('\n');
+            return text;
+        }
+        let last = lines.len() - 1;
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let from = if i == 0 { start.1 } else { 0 };
+                let to = if i == last { end.1 + 1 } else { usize::MAX };
+                line.chars()
+                    .skip(from)
+                    .take(to.saturating_sub(from))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Mouse selection in the RX pane, as screen positions
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub anchor: (u16, u16),
+    pub head: (u16, u16),
+    pub dragging: bool,
+    /// selected text, captured from the screen whenever the selection is drawn
+    pub text: String,
+}
+
+impl Selection {
+    fn new(pos: (u16, u16)) -> Self {
+        Self {
+            anchor: pos,
+            head: pos,
+            dragging: true,
+            text: String::new(),
+        }
+    }
+    /// start and end of the selection in reading order, inclusive
+    pub fn bounds(&self) -> ((u16, u16), (u16, u16)) {
+        let key = |(x, y): (u16, u16)| (y, x);
+        if key(self.anchor) <= key(self.head) {
+            (self.anchor, self.head)
+        } else {
+            (self.head, self.anchor)
+        }
+    }
+}
+
 enum Cursor {
     Normal,
     Insert {
@@ -1,4 +1,19 @@
+use crate::app::{CtrlC, EscapeKey};
+use crate::capture::LogFormat;
+use crate::decode::DecoderKind;
+use crate::fuzz::{parse_bytes, parse_len};
+use crate::highlight;
+use crate::hooks::Hook;
+use crate::idle::Rule;
+use crate::line_edit::LineEnding;
+use crate::notify::Bell;
+use crate::screen::{parse_breakpoint, parse_hex_breakpoint};
+use crate::theme::{ColorDepth, ThemeName};
+use crate::watch::Watch;
 use argh::FromArgs;
+use regex::bytes::Regex;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
 
 #[derive(FromArgs)]
 /// A tui serial terminal
@@ -7,7 +22,267 @@ pub struct Args {
     /// baud rate
     pub baud: u32,
 
-    /// serial port to connect to
+    /// payload to send periodically, may contain an incrementing counter token
+    #[argh(option)]
+    pub periodic: Option<String>,
+
+    #[argh(option, default = "1000")]
+    /// periodic send interval in milliseconds
+    pub interval: u64,
+
+    /// payload to send when nothing was sent or received for a while
+    #[argh(option)]
+    pub keepalive: Option<String>,
+
+    #[argh(option, default = "30")]
+    /// seconds without traffic before a keepalive is sent
+    pub keepalive_idle: u64,
+
+    #[argh(option, long = "idle")]
+    /// act after seconds without RX data and again every time as long again
+    /// passes, as `secs:send:<payload>`, `secs:dtr` to pulse DTR or
+    /// `secs:hook:<command>`, can be repeated
+    pub idle: Vec<Rule>,
+
+    #[argh(option)]
+    /// seed for the fuzzer, random if not given
+    pub fuzz_seed: Option<u64>,
+
+    #[argh(option, from_str_fn(parse_len), default = "1..=16")]
+    /// length of fuzz cases as `min-max`
+    pub fuzz_len: RangeInclusive<usize>,
+
+    #[argh(option, from_str_fn(parse_bytes), default = "0..=255")]
+    /// hex range of fuzzed bytes as `lo-hi`, e.g. `20-7E`
+    pub fuzz_bytes: RangeInclusive<u8>,
+
+    #[argh(option, default = "100")]
+    /// milliseconds between fuzz cases
+    pub fuzz_rate: u64,
+
+    #[argh(option)]
+    /// payload to mutate instead of sending random bytes
+    pub fuzz_base: Option<String>,
+
+    #[argh(option)]
+    /// file to log every fuzz case to
+    pub fuzz_log: Option<PathBuf>,
+
+    #[argh(option, long = "break", from_str_fn(parse_breakpoint))]
+    /// pause the RX display when this regex matches, can be repeated
+    pub breakpoints: Vec<Regex>,
+
+    #[argh(option, from_str_fn(parse_hex_breakpoint))]
+    /// pause the RX display when these hex bytes arrive, can be repeated
+    pub break_hex: Vec<Regex>,
+
+    #[argh(option, from_str_fn(parse_bytes))]
+    /// hex byte or range like `00` or `80-9F` left out of the RX display and
+    /// the text log, can be repeated
+    pub drop: Vec<RangeInclusive<u8>>,
+
+    #[argh(option, long = "watch")]
+    /// count RX matches of a regex on a dashboard, as `label=regex` or just
+    /// `regex`, can be repeated
+    pub watches: Vec<Watch>,
+
+    #[argh(option, from_str_fn(parse_breakpoint))]
+    /// regex matching the device's prompt at the end of the last line, like
+    /// '\$ $', shown as READY on the status line while it's waiting for input
+    pub prompt: Option<Regex>,
+
+    #[argh(switch)]
+    /// hold periodic sends until the device shows its --prompt
+    pub prompt_gate: bool,
+
+    #[argh(option, from_str_fn(parse_breakpoint))]
+    /// regex the device acknowledges what it got with, like 'ok' or '\x06':
+    /// file sends and pastes then wait for it
+    pub ack: Option<Regex>,
+
+    #[argh(option, default = "1")]
+    /// lines or blocks of 64 bytes sent ahead of their --ack, 1 by default
+    pub ack_window: usize,
+
+    #[argh(switch)]
+    /// number and checksum the lines :gcode sends, for Marlin based printers
+    pub gcode_checksums: bool,
+
+    #[argh(option, long = "hook")]
+    /// run a shell command on an event, as `event=command`: connect,
+    /// disconnect, break, silence or match:<regex>, can be repeated
+    pub hooks: Vec<Hook>,
+
+    #[argh(option)]
+    /// decode RX data with a protocol decoder: slcan, midi, ubx, sbus, xbee
+    pub decode: Option<DecoderKind>,
+
+    #[argh(option, long = "highlight")]
+    /// color decoded frames by their fields, like 'status != 0 -> red' or
+    /// 'id == 0x123 -> yellow', the first matching rule wins, can be repeated
+    pub highlights: Vec<highlight::Rule>,
+
+    #[argh(option, default = "0")]
+    /// which number of each RX line the waveform plots, 0 for the first
+    pub wave_field: usize,
+
+    #[argh(option, default = "ThemeName::Default")]
+    /// color theme: default, solarized-dark, gruvbox, high-contrast,
+    /// deuteranopia, protanopia
+    pub theme: ThemeName,
+
+    #[argh(option)]
+    /// color depth: truecolor, 256 or 16, detected from the environment if not given
+    pub colors: Option<ColorDepth>,
+
+    #[argh(option)]
+    /// seconds newly received text stays highlighted, fading as it ages
+    pub fade: Option<u64>,
+
+    #[argh(option, default = "CtrlC::Send")]
+    /// what Ctrl+C does: send (0x03 to the device in INSERT mode), copy (the
+    /// selection) or quit
+    pub ctrl_c: CtrlC,
+
+    #[argh(option, default = "Bell::Ring")]
+    /// what a BEL from the device does: ring (the terminal bell), flash (the
+    /// screen) or off
+    pub bell: Bell,
+
+    #[argh(option)]
+    /// prefix key for commands in INSERT mode, like C-a, so Esc and other
+    /// control keys are sent to the device
+    pub escape: Option<EscapeKey>,
+
+    #[argh(switch)]
+    /// edit lines in INSERT mode and send them on ENTER, rather than sending
+    /// every key
+    pub line_mode: bool,
+
+    #[argh(switch)]
+    /// write no faster than the baud rate carries the bytes, so large sends
+    /// don't pile up in the OS buffer where they can't be stopped
+    pub shape_tx: bool,
+
+    #[argh(option)]
+    /// what ends lines sent in line mode: none, cr, lf or crlf
+    pub line_ending: Option<LineEnding>,
+
+    #[argh(option)]
+    /// warn when no RX data arrived for this many seconds
+    pub watchdog: Option<u64>,
+
+    #[argh(switch)]
+    /// also raise a desktop notification when the watchdog fires
+    pub watchdog_notify: bool,
+
+    #[argh(option)]
+    /// save RX to a timestamped file in this directory when the device goes
+    /// quiet or disconnects
+    pub snapshot_dir: Option<PathBuf>,
+
+    #[argh(option, default = "5")]
+    /// minutes without RX data before a snapshot is saved
+    pub snapshot_idle: u64,
+
+    #[argh(option)]
+    /// record the first seconds of RX after every connect to a new file in
+    /// this directory, to keep boot banners
+    pub boot_log: Option<PathBuf>,
+
+    #[argh(option, default = "10")]
+    /// seconds of RX recorded to the boot log after connecting
+    pub boot_secs: u64,
+
+    #[argh(option)]
+    /// log everything received to this file, appending to it
+    pub log: Option<PathBuf>,
+
+    #[argh(option)]
+    /// how the log is written: raw (the bytes as they came, the default), text
+    /// (with the time at the start of every line) or hex (a timestamped hex
+    /// line per read)
+    pub log_format: Option<LogFormat>,
+
+    #[argh(option, default = "1000")]
+    /// milliseconds logged data may wait before it's written and synced to
+    /// disk, the most a crash or power loss takes from the log and boot log
+    pub log_flush: u64,
+
+    #[argh(option)]
+    /// size in KiB the log grows to before it's renamed to <log>.<timestamp>
+    /// and a new one started, running `rotate` hooks
+    pub log_max_size: Option<u64>,
+
+    #[argh(option, from_str_fn(parse_breakpoint))]
+    /// regex masked with * in logs, snapshots and saved files, the capture
+    /// groups only if it has any, like 'psk="(.*)"', can be repeated
+    pub redact: Vec<Regex>,
+
+    #[argh(option)]
+    /// write a summary of the session to this file on exit, - for stdout
+    pub summary: Option<PathBuf>,
+
+    #[argh(option)]
+    /// frame templates to build binary requests from with `t`, read from
+    /// tuicom/templates.toml in the config directory if not given
+    pub templates: Option<PathBuf>,
+
+    #[argh(option)]
+    /// presets to turn on with --preset or :preset, read from
+    /// tuicom/presets.toml in the config directory if not given
+    pub presets: Option<PathBuf>,
+
+    #[argh(option, long = "preset")]
+    /// turn on a preset of watches, dropped bytes, windows and a decoder, can
+    /// be repeated
+    pub preset: Vec<String>,
+
+    #[argh(option)]
+    /// session file saved with :mksession to reopen, with what was received
+    /// and sent and the view as they were
+    pub session: Option<PathBuf>,
+
+    #[argh(option)]
+    /// share the port on this address without a UI, for another tuicom to
+    /// open as tuicom://host:7878; a bare port like 7878 is this machine only
+    pub serve: Option<String>,
+
+    #[argh(switch)]
+    /// let --serve listen on addresses other machines can reach, like 0.0.0.0:7878
+    pub serve_public: bool,
+
+    #[argh(option)]
+    /// token a served port asks for, generated and printed if not given, and
+    /// remote ports are opened with
+    pub token: Option<String>,
+
+    #[argh(option)]
+    /// PEM certificate chain to serve the port over TLS with, clients open it
+    /// as tuicoms://host:7878
+    pub tls_cert: Option<PathBuf>,
+
+    #[argh(option)]
+    /// PEM private key of the --tls-cert certificate
+    pub tls_key: Option<PathBuf>,
+
+    #[argh(option)]
+    /// PEM certificates trusted for tuicoms:// ports instead of the public
+    /// roots, like the server's self-signed certificate
+    pub tls_ca: Option<PathBuf>,
+
+    #[argh(switch)]
+    /// accept any certificate from tuicoms:// ports, encrypting without
+    /// knowing who is at the other end
+    pub tls_insecure: bool,
+
+    #[argh(option)]
+    /// profile to restore the layout from and save it to, named after the port if not given
+    pub profile: Option<String>,
+
+    /// serial port to connect to, or a glob like /dev/ttyUSB* or prefix like
+    /// ttyACM matched against the available ports. Defaults to the only USB
+    /// serial port, if there is just one
     #[argh(positional)]
-    pub port: String,
+    pub port: Option<String>,
 }
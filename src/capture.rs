@@ -0,0 +1,355 @@
+//! Saving RX to files by itself, so output isn't lost when nobody was watching
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Writes RX to a timestamped file when the device goes quiet or away
+pub struct Snapshots {
+    pub dir: PathBuf,
+    /// how long RX has to be quiet before it's saved
+    pub idle: Duration,
+    /// when the newest saved byte arrived, so the same data isn't saved twice
+    saved: Option<Instant>,
+}
+
+impl Snapshots {
+    pub fn new(dir: PathBuf, idle: Duration) -> Self {
+        Self {
+            dir,
+            idle,
+            saved: None,
+        }
+    }
+    /// whether RX has been quiet for long enough since data that wasn't saved yet
+    pub fn is_due(&self, last_recv: Instant) -> bool {
+        last_recv.elapsed() >= self.idle && self.is_new(last_recv)
+    }
+    fn is_new(&self, last_recv: Instant) -> bool {
+        self.saved.is_none_or(|saved| last_recv > saved)
+    }
+    /// writes `rx` to `<dir>/<name>-<timestamp>-<reason>.log`, returning the
+    /// path, or `None` when there is nothing new to save
+    pub fn save(
+        &mut self,
+        name: &str,
+        reason: &str,
+        rx: &[u8],
+        last_recv: Instant,
+    ) -> Result<Option<PathBuf>, io::Error> {
+        if rx.is_empty() || !self.is_new(last_recv) {
+            return Ok(None);
+        }
+        self.saved = Some(last_recv);
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!(
+            "{name}-{}-{reason}.log",
+            timestamp(SystemTime::now())
+        ));
+        fs::write(&path, rx)?;
+        Ok(Some(path))
+    }
+}
+
+/// Appends to a file a batch at a time, every batch written and synced to
+/// disk before the next one starts, so a panic, a kill or a power loss only
+/// takes what arrived since the last one
+pub struct Journal {
+    file: File,
+    /// bytes not written yet
+    pending: Vec<u8>,
+    /// longest time bytes wait before they're on disk
+    pub interval: Duration,
+    flushed: Instant,
+}
+
+impl Journal {
+    /// how long bytes wait unless set otherwise
+    pub const INTERVAL: Duration = Duration::from_secs(1);
+    /// bytes held before they're written, however soon that is
+    const MAX_PENDING: usize = 64 * 1024;
+
+    /// opens `path` to append to, creating it if needed
+    pub fn append(path: &Path, interval: Duration) -> Result<Self, io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(file, interval))
+    }
+    /// creates `path`, emptying it if it exists
+    pub fn create(path: &Path, interval: Duration) -> Result<Self, io::Error> {
+        Ok(Self::new(File::create(path)?, interval))
+    }
+    fn new(file: File, interval: Duration) -> Self {
+        Self {
+            file,
+            pending: Vec::new(),
+            interval,
+            flushed: Instant::now(),
+        }
+    }
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), io::Error> {
+        self.pending.extend_from_slice(bytes);
+        if self.pending.len() >= Self::MAX_PENDING {
+            return self.flush();
+        }
+        self.poll()
+    }
+    /// writes what's pending if it waited long enough, also when nothing
+    /// arrives anymore
+    pub fn poll(&mut self) -> Result<(), io::Error> {
+        if self.flushed.elapsed() < self.interval {
+            return Ok(());
+        }
+        self.flush()
+    }
+    /// writes what's pending and waits until it's on disk
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        self.flushed = Instant::now();
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        // dropped even if writing fails, retrying could write it twice
+        let pending = std::mem::take(&mut self.pending);
+        self.file.write_all(&pending)?;
+        self.file.sync_data()
+    }
+}
+
+/// the last batch is written when the log is closed or the app unwinds
+impl Drop for Journal {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Records the first seconds of output after every connect, where boot
+/// banners are
+pub struct BootLog {
+    pub dir: PathBuf,
+    /// how long after connecting RX is recorded
+    pub duration: Duration,
+    /// longest time recorded bytes wait before they're on disk
+    pub flush: Duration,
+    /// file being recorded to and when recording started
+    current: Option<(Journal, Instant)>,
+}
+
+impl BootLog {
+    pub fn new(dir: PathBuf, duration: Duration) -> Self {
+        Self {
+            dir,
+            duration,
+            flush: Journal::INTERVAL,
+            current: None,
+        }
+    }
+    /// starts recording to `<dir>/<name>-<timestamp>-boot.log`, returning the path
+    pub fn start(&mut self, name: &str) -> Result<PathBuf, io::Error> {
+        self.current = None;
+        fs::create_dir_all(&self.dir)?;
+        let path = self
+            .dir
+            .join(format!("{name}-{}-boot.log", timestamp(SystemTime::now())));
+        self.current = Some((Journal::create(&path, self.flush)?, Instant::now()));
+        Ok(path)
+    }
+    /// how much longer RX is recorded
+    pub fn remaining(&self) -> Option<Duration> {
+        let (_, started) = self.current.as_ref()?;
+        self.duration.checked_sub(started.elapsed())
+    }
+    /// records received bytes, until the time is up
+    pub fn push(&mut self, bytes: &[u8]) -> Result<(), io::Error> {
+        let Some((file, started)) = &mut self.current else {
+            return Ok(());
+        };
+        if started.elapsed() >= self.duration {
+            self.current = None;
+            return Ok(());
+        }
+        if let Err(e) = file.write(bytes) {
+            self.current = None;
+            return Err(e);
+        }
+        Ok(())
+    }
+    /// writes recorded bytes that waited long enough
+    pub fn poll(&mut self) -> Result<(), io::Error> {
+        match &mut self.current {
+            Some((file, _)) => file.poll(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How the session log writes what was received
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// the bytes as they came
+    Raw,
+    /// the bytes with the time at the start of every line
+    Text,
+    /// a line of hex bytes with the time for every read
+    Hex,
+}
+
+impl LogFormat {
+    pub const ALL: &'static [Self] = &[Self::Raw, Self::Text, Self::Hex];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Text => "text",
+            Self::Hex => "hex",
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|f| f.name() == s)
+            .ok_or_else(|| format!("unknown log format `{s}`, expected raw, text or hex"))
+    }
+}
+
+/// Writes everything received to a file, for as long as it's on
+pub struct SessionLog {
+    pub path: PathBuf,
+    pub format: LogFormat,
+    file: Journal,
+    /// the next byte starts a line, so it gets a time in the text format
+    line_start: bool,
+    /// size the file is moved aside at, to start over in a new one
+    pub max_size: Option<u64>,
+    /// bytes in the file, including those not written yet
+    size: u64,
+}
+
+impl SessionLog {
+    /// opens `path` to append to, so toggling the log doesn't lose what it
+    /// has, with at most `flush` of data not on disk yet
+    pub fn open(path: PathBuf, format: LogFormat, flush: Duration) -> Result<Self, io::Error> {
+        let file = Journal::append(&path, flush)?;
+        let size = fs::metadata(&path)?.len();
+        Ok(Self {
+            path,
+            format,
+            file,
+            line_start: true,
+            max_size: None,
+            size,
+        })
+    }
+    pub fn push(&mut self, bytes: &[u8]) -> Result<(), io::Error> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let mut out = Vec::with_capacity(bytes.len() + 16);
+        let now = clock(SystemTime::now());
+        match self.format {
+            LogFormat::Raw => out.extend_from_slice(bytes),
+            LogFormat::Text => {
+                for line in bytes.split_inclusive(|&b| b == b'\n') {
+                    if self.line_start {
+                        write!(out, "[{now}] ")?;
+                    }
+                    out.extend_from_slice(line);
+                    self.line_start = line.ends_with(b"\n");
+                }
+            }
+            LogFormat::Hex => {
+                write!(out, "[{now}]")?;
+                for b in bytes {
+                    write!(out, " {b:02X}")?;
+                }
+                writeln!(out)?;
+            }
+        }
+        self.size += out.len() as u64;
+        self.file.write(&out)
+    }
+    /// once the file reached `max_size`, renames it to `<path>.<timestamp>`
+    /// and goes on in a new file at `path`, returning the renamed one
+    pub fn rotate(&mut self) -> Result<Option<PathBuf>, io::Error> {
+        if self.max_size.is_none_or(|max| self.size < max) {
+            return Ok(None);
+        }
+        self.file.flush()?;
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", timestamp(SystemTime::now())));
+        // a log filling up within a second doesn't replace the one before
+        let mut rotated = PathBuf::from(&name);
+        for n in 1.. {
+            if !rotated.exists() {
+                break;
+            }
+            let mut numbered = name.clone();
+            numbered.push(format!("-{n}"));
+            rotated = PathBuf::from(numbered);
+        }
+        fs::rename(&self.path, &rotated)?;
+        self.file = Journal::create(&self.path, self.file.interval)?;
+        self.size = 0;
+        Ok(Some(rotated))
+    }
+    /// writes logged bytes that waited long enough
+    pub fn poll(&mut self) -> Result<(), io::Error> {
+        self.file.poll()
+    }
+    /// writes every logged byte to disk
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        self.file.flush()
+    }
+}
+
+/// UTC time of day with milliseconds, like `14:25:01.123`
+pub fn clock(time: SystemTime) -> String {
+    let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since.as_secs() % 86400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        since.subsec_millis()
+    )
+}
+
+/// UTC time formatted like `20261016-142501`, sorting in time order
+pub fn timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs) = (secs / 86400, secs % 86400);
+    // days since 1970-01-01 to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -0,0 +1,466 @@
+//! Commands typed after `:`, vim style
+use crate::fuzz::parse_bytes;
+use crate::line_edit::LineEnding;
+use crate::region::View;
+use crate::session;
+use crate::window;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `[range]y[ank] [x]`: yank RX lines into register `x`
+    Yank {
+        range: Range,
+        register: Option<char>,
+    },
+    /// `[range]w[rite] <file>`: write RX lines to a file, all of them without a range
+    Write { range: Range, path: PathBuf },
+    /// `by[ank] [hex|c|rust] [x]`: yank the bytes selected in the hex view
+    /// formatted as source code, hex by default
+    YankBytes {
+        format: ByteFormat,
+        register: Option<char>,
+    },
+    /// `bw[rite] <file>`: write the bytes selected in the hex view to a file as they are
+    WriteBytes { path: PathBuf },
+    /// `mks[ession] <file>`: save the session to a file
+    SaveSession { path: PathBuf },
+    /// `ses[sion] <file>`: reopen a saved session
+    LoadSession { path: PathBuf },
+    /// `diff <file>`: compare RX against another capture
+    Diff { path: PathBuf },
+    /// `flush [rx|tx|all]`: discard what the OS buffers for the port, both
+    /// directions by default
+    Flush { buffer: Buffer },
+    /// `secret <name>`: send a secret from the OS keyring, asking for it and
+    /// storing it there the first time
+    Secret { name: String },
+    /// `gc[ode] <file>`: send a G-code file a line at a time, each after the
+    /// controller acknowledged the one before
+    GCode { path: PathBuf },
+    /// `drop [bytes]`: leave bytes like `00` or ranges like `80-9F` out of
+    /// what RX shows from now on, every byte shown again without any
+    Drop { ranges: Vec<RangeInclusive<u8>> },
+    /// `[range]vi[ew] [hex|utf8|latin1|utf16le|utf16be|<decoder>]`: show RX
+    /// lines, or the bytes selected in the hex view without a range, another
+    /// way than the pane does, hex by default
+    View { range: Option<Range>, view: View },
+    /// `sweep [rates]`: listen to the port at one baud rate after another to
+    /// find the one that looks right, the usual rates without any
+    Sweep { rates: Vec<u32> },
+    /// `trace [file]`: show what the app went through, or write it to a file
+    Trace { path: Option<PathBuf> },
+    /// `win[dow] grep <regex>|grep! <regex>|hex`: open another view of RX in
+    /// its pane, like the lines matching a pattern or a hexdump
+    Window { kind: window::Kind },
+    /// `clo[se]`: close the window shown in the RX pane
+    CloseWindow,
+    /// `pre[set] [name]`: turn a preset on or off, list them without a name
+    Preset { name: Option<String> },
+    /// `eol [cr|lf|crlf]`: set TX and RX to the line ending the device uses,
+    /// the one detected without any
+    Eol { ending: Option<LineEnding> },
+    /// `hexp[aste]`: take the next paste as hex text and send the bytes it
+    /// stands for, or stop waiting for it
+    HexPaste,
+    /// `notes`: jot down findings next to the capture, kept in the session
+    Notes,
+    /// `timeline <file>`: show RX and a session saved on another port in the
+    /// order their lines arrived
+    Timeline { path: PathBuf },
+}
+
+/// OS buffers of the port `:flush` discards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Buffer {
+    /// received but not read yet
+    Rx,
+    /// written but not on the line yet
+    Tx,
+    #[default]
+    All,
+}
+
+impl Buffer {
+    pub const ALL: &'static [Self] = &[Self::Rx, Self::Tx, Self::All];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rx => "rx",
+            Self::Tx => "tx",
+            Self::All => "all",
+        }
+    }
+}
+
+impl FromStr for Buffer {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|b| b.name() == s)
+            .ok_or_else(|| format!("unknown buffer `{s}`, expected rx, tx or all"))
+    }
+}
+
+/// How yanked bytes are written out, to paste them into code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteFormat {
+    /// `0D 0A`
+    #[default]
+    Hex,
+    /// `{ 0x0D, 0x0A }`
+    C,
+    /// `[0x0D, 0x0A]`
+    Rust,
+}
+
+impl ByteFormat {
+    pub const ALL: &'static [Self] = &[Self::Hex, Self::C, Self::Rust];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hex => "hex",
+            Self::C => "c",
+            Self::Rust => "rust",
+        }
+    }
+    pub fn format(self, bytes: &[u8]) -> String {
+        let list = |sep| {
+            bytes
+                .iter()
+                .map(|b| format!("0x{b:02X}"))
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+        match self {
+            Self::Hex => bytes
+                .iter()
+                .map(|b| format!("{b:02X}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Self::C => format!("{{ {} }}", list(", ")),
+            Self::Rust => format!("[{}]", list(", ")),
+        }
+    }
+}
+
+impl FromStr for ByteFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|f| f.name() == s)
+            .ok_or_else(|| format!("unknown format `{s}`, expected hex, c or rust"))
+    }
+}
+
+/// Inclusive range of RX lines, `:` ranges default to the current line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub from: Address,
+    pub to: Address,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Address {
+    /// 1-based line number
+    Line(usize),
+    /// `.`, the line at the top of the RX view
+    Current,
+    /// `$`
+    Last,
+    /// `'<`, the first line of the last visual selection
+    VisualStart,
+    /// `'>`, the last line of the last visual selection
+    VisualEnd,
+}
+
+/// What addresses are resolved against
+#[derive(Debug, Clone, Copy)]
+pub struct Lines {
+    pub current: usize,
+    pub len: usize,
+    /// first and last line of the last visual selection
+    pub visual: Option<(usize, usize)>,
+}
+
+impl Address {
+    /// 0-based line index
+    pub fn resolve(self, lines: Lines) -> Result<usize, String> {
+        let last = lines.len.saturating_sub(1);
+        let visual = || {
+            lines
+                .visual
+                .ok_or_else(|| String::from("no visual selection"))
+        };
+        Ok(match self {
+            Self::Line(n) => n.saturating_sub(1).min(last),
+            Self::Current => lines.current,
+            Self::Last => last,
+            Self::VisualStart => visual()?.0,
+            Self::VisualEnd => visual()?.1,
+        })
+    }
+}
+
+impl FromStr for Address {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "." => Ok(Self::Current),
+            "$" => Ok(Self::Last),
+            "'<" => Ok(Self::VisualStart),
+            "'>" => Ok(Self::VisualEnd),
+            _ => s
+                .parse()
+                .map(Self::Line)
+                .map_err(|_| format!("invalid address `{s}`")),
+        }
+    }
+}
+
+impl Range {
+    /// `%`, what `:w` writes without a range
+    pub const ALL: Self = Self {
+        from: Address::Line(1),
+        to: Address::Last,
+    };
+    pub const CURRENT: Self = Self {
+        from: Address::Current,
+        to: Address::Current,
+    };
+
+    /// first and last 0-based line, in order
+    pub fn resolve(self, lines: Lines) -> Result<(usize, usize), String> {
+        let (from, to) = (self.from.resolve(lines)?, self.to.resolve(lines)?);
+        Ok((from.min(to), from.max(to)))
+    }
+}
+
+impl FromStr for Range {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (from, to) = match s {
+            "" => return Ok(Self::CURRENT),
+            "%" => return Ok(Self::ALL),
+            _ => match s.split_once(',') {
+                Some((from, to)) => (from.trim().parse()?, to.trim().parse()?),
+                None => {
+                    let line = s.parse()?;
+                    (line, line)
+                }
+            },
+        };
+        Ok(Self { from, to })
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        // the range is everything up to the command name
+        let name_start = s
+            .find(|c: char| c.is_ascii_alphabetic())
+            .ok_or_else(|| String::from("missing command"))?;
+        let (range, rest) = s.split_at(name_start);
+        let (name, args) = rest.split_once(' ').unwrap_or((rest, ""));
+        let range_given = !range.trim().is_empty();
+        let range = range.trim().parse::<Range>()?;
+        match name {
+            "y" | "yank" => {
+                let args = args.trim();
+                let mut chars = args.chars();
+                let register = match (chars.next(), chars.next()) {
+                    (None, _) => None,
+                    (Some(c), None) if is_register(c) => Some(c),
+                    _ => return Err(format!("invalid register `{args}`")),
+                };
+                Ok(Self::Yank { range, register })
+            }
+            _ if range_given && matches!(name, "by" | "byank" | "bw" | "bwrite") => Err(
+                String::from("byte commands work on the hex view selection, not lines"),
+            ),
+            "by" | "byank" => {
+                let args = args.split_whitespace().collect::<Vec<_>>();
+                // a lone argument is the format if it names one, so `c` isn't a register
+                let (format, register) = match args[..] {
+                    [] => (ByteFormat::default(), None),
+                    [arg] => match arg.parse() {
+                        Ok(format) => (format, None),
+                        Err(_) => (ByteFormat::default(), Some(arg)),
+                    },
+                    [format, register] => (format.parse()?, Some(register)),
+                    _ => return Err(String::from("too many arguments")),
+                };
+                let register = match register {
+                    None => None,
+                    Some(r) => {
+                        let mut chars = r.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(c), None) if is_register(c) => Some(c),
+                            _ => return Err(format!("invalid register `{r}`")),
+                        }
+                    }
+                };
+                Ok(Self::YankBytes { format, register })
+            }
+            "bw" | "bwrite" => match args.trim() {
+                "" => Err(String::from("missing file name")),
+                path => Ok(Self::WriteBytes {
+                    path: PathBuf::from(path),
+                }),
+            },
+            _ if range_given && matches!(name, "mks" | "mksession" | "ses" | "session") => Err(
+                String::from("sessions are saved and opened whole, without a range"),
+            ),
+            "mks" | "mksession" => match args.trim() {
+                "" => Err(String::from("missing file name")),
+                path => Ok(Self::SaveSession {
+                    path: session::file_name(path),
+                }),
+            },
+            "ses" | "session" => match args.trim() {
+                "" => Err(String::from("missing file name")),
+                path => Ok(Self::LoadSession {
+                    path: session::file_name(path),
+                }),
+            },
+            _ if range_given && name == "diff" => {
+                Err(String::from("all of RX is compared, without a range"))
+            }
+            "diff" => match args.trim() {
+                "" => Err(String::from("missing file name")),
+                path => Ok(Self::Diff {
+                    path: PathBuf::from(path),
+                }),
+            },
+            _ if range_given && name == "secret" => Err(String::from("secrets take no range")),
+            "secret" => match args.trim() {
+                "" => Err(String::from("missing secret name")),
+                name => Ok(Self::Secret {
+                    name: name.to_owned(),
+                }),
+            },
+            _ if range_given && matches!(name, "gc" | "gcode") => {
+                Err(String::from("G-code files are sent whole, without a range"))
+            }
+            "gc" | "gcode" => match args.trim() {
+                "" => Err(String::from("missing file name")),
+                path => Ok(Self::GCode {
+                    path: PathBuf::from(path),
+                }),
+            },
+            "vi" | "view" => Ok(Self::View {
+                range: range_given.then_some(range),
+                view: match args.trim() {
+                    "" => View::Hex,
+                    view => view.parse()?,
+                },
+            }),
+            _ if range_given && name == "sweep" => Err(String::from("sweeps take no range")),
+            _ if range_given && name == "trace" => Err(String::from("the trace takes no range")),
+            _ if range_given && matches!(name, "win" | "window" | "clo" | "close") => {
+                Err(String::from("windows show all of RX, without a range"))
+            }
+            "win" | "window" => Ok(Self::Window {
+                kind: args.trim().parse()?,
+            }),
+            "clo" | "close" if args.trim().is_empty() => Ok(Self::CloseWindow),
+            "clo" | "close" => Err(String::from("close takes no argument")),
+            _ if range_given && matches!(name, "pre" | "preset") => {
+                Err(String::from("presets apply to all of RX, without a range"))
+            }
+            _ if range_given && name == "eol" => Err(String::from(
+                "line endings apply to all lines, without a range",
+            )),
+            "eol" => Ok(Self::Eol {
+                ending: match args.trim() {
+                    "" => None,
+                    "none" => {
+                        return Err(String::from(
+                            "unknown line ending `none`, expected cr, lf or crlf",
+                        ))
+                    }
+                    ending => Some(ending.parse()?),
+                },
+            }),
+            _ if range_given && matches!(name, "hexp" | "hexpaste") => {
+                Err(String::from("pastes are sent whole, without a range"))
+            }
+            "hexp" | "hexpaste" if args.trim().is_empty() => Ok(Self::HexPaste),
+            "hexp" | "hexpaste" => Err(String::from("hexpaste takes no argument")),
+            "pre" | "preset" => Ok(Self::Preset {
+                name: match args.trim() {
+                    "" => None,
+                    name => Some(name.to_owned()),
+                },
+            }),
+            _ if range_given && name == "timeline" => Err(String::from(
+                "the timeline shows all of RX, without a range",
+            )),
+            "timeline" => match args.trim() {
+                "" => Err(String::from("missing file name")),
+                path => Ok(Self::Timeline {
+                    path: session::file_name(path),
+                }),
+            },
+            _ if range_given && name == "notes" => Err(String::from("notes take no range")),
+            "notes" if args.trim().is_empty() => Ok(Self::Notes),
+            "notes" => Err(String::from("notes takes no argument")),
+            "trace" => Ok(Self::Trace {
+                path: match args.trim() {
+                    "" => None,
+                    path => Some(PathBuf::from(path)),
+                },
+            }),
+            "sweep" => Ok(Self::Sweep {
+                rates: args
+                    .split_whitespace()
+                    .map(|rate| match rate.parse() {
+                        Ok(rate) if rate > 0 => Ok(rate),
+                        _ => Err(format!("invalid baud rate `{rate}`")),
+                    })
+                    .collect::<Result<_, _>>()?,
+            }),
+            _ if range_given && name == "drop" => Err(String::from(
+                "bytes are dropped as they arrive, without a range",
+            )),
+            "drop" => Ok(Self::Drop {
+                ranges: args
+                    .split_whitespace()
+                    .map(parse_bytes)
+                    .collect::<Result<_, _>>()?,
+            }),
+            _ if range_given && name == "flush" => {
+                Err(String::from("buffers are flushed whole, without a range"))
+            }
+            "flush" => match args.trim() {
+                "" => Ok(Self::Flush {
+                    buffer: Buffer::default(),
+                }),
+                buffer => Ok(Self::Flush {
+                    buffer: buffer.parse()?,
+                }),
+            },
+            "w" | "write" => match args.trim() {
+                "" => Err(String::from("missing file name")),
+                path => Ok(Self::Write {
+                    range: if range_given { range } else { Range::ALL },
+                    path: PathBuf::from(path),
+                }),
+            },
+            _ => Err(format!("unknown command `{name}`")),
+        }
+    }
+}
+
+/// registers are named `a` to `z`, `"` is the unnamed one every yank goes to
+pub fn is_register(c: char) -> bool {
+    c.is_ascii_lowercase() || c == '"'
+}
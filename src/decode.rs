@@ -0,0 +1,237 @@
+//! Protocol decoders turning the RX byte stream into readable records
+pub mod midi;
+pub mod sbus;
+pub mod slcan;
+pub mod ubx;
+pub mod xbee;
+
+use serde::{Deserialize, Serialize};
+use serialport::{DataBits, Parity, StopBits};
+use std::fmt;
+use std::str::FromStr;
+
+/// A decoded frame or message, one line in the decoded RX view
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub text: String,
+    /// set for malformed frames or failed checksums
+    pub error: bool,
+    /// named values of the frame, like `id` or `status`, for `--highlight`
+    pub fields: Vec<(&'static str, i64)>,
+}
+
+impl Record {
+    pub fn ok(text: String) -> Self {
+        Self {
+            text,
+            error: false,
+            fields: Vec::new(),
+        }
+    }
+    pub fn error(text: String) -> Self {
+        Self {
+            text,
+            error: true,
+            fields: Vec::new(),
+        }
+    }
+    pub fn field(mut self, name: &'static str, value: impl Into<i64>) -> Self {
+        self.fields.push((name, value.into()));
+        self
+    }
+}
+
+pub trait Decoder {
+    /// feeds received bytes, pushing every completed record to `out`
+    fn push(&mut self, bytes: &[u8], out: &mut Vec<Record>);
+    /// column header shown above the records
+    fn header(&self) -> Option<&'static str> {
+        None
+    }
+    /// live state shown pinned above the records, e.g. the latest channel values
+    fn live(&self) -> Vec<Record> {
+        Vec::new()
+    }
+    /// example input for the frame builder, `None` if the protocol can't be sent
+    fn encode_hint(&self) -> Option<&'static str> {
+        None
+    }
+    /// builds the bytes of a frame from the user's frame builder input
+    fn encode(&self, _input: &str) -> Result<Vec<u8>, String> {
+        Err(String::from("sending frames is not supported"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSettings {
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+}
+
+impl LineSettings {
+    fn data_bits(&self) -> u32 {
+        match self.data_bits {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        }
+    }
+    /// bytes per second the line carries at most, counting start, parity and stop bits
+    pub fn byte_rate(&self) -> f64 {
+        let parity = match self.parity {
+            Parity::None => 0,
+            Parity::Odd | Parity::Even => 1,
+        };
+        let stop_bits = match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        self.baud_rate as f64 / (1 + self.data_bits() + parity + stop_bits) as f64
+    }
+}
+
+/// formatted like `100000 8E2`
+impl fmt::Display for LineSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data_bits = self.data_bits();
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+        let stop_bits = match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        write!(f, "{} {data_bits}{parity}{stop_bits}", self.baud_rate)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecoderKind {
+    Slcan,
+    Midi,
+    Ubx,
+    Sbus,
+    Xbee,
+}
+
+impl DecoderKind {
+    pub const ALL: &'static [Self] = &[Self::Slcan, Self::Midi, Self::Ubx, Self::Sbus, Self::Xbee];
+
+    pub fn build(self) -> Box<dyn Decoder> {
+        match self {
+            Self::Slcan => Box::<slcan::Slcan>::default(),
+            Self::Midi => Box::<midi::Midi>::default(),
+            Self::Ubx => Box::<ubx::Ubx>::default(),
+            Self::Sbus => Box::<sbus::Sbus>::default(),
+            Self::Xbee => Box::<xbee::Xbee>::default(),
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Slcan => "slcan",
+            Self::Midi => "midi",
+            Self::Ubx => "ubx",
+            Self::Sbus => "sbus",
+            Self::Xbee => "xbee",
+        }
+    }
+    /// line settings the protocol mandates, applied when the decoder is selected
+    pub fn line_settings(self) -> Option<LineSettings> {
+        match self {
+            Self::Sbus => Some(LineSettings {
+                baud_rate: 100_000,
+                data_bits: DataBits::Eight,
+                parity: Parity::Even,
+                stop_bits: StopBits::Two,
+            }),
+            _ => None,
+        }
+    }
+    /// next decoder when cycling through them, `None` meaning no decoder
+    pub fn cycle(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Self::ALL.first().copied(),
+            Some(kind) => {
+                let i = Self::ALL.iter().position(|&k| k == kind).unwrap_or(0);
+                Self::ALL.get(i + 1).copied()
+            }
+        }
+    }
+}
+
+impl fmt::Display for DecoderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DecoderKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|k| k.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown decoder `{s}`"))
+    }
+}
+
+/// parses a string of hex digit pairs, ignoring whitespace
+pub fn parse_hex(s: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if digits.len() & 1 == 1 {
+        return Err(format!("odd number of hex digits in `{s}`"));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| format!("invalid hex byte `{}`", String::from_utf8_lossy(pair)))
+        })
+        .collect()
+}
+
+/// formats bytes as space separated hex pairs
+pub fn hex_string(bytes: &[u8]) -> String {
+    use fmt::Write;
+    let mut s = String::with_capacity(bytes.len() * 3);
+    for (i, b) in bytes.iter().enumerate() {
+        if i > 0 {
+            s.push(' ');
+        }
+        write!(s, "{b:02X}").unwrap();
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(parse_hex("DEad BE ef"), Ok(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+        assert_eq!(parse_hex(" "), Ok(vec![]));
+        assert!(parse_hex("ABC").is_err());
+        assert!(parse_hex("zz").is_err());
+        // `from_str_radix` alone would take the sign
+        assert!(parse_hex("+1").is_err());
+        assert!(parse_hex("-1").is_err());
+    }
+
+    #[test]
+    fn hex_string_parses_back() {
+        let bytes = [0x00, 0x7F, 0xFF];
+        assert_eq!(hex_string(&bytes), "00 7F FF");
+        assert_eq!(parse_hex(&hex_string(&bytes)), Ok(bytes.to_vec()));
+    }
+}
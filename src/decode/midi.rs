@@ -0,0 +1,228 @@
+//! MIDI byte streams, e.g. from a DIN-MIDI to serial adapter
+use super::{hex_string, Decoder, Record};
+
+#[derive(Default)]
+pub struct Midi {
+    /// status of the message being received, kept between messages for running status
+    status: Option<u8>,
+    data: Vec<u8>,
+    sysex: Option<Vec<u8>>,
+}
+
+impl Decoder for Midi {
+    fn push(&mut self, bytes: &[u8], out: &mut Vec<Record>) {
+        for &b in bytes {
+            match b {
+                // real time messages may appear anywhere, even inside other messages
+                0xF8..=0xFF => out.push(Record::ok(format!("--  {}", realtime_name(b)))),
+                0xF0 => {
+                    self.status = None;
+                    self.sysex = Some(Vec::new());
+                }
+                0xF7 => match self.sysex.take() {
+                    Some(data) => out.push(Record::ok(format!(
+                        "--  SysEx            {}",
+                        hex_string(&data)
+                    ))),
+                    None => out.push(Record::error(String::from("--  stray SysEx end"))),
+                },
+                0x80..=0xEF | 0xF1..=0xF6 => {
+                    if let Some(data) = self.sysex.take() {
+                        out.push(Record::error(format!(
+                            "--  unterminated SysEx {}",
+                            hex_string(&data)
+                        )));
+                    }
+                    if !self.data.is_empty() {
+                        out.push(Record::error(format!(
+                            "--  incomplete message {:02X} {}",
+                            self.status.unwrap_or(0),
+                            hex_string(&self.data)
+                        )));
+                    }
+                    self.status = Some(b);
+                    self.data.clear();
+                    self.complete(out);
+                }
+                _ => {
+                    if let Some(sysex) = &mut self.sysex {
+                        sysex.push(b);
+                    } else if self.status.is_some() {
+                        self.data.push(b);
+                        self.complete(out);
+                    } else {
+                        out.push(Record::error(format!("--  stray data byte {b:02X}")));
+                    }
+                }
+            }
+        }
+    }
+    fn header(&self) -> Option<&'static str> {
+        Some("CH  EVENT            DATA")
+    }
+}
+
+impl Midi {
+    /// emits the current message if all of its data bytes arrived
+    fn complete(&mut self, out: &mut Vec<Record>) {
+        let Some(status) = self.status else { return };
+        if self.data.len() < data_len(status) {
+            return;
+        }
+        let mut record = Record::ok(describe(status, &self.data)).field("status", status);
+        if status < 0xF0 {
+            record = record.field("channel", (status & 0x0F) + 1);
+        }
+        for (name, &b) in ["data1", "data2"].into_iter().zip(&self.data) {
+            record = record.field(name, b);
+        }
+        out.push(record);
+        self.data.clear();
+        // system common messages cancel running status
+        if status >= 0xF0 {
+            self.status = None;
+        }
+    }
+}
+
+fn data_len(status: u8) -> usize {
+    match status & 0xF0 {
+        0xC0 | 0xD0 => 1,
+        0xF0 => match status {
+            0xF1 | 0xF3 => 1,
+            0xF2 => 2,
+            _ => 0,
+        },
+        _ => 2,
+    }
+}
+
+fn describe(status: u8, data: &[u8]) -> String {
+    let ch = (status & 0x0F) + 1;
+    match status & 0xF0 {
+        // note on with velocity 0 is a note off
+        0x90 if data[1] == 0 => format!("{ch:<2}  Note Off         {}", note_name(data[0])),
+        0x80 => format!(
+            "{ch:<2}  Note Off         {} vel {}",
+            note_name(data[0]),
+            data[1]
+        ),
+        0x90 => format!(
+            "{ch:<2}  Note On          {} vel {}",
+            note_name(data[0]),
+            data[1]
+        ),
+        0xA0 => format!(
+            "{ch:<2}  Poly Aftertouch  {} {}",
+            note_name(data[0]),
+            data[1]
+        ),
+        0xB0 => format!(
+            "{ch:<2}  Control Change   {} = {}",
+            controller_name(data[0]),
+            data[1]
+        ),
+        0xC0 => format!("{ch:<2}  Program Change   {}", data[0]),
+        0xD0 => format!("{ch:<2}  Channel Pressure {}", data[0]),
+        0xE0 => {
+            let bend = ((data[1] as i32) << 7 | data[0] as i32) - 0x2000;
+            format!("{ch:<2}  Pitch Bend       {bend:+}")
+        }
+        _ => match status {
+            0xF1 => format!("--  MTC Quarter    {:02X}", data[0]),
+            0xF2 => format!(
+                "--  Song Position  {}",
+                (data[1] as u16) << 7 | data[0] as u16
+            ),
+            0xF3 => format!("--  Song Select    {}", data[0]),
+            0xF6 => String::from("--  Tune Request"),
+            _ => format!("--  Undefined {status:02X}"),
+        },
+    }
+}
+
+fn realtime_name(status: u8) -> &'static str {
+    match status {
+        0xF8 => "Clock",
+        0xFA => "Start",
+        0xFB => "Continue",
+        0xFC => "Stop",
+        0xFE => "Active Sensing",
+        0xFF => "Reset",
+        _ => "Undefined",
+    }
+}
+
+/// note name with octave, middle C (60) being C4
+fn note_name(note: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+    ];
+    format!("{}{}", NAMES[note as usize % 12], note as i32 / 12 - 1)
+}
+
+fn controller_name(cc: u8) -> String {
+    let name = match cc {
+        0 => "Bank Select",
+        1 => "Modulation",
+        7 => "Volume",
+        10 => "Pan",
+        11 => "Expression",
+        64 => "Sustain",
+        120 => "All Sound Off",
+        121 => "Reset Controllers",
+        123 => "All Notes Off",
+        _ => return format!("CC{cc}"),
+    };
+    format!("CC{cc} {name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<Record> {
+        let mut out = Vec::new();
+        Midi::default().push(bytes, &mut out);
+        out
+    }
+
+    #[test]
+    fn decodes_messages_with_running_status() {
+        let records = decode(&[0x91, 60, 100, 62, 0]);
+        assert_eq!(records.len(), 2);
+        assert!(records[0].text.contains("Note On          C4 vel 100"));
+        assert!(records[0].fields.contains(&("channel", 2)));
+        assert!(records[0].fields.contains(&("data2", 100)));
+        // running status, velocity 0 is a note off
+        assert!(records[1].text.contains("Note Off         D4"));
+        assert!(records[1].fields.contains(&("status", 0x91)));
+    }
+
+    #[test]
+    fn realtime_inside_a_message() {
+        let records = decode(&[0xB0, 7, 0xF8, 90]);
+        assert_eq!(records.len(), 2);
+        assert!(records[0].text.contains("Clock"));
+        assert!(records[1].text.contains("CC7 Volume = 90"));
+    }
+
+    #[test]
+    fn sysex() {
+        let records = decode(&[0xF0, 0x7E, 0x01, 0xF7, 0xF7]);
+        assert_eq!(records[0].text, "--  SysEx            7E 01");
+        assert!(!records[0].error);
+        assert!(records[1].error);
+    }
+
+    #[test]
+    fn resyncs_after_garbage() {
+        // stray data, then a message cut short by the next status byte
+        let records = decode(&[0x12, 0x34, 0x90, 60, 0xC0, 5]);
+        assert_eq!(records.len(), 4);
+        assert!(records[0].error && records[1].error);
+        assert!(records[2].text.contains("incomplete message 90 3C"));
+        assert!(!records[3].error);
+        assert!(records[3].text.contains("Program Change   5"));
+    }
+}
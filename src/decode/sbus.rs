@@ -0,0 +1,215 @@
+//! SBUS frames from RC receivers
+//!
+//! SBUS runs at 100000 baud 8E2 with inverted logic, so the receiver has to be
+//! connected through an inverter or an adapter that supports inverting RX.
+use super::{hex_string, Decoder, Record};
+
+const FRAME_LEN: usize = 25;
+const HEADER: u8 = 0x0F;
+const CHANNELS: usize = 16;
+/// width of the channel bars in the live view
+const BAR_WIDTH: usize = 32;
+/// full range of the 11 bit channel values
+const MAX_VALUE: u16 = 2047;
+
+#[derive(Default)]
+pub struct Sbus {
+    buf: Vec<u8>,
+    last: Option<Frame>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    channels: [u16; CHANNELS],
+    ch17: bool,
+    ch18: bool,
+    frame_lost: bool,
+    failsafe: bool,
+}
+
+impl Decoder for Sbus {
+    fn push(&mut self, bytes: &[u8], out: &mut Vec<Record>) {
+        self.buf.extend_from_slice(bytes);
+        while let Some(start) = self.buf.iter().position(|&b| b == HEADER) {
+            if start > 0 {
+                out.push(Record::error(format!(
+                    "?     {}",
+                    hex_string(&self.buf[..start])
+                )));
+                self.buf.drain(..start);
+            }
+            if self.buf.len() < FRAME_LEN {
+                return;
+            }
+            if !is_footer(self.buf[FRAME_LEN - 1]) {
+                // not a frame start after all, resync on the next header
+                out.push(Record::error(String::from("?     0F")));
+                self.buf.drain(..1);
+                continue;
+            }
+            let frame = Frame::decode(&self.buf[..FRAME_LEN]);
+            out.push(frame.record());
+            self.last = Some(frame);
+            self.buf.drain(..FRAME_LEN);
+        }
+        if !self.buf.is_empty() {
+            out.push(Record::error(format!("?     {}", hex_string(&self.buf))));
+            self.buf.clear();
+        }
+    }
+    fn header(&self) -> Option<&'static str> {
+        Some("SBUS is 100000 baud 8E2 with inverted logic, use an inverting adapter")
+    }
+    fn live(&self) -> Vec<Record> {
+        let Some(frame) = &self.last else {
+            return vec![Record::ok(String::from("waiting for frames..."))];
+        };
+        frame
+            .channels
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| Record::ok(format!("CH{:02} {} {v:>4}", i + 1, bar(v))))
+            .collect()
+    }
+}
+
+/// SBUS2 receivers use the upper nibble of the footer for telemetry slots
+fn is_footer(b: u8) -> bool {
+    b & 0x0F == 0x04 || b == 0x00
+}
+
+impl Frame {
+    fn decode(frame: &[u8]) -> Self {
+        let mut channels = [0; CHANNELS];
+        // 16 channels of 11 bits each, packed LSB first
+        let mut bits: u32 = 0;
+        let mut n_bits = 0;
+        let mut ch = 0;
+        for &b in &frame[1..23] {
+            bits |= (b as u32) << n_bits;
+            n_bits += 8;
+            while n_bits >= 11 && ch < CHANNELS {
+                channels[ch] = (bits & 0x7FF) as u16;
+                bits >>= 11;
+                n_bits -= 11;
+                ch += 1;
+            }
+        }
+        let flags = frame[23];
+        Self {
+            channels,
+            ch17: flags & 0x01 != 0,
+            ch18: flags & 0x02 != 0,
+            frame_lost: flags & 0x04 != 0,
+            failsafe: flags & 0x08 != 0,
+        }
+    }
+    fn record(&self) -> Record {
+        const NAMES: [&str; CHANNELS] = [
+            "ch1", "ch2", "ch3", "ch4", "ch5", "ch6", "ch7", "ch8", "ch9", "ch10", "ch11", "ch12",
+            "ch13", "ch14", "ch15", "ch16",
+        ];
+        let mut text = String::from("SBUS ");
+        for v in self.channels {
+            text.push_str(&format!(" {v:>4}"));
+        }
+        text.push_str(&format!("  {}{}", self.ch17 as u8, self.ch18 as u8));
+        if self.frame_lost {
+            text.push_str("  LOST");
+        }
+        let mut record = match self.failsafe {
+            true => Record::error(format!("{text}  FAILSAFE")),
+            false => Record::ok(text),
+        };
+        for (name, v) in NAMES.into_iter().zip(self.channels) {
+            record = record.field(name, v);
+        }
+        record
+            .field("ch17", self.ch17)
+            .field("ch18", self.ch18)
+            .field("lost", self.frame_lost)
+            .field("failsafe", self.failsafe)
+    }
+}
+
+fn bar(value: u16) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = value.min(MAX_VALUE) as usize * BAR_WIDTH * 8 / MAX_VALUE as usize;
+    let mut s = "█".repeat(eighths / 8);
+    if eighths / 8 < BAR_WIDTH {
+        s.push(PARTIAL[eighths % 8]);
+    }
+    let pad = BAR_WIDTH - s.chars().count();
+    s.push_str(&" ".repeat(pad));
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// packs channels into a frame the way a receiver does
+    fn frame(channels: [u16; CHANNELS], flags: u8) -> Vec<u8> {
+        let mut out = vec![HEADER];
+        let (mut bits, mut n_bits) = (0u32, 0);
+        for v in channels {
+            bits |= (v as u32 & 0x7FF) << n_bits;
+            n_bits += 11;
+            while n_bits >= 8 {
+                out.push(bits as u8);
+                bits >>= 8;
+                n_bits -= 8;
+            }
+        }
+        out.extend_from_slice(&[flags, 0x00]);
+        out
+    }
+
+    fn channels() -> [u16; CHANNELS] {
+        std::array::from_fn(|i| i as u16 * 127 + 1)
+    }
+
+    #[test]
+    fn decodes_channels_and_flags() {
+        let bytes = frame(channels(), 0x05);
+        assert_eq!(bytes.len(), FRAME_LEN);
+        let mut out = Vec::new();
+        Sbus::default().push(&bytes, &mut out);
+        assert_eq!(out.len(), 1);
+        let r = &out[0];
+        assert!(!r.error);
+        assert!(r.fields.contains(&("ch1", 1)));
+        assert!(r.fields.contains(&("ch16", 15 * 127 + 1)));
+        assert!(r.fields.contains(&("ch17", 1)));
+        assert!(r.fields.contains(&("lost", 1)));
+        assert!(r.text.contains("LOST"));
+    }
+
+    #[test]
+    fn flags_failsafe() {
+        let mut out = Vec::new();
+        Sbus::default().push(&frame(channels(), 0x08), &mut out);
+        assert!(out[0].error);
+        assert!(out[0].fields.contains(&("failsafe", 1)));
+    }
+
+    #[test]
+    fn resyncs_after_garbage() {
+        let good = frame(channels(), 0);
+        // garbage, then a header byte whose frame has no footer
+        let mut bytes = vec![0x12, 0x34, HEADER, 0x55];
+        bytes.extend_from_slice(&[0x22; FRAME_LEN]);
+        bytes.extend_from_slice(&good);
+        let mut sbus = Sbus::default();
+        let mut out = Vec::new();
+        // split inside the good frame, which waits for the rest
+        let (a, b) = bytes.split_at(bytes.len() - 5);
+        sbus.push(a, &mut out);
+        sbus.push(b, &mut out);
+        let last = out.pop().unwrap();
+        assert!(!last.error);
+        assert!(last.fields.contains(&("ch2", 128)));
+        assert!(!out.is_empty() && out.iter().all(|r| r.error));
+        assert!(sbus.live()[1].text.ends_with(" 128"));
+    }
+}
@@ -0,0 +1,208 @@
+//! slcan / LAWICEL ASCII protocol spoken by most CAN-USB adapters
+use super::{hex_string, parse_hex, Decoder, Record};
+
+const BELL: u8 = 0x07;
+
+#[derive(Default)]
+pub struct Slcan {
+    line: Vec<u8>,
+}
+
+impl Decoder for Slcan {
+    fn push(&mut self, bytes: &[u8], out: &mut Vec<Record>) {
+        for &b in bytes {
+            match b {
+                b'\r' => {
+                    out.push(decode_line(&self.line));
+                    self.line.clear();
+                }
+                BELL => {
+                    self.line.clear();
+                    out.push(Record::error(String::from("ERROR (BEL)")));
+                }
+                b'\n' => (),
+                _ => self.line.push(b),
+            }
+        }
+    }
+    fn header(&self) -> Option<&'static str> {
+        Some("TYPE  ID        DLC  DATA")
+    }
+    fn encode_hint(&self) -> Option<&'static str> {
+        Some("ID#DATA, e.g. 123#DEADBEEF, 1F334455#R")
+    }
+    fn encode(&self, input: &str) -> Result<Vec<u8>, String> {
+        let (id, data) = input
+            .trim()
+            .split_once('#')
+            .ok_or_else(|| String::from("expected ID#DATA"))?;
+        let id_val = hex_u32(id).ok_or_else(|| format!("invalid ID `{id}`"))?;
+        let extended = id.len() > 3;
+        if (!extended && id_val > 0x7FF) || id_val > 0x1FFF_FFFF {
+            return Err(format!("ID `{id}` out of range"));
+        }
+        let mut out = if extended {
+            format!("T{id_val:08X}")
+        } else {
+            format!("t{id_val:03X}")
+        };
+        if let Some(dlc) = data.strip_prefix(['R', 'r']) {
+            // remote frame, optionally with a requested length
+            let dlc: u8 = match dlc {
+                "" => 0,
+                _ if dlc.bytes().all(|b| b.is_ascii_digit()) => {
+                    dlc.parse().map_err(|_| format!("invalid DLC `{dlc}`"))?
+                }
+                _ => return Err(format!("invalid DLC `{dlc}`")),
+            };
+            if dlc > 8 {
+                return Err(String::from("DLC must be at most 8"));
+            }
+            out.replace_range(..1, if extended { "R" } else { "r" });
+            out.push_str(&dlc.to_string());
+        } else {
+            let data = parse_hex(data)?;
+            if data.len() > 8 {
+                return Err(String::from("at most 8 data bytes"));
+            }
+            out.push_str(&data.len().to_string());
+            for b in data {
+                out.push_str(&format!("{b:02X}"));
+            }
+        }
+        out.push('\r');
+        Ok(out.into_bytes())
+    }
+}
+
+fn decode_line(line: &[u8]) -> Record {
+    let Ok(line) = std::str::from_utf8(line) else {
+        return Record::error(format!("?     {}", hex_string(line)));
+    };
+    let (kind, id_len, rtr) = match line.chars().next() {
+        Some('t') => ("STD", 3, false),
+        Some('T') => ("EXT", 8, false),
+        Some('r') => ("STD", 3, true),
+        Some('R') => ("EXT", 8, true),
+        // acknowledgement of a sent frame, or of any other command
+        Some('z' | 'Z') | None => return Record::ok(String::from("OK")),
+        Some('V' | 'v') => return Record::ok(format!("VERSION {}", &line[1..])),
+        Some('N') => return Record::ok(format!("SERIAL {}", &line[1..])),
+        Some('F') => return Record::ok(format!("STATUS {}", &line[1..])),
+        _ => return Record::error(format!("?     {line}")),
+    };
+    match decode_frame(&line[1..], id_len, rtr) {
+        Some((id, dlc, data)) => {
+            let text = if rtr {
+                String::from("remote request")
+            } else {
+                hex_string(&data)
+            };
+            let mut record = Record::ok(format!("{kind}   {id:<8X}  {dlc:<3}  {text}"))
+                .field("id", id)
+                .field("dlc", dlc)
+                .field("rtr", rtr);
+            for (name, &b) in ["d0", "d1", "d2", "d3", "d4", "d5", "d6", "d7"]
+                .into_iter()
+                .zip(&data)
+            {
+                record = record.field(name, b);
+            }
+            record
+        }
+        None => Record::error(format!("{kind}   malformed frame `{line}`")),
+    }
+}
+
+/// `s` as a hex number, which `from_str_radix` would also take with a sign
+fn hex_u32(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(s, 16).ok()
+}
+
+/// decodes id, dlc and data of a frame after the type character
+fn decode_frame(s: &str, id_len: usize, rtr: bool) -> Option<(u32, u8, Vec<u8>)> {
+    let id = hex_u32(s.get(..id_len)?)?;
+    let dlc = s.get(id_len..id_len + 1)?.parse::<u8>().ok()?;
+    if dlc > 8 {
+        return None;
+    }
+    let data = if rtr {
+        Vec::new()
+    } else {
+        // an optional timestamp may follow, so only take what the DLC says
+        parse_hex(s.get(id_len + 1..id_len + 1 + dlc as usize * 2)?).ok()?
+    };
+    Some((id, dlc, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<Record> {
+        let mut out = Vec::new();
+        Slcan::default().push(bytes, &mut out);
+        out
+    }
+
+    #[test]
+    fn round_trips_frames() {
+        let frame = Slcan::default().encode("123#DEADBEEF").unwrap();
+        assert_eq!(frame, b"t1234DEADBEEF\r");
+        let records = decode(&frame);
+        assert_eq!(records.len(), 1);
+        let r = &records[0];
+        assert!(!r.error);
+        assert!(r.fields.contains(&("id", 0x123)));
+        assert!(r.fields.contains(&("dlc", 4)));
+        assert!(r.fields.contains(&("d0", 0xDE)));
+        assert!(r.fields.contains(&("d3", 0xEF)));
+
+        let frame = Slcan::default().encode("1F334455#R").unwrap();
+        assert_eq!(frame, b"R1F3344550\r");
+        let records = decode(&frame);
+        assert!(!records[0].error);
+        assert!(records[0].fields.contains(&("id", 0x1F33_4455)));
+        assert!(records[0].fields.contains(&("rtr", 1)));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let slcan = Slcan::default();
+        for bad in [
+            "123",
+            "800#00",
+            "+12#00",
+            "-1#00",
+            "123#R9",
+            "123#001122334455667788",
+        ] {
+            assert!(slcan.encode(bad).is_err(), "`{bad}` encoded");
+        }
+    }
+
+    #[test]
+    fn flags_malformed_frames() {
+        // signs and a DLC beyond the data aren't frames
+        for bad in [&b"t+1210\r"[..], b"t12340011\r", b"t1239\r", b"?\r"] {
+            let records = decode(bad);
+            assert!(records[0].error, "{:?} decoded", records[0].text);
+        }
+    }
+
+    #[test]
+    fn resyncs_after_garbage() {
+        let mut slcan = Slcan::default();
+        let mut out = Vec::new();
+        slcan.push(b"\x01\xFFgarbage\r\x07t12", &mut out);
+        slcan.push(b"31AA\r\n", &mut out);
+        assert_eq!(out.len(), 3);
+        assert!(out[0].error);
+        assert_eq!(out[1].text, "ERROR (BEL)");
+        assert!(!out[2].error);
+        assert!(out[2].fields.contains(&("d0", 0xAA)));
+    }
+}
@@ -0,0 +1,241 @@
+//! u-blox UBX binary protocol, with interleaved NMEA sentences passed through
+use super::{hex_string, parse_hex, Decoder, Record};
+
+const SYNC: [u8; 2] = [0xB5, 0x62];
+/// NMEA sentences are at most 82 characters, anything longer is garbage
+const MAX_NMEA: usize = 128;
+/// longer lengths are assumed to be a false sync rather than waited for
+const MAX_UBX: usize = 8192;
+
+#[derive(Default)]
+pub struct Ubx {
+    buf: Vec<u8>,
+}
+
+impl Decoder for Ubx {
+    fn push(&mut self, bytes: &[u8], out: &mut Vec<Record>) {
+        self.buf.extend_from_slice(bytes);
+        loop {
+            let consumed = match self.buf.first() {
+                None => break,
+                Some(&b'$') => match self.buf.iter().position(|&b| b == b'\n') {
+                    Some(end) => {
+                        out.push(nmea(&self.buf[..=end]));
+                        end + 1
+                    }
+                    None if self.buf.len() > MAX_NMEA => {
+                        out.push(Record::error(String::from("NMEA  unterminated sentence")));
+                        1
+                    }
+                    None => break,
+                },
+                Some(&b) if b == SYNC[0] => {
+                    if self.buf.len() < 2 {
+                        break;
+                    }
+                    if self.buf[1] != SYNC[1] {
+                        out.push(Record::error(String::from("?     B5")));
+                        1
+                    } else if self.buf.len() < 6 {
+                        break;
+                    } else {
+                        let len = u16::from_le_bytes([self.buf[4], self.buf[5]]) as usize;
+                        if len > MAX_UBX {
+                            out.push(Record::error(String::from("UBX   implausible length")));
+                            2
+                        } else if self.buf.len() < len + 8 {
+                            break;
+                        } else {
+                            out.push(ubx(&self.buf[..len + 8]));
+                            len + 8
+                        }
+                    }
+                }
+                Some(_) => {
+                    // skip to the next thing that looks like the start of a message
+                    let skip = self
+                        .buf
+                        .iter()
+                        .position(|&b| b == b'$' || b == SYNC[0])
+                        .unwrap_or(self.buf.len());
+                    let garbage = &self.buf[..skip];
+                    // stray line endings between messages are not worth a record
+                    if garbage.iter().any(|&b| b != b'\r' && b != b'\n') {
+                        out.push(Record::error(format!("?     {}", hex_string(garbage))));
+                    }
+                    skip
+                }
+            };
+            self.buf.drain(..consumed);
+        }
+    }
+    fn header(&self) -> Option<&'static str> {
+        Some("TYPE  MESSAGE       LEN  PAYLOAD")
+    }
+    fn encode_hint(&self) -> Option<&'static str> {
+        Some("CLASS ID PAYLOAD in hex, e.g. 0A 04 to poll MON-VER")
+    }
+    fn encode(&self, input: &str) -> Result<Vec<u8>, String> {
+        let body = parse_hex(input)?;
+        if body.len() < 2 {
+            return Err(String::from("expected at least class and ID"));
+        }
+        let len = (body.len() - 2) as u16;
+        let mut frame = SYNC.to_vec();
+        frame.extend_from_slice(&body[..2]);
+        frame.extend_from_slice(&len.to_le_bytes());
+        frame.extend_from_slice(&body[2..]);
+        let (a, b) = checksum(&frame[2..]);
+        frame.extend_from_slice(&[a, b]);
+        Ok(frame)
+    }
+}
+
+/// 8-bit Fletcher checksum over class, id, length and payload
+fn checksum(bytes: &[u8]) -> (u8, u8) {
+    bytes.iter().fold((0u8, 0u8), |(a, b), &x| {
+        let a = a.wrapping_add(x);
+        (a, b.wrapping_add(a))
+    })
+}
+
+fn ubx(frame: &[u8]) -> Record {
+    let (class, id) = (frame[2], frame[3]);
+    let payload = &frame[6..frame.len() - 2];
+    let name = message_name(class, id);
+    let text = format!(
+        "UBX   {name:<12}  {:<3}  {}",
+        payload.len(),
+        hex_string(payload)
+    );
+    let ck = &frame[frame.len() - 2..];
+    let record = if checksum(&frame[2..frame.len() - 2]) == (ck[0], ck[1]) {
+        Record::ok(text)
+    } else {
+        Record::error(format!("{text}  (bad checksum)"))
+    };
+    record
+        .field("class", class)
+        .field("id", id)
+        .field("len", payload.len() as i64)
+}
+
+fn nmea(line: &[u8]) -> Record {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim_end();
+    let Some((body, ck)) = line[1..].rsplit_once('*') else {
+        return Record::error(format!("NMEA  {line}  (no checksum)"));
+    };
+    let sum = body.bytes().fold(0u8, |a, b| a ^ b);
+    if u8::from_str_radix(ck, 16) == Ok(sum) {
+        Record::ok(format!("NMEA  {line}"))
+    } else {
+        Record::error(format!("NMEA  {line}  (bad checksum)"))
+    }
+}
+
+fn message_name(class: u8, id: u8) -> String {
+    let class_name = match class {
+        0x01 => "NAV",
+        0x02 => "RXM",
+        0x04 => "INF",
+        0x05 => "ACK",
+        0x06 => "CFG",
+        0x09 => "UPD",
+        0x0A => "MON",
+        0x0D => "TIM",
+        0x10 => "ESF",
+        0x13 => "MGA",
+        0x21 => "LOG",
+        0x27 => "SEC",
+        _ => return format!("{class:02X}-{id:02X}"),
+    };
+    let id_name = match (class, id) {
+        (0x01, 0x02) => "POSLLH",
+        (0x01, 0x03) => "STATUS",
+        (0x01, 0x07) => "PVT",
+        (0x01, 0x21) => "TIMEUTC",
+        (0x01, 0x35) => "SAT",
+        (0x04, 0x00) => "ERROR",
+        (0x04, 0x01) => "WARNING",
+        (0x04, 0x02) => "NOTICE",
+        (0x05, 0x00) => "NAK",
+        (0x05, 0x01) => "ACK",
+        (0x06, 0x00) => "PRT",
+        (0x06, 0x01) => "MSG",
+        (0x06, 0x04) => "RST",
+        (0x06, 0x08) => "RATE",
+        (0x06, 0x8A) => "VALSET",
+        (0x06, 0x8B) => "VALGET",
+        (0x0A, 0x04) => "VER",
+        (0x0A, 0x09) => "HW",
+        (0x0D, 0x01) => "TP",
+        _ => return format!("{class_name}-{id:02X}"),
+    };
+    format!("{class_name}-{id_name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<Record> {
+        let mut out = Vec::new();
+        Ubx::default().push(bytes, &mut out);
+        out
+    }
+
+    #[test]
+    fn round_trips_frames() {
+        let poll = Ubx::default().encode("0A 04").unwrap();
+        assert_eq!(poll, [0xB5, 0x62, 0x0A, 0x04, 0x00, 0x00, 0x0E, 0x34]);
+        let frame = Ubx::default().encode("05 01 06 8A").unwrap();
+        let records = decode(&frame);
+        assert_eq!(records.len(), 1);
+        assert!(!records[0].error);
+        assert!(records[0].text.contains("ACK-ACK"));
+        assert!(records[0].fields.contains(&("len", 2)));
+        assert!(Ubx::default().encode("0A").is_err());
+    }
+
+    #[test]
+    fn flags_bad_checksums() {
+        let mut frame = Ubx::default().encode("05 01 06 8A").unwrap();
+        *frame.last_mut().unwrap() ^= 1;
+        let records = decode(&frame);
+        assert!(records[0].error);
+        assert!(records[0].text.ends_with("(bad checksum)"));
+
+        let records = decode(b"$GPTXT,hello*00\r\n");
+        assert!(records[0].error);
+    }
+
+    #[test]
+    fn passes_nmea_through() {
+        let body = "GPTXT,01,01,02,hello";
+        let sum = body.bytes().fold(0u8, |a, b| a ^ b);
+        let records = decode(format!("${body}*{sum:02X}\r\n").as_bytes());
+        assert_eq!(records.len(), 1);
+        assert!(!records[0].error);
+    }
+
+    #[test]
+    fn resyncs_after_garbage() {
+        let frame = Ubx::default().encode("0A 04").unwrap();
+        let mut bytes = b"\r\nnoise".to_vec();
+        bytes.extend_from_slice(&[0xB5, 0x00]);
+        bytes.extend_from_slice(&frame);
+        let mut ubx = Ubx::default();
+        let mut out = Vec::new();
+        // split inside the frame, which waits for the rest
+        let (a, b) = bytes.split_at(bytes.len() - 3);
+        ubx.push(a, &mut out);
+        // the noise, a false sync and the byte after it
+        assert_eq!(out.len(), 3);
+        ubx.push(b, &mut out);
+        assert_eq!(out.len(), 4);
+        assert!(out[0].error && out[1].error && out[2].error);
+        assert!(!out[3].error);
+        assert!(out[3].text.contains("MON-VER"));
+    }
+}
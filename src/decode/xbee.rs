@@ -0,0 +1,298 @@
+//! XBee / Zigbee API frames (API mode 1, without escaping)
+use super::{hex_string, parse_hex, Decoder, Record};
+
+const DELIMITER: u8 = 0x7E;
+/// longer lengths are assumed to be a false start rather than waited for
+const MAX_LEN: usize = 512;
+
+/// frame id of frames built with the `AT` shortcut, non zero so the module responds
+const AT_FRAME_ID: u8 = 0x01;
+
+#[derive(Default)]
+pub struct Xbee {
+    buf: Vec<u8>,
+}
+
+impl Decoder for Xbee {
+    fn push(&mut self, bytes: &[u8], out: &mut Vec<Record>) {
+        self.buf.extend_from_slice(bytes);
+        loop {
+            let skip = self
+                .buf
+                .iter()
+                .position(|&b| b == DELIMITER)
+                .unwrap_or(self.buf.len());
+            if skip > 0 {
+                out.push(Record::error(format!(
+                    "?                       {}",
+                    hex_string(&self.buf[..skip])
+                )));
+                self.buf.drain(..skip);
+            }
+            if self.buf.len() < 3 {
+                return;
+            }
+            let len = u16::from_be_bytes([self.buf[1], self.buf[2]]) as usize;
+            if len == 0 || len > MAX_LEN {
+                out.push(Record::error(String::from("?                       7E")));
+                self.buf.drain(..1);
+                continue;
+            }
+            if self.buf.len() < len + 4 {
+                return;
+            }
+            let data = &self.buf[3..3 + len];
+            let text = describe(data);
+            let record = if checksum(data) == self.buf[3 + len] {
+                Record::ok(text)
+            } else {
+                Record::error(format!("{text}  (bad checksum)"))
+            };
+            out.push(with_fields(record, data));
+            self.buf.drain(..len + 4);
+        }
+    }
+    fn header(&self) -> Option<&'static str> {
+        Some("TYPE                 ID  DETAILS")
+    }
+    fn encode_hint(&self) -> Option<&'static str> {
+        Some("TYPE DATA in hex, or AT <cmd> [hex param], e.g. AT NI")
+    }
+    fn encode(&self, input: &str) -> Result<Vec<u8>, String> {
+        let input = input.trim();
+        let data = match input.get(..3) {
+            Some(at) if at.eq_ignore_ascii_case("AT ") => {
+                let rest = input[3..].trim_start();
+                let (Some(cmd), Some(param)) = (rest.get(..2), rest.get(2..)) else {
+                    return Err(String::from("expected a two letter AT command"));
+                };
+                let mut data = vec![0x08, AT_FRAME_ID];
+                data.extend_from_slice(cmd.to_ascii_uppercase().as_bytes());
+                data.extend(parse_hex(param)?);
+                data
+            }
+            _ => parse_hex(input)?,
+        };
+        if data.is_empty() || data.len() > MAX_LEN {
+            return Err(String::from("frame data must be 1 to 512 bytes"));
+        }
+        let mut frame = vec![DELIMITER];
+        frame.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&data);
+        frame.push(checksum(&data));
+        Ok(frame)
+    }
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    0xFF - data.iter().fold(0u8, |a, &b| a.wrapping_add(b))
+}
+
+/// the frame type, its frame id and the status of responses as fields
+fn with_fields(record: Record, data: &[u8]) -> Record {
+    let kind = data[0];
+    let d = &data[1..];
+    let mut record = record.field("type", kind).field("len", data.len() as i64);
+    let status = match kind {
+        0x88 => d.get(3),
+        0x8B => d.get(4),
+        0x8A => d.first(),
+        _ => None,
+    };
+    if let Some(&status) = status {
+        record = record.field("status", status);
+    }
+    if matches!(kind, 0x08 | 0x09 | 0x88 | 0x8B | 0x10) {
+        if let Some(&id) = d.first() {
+            record = record.field("frame_id", id);
+        }
+    }
+    record
+}
+
+fn describe(data: &[u8]) -> String {
+    let kind = data[0];
+    let d = &data[1..];
+    let name = frame_name(kind);
+    // frame id is the first byte of every frame type that has one
+    let id = || d.first().map(|id| format!("{id:<2}")).unwrap_or_default();
+    let details = match kind {
+        0x08 | 0x09 if d.len() >= 3 => format!(
+            "{}  {}{}",
+            id(),
+            String::from_utf8_lossy(&d[1..3]),
+            param(&d[3..])
+        ),
+        0x88 if d.len() >= 4 => format!(
+            "{}  {} {}{}",
+            id(),
+            String::from_utf8_lossy(&d[1..3]),
+            at_status(d[3]),
+            param(&d[4..])
+        ),
+        0x8A if !d.is_empty() => format!("    {}", modem_status(d[0])),
+        0x8B if d.len() >= 6 => format!(
+            "{}  to {:02X}{:02X} retries {} {}",
+            id(),
+            d[1],
+            d[2],
+            d[3],
+            delivery_status(d[4])
+        ),
+        0x10 if d.len() >= 13 => format!(
+            "{}  to {} / {:02X}{:02X}  {}",
+            id(),
+            hex_string(&d[1..9]).replace(' ', ""),
+            d[9],
+            d[10],
+            payload(&d[13..])
+        ),
+        0x90 if d.len() >= 11 => format!(
+            "    from {} / {:02X}{:02X}  {}",
+            hex_string(&d[..8]).replace(' ', ""),
+            d[8],
+            d[9],
+            payload(&d[11..])
+        ),
+        _ => format!("    {}", hex_string(d)),
+    };
+    format!("{name:<19}  {details}")
+}
+
+fn frame_name(kind: u8) -> String {
+    let name = match kind {
+        0x08 => "AT Command",
+        0x09 => "AT Command Queue",
+        0x10 => "Transmit Request",
+        0x11 => "Explicit Transmit",
+        0x17 => "Remote AT Command",
+        0x88 => "AT Response",
+        0x8A => "Modem Status",
+        0x8B => "Transmit Status",
+        0x90 => "Receive Packet",
+        0x91 => "Explicit Receive",
+        0x95 => "Node Identification",
+        0x97 => "Remote AT Response",
+        _ => return format!("Frame {kind:02X}"),
+    };
+    name.to_owned()
+}
+
+fn param(p: &[u8]) -> String {
+    if p.is_empty() {
+        String::new()
+    } else {
+        format!(" = {}", hex_string(p))
+    }
+}
+
+fn payload(p: &[u8]) -> String {
+    if p.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        format!("\"{}\"", String::from_utf8_lossy(p))
+    } else {
+        hex_string(p)
+    }
+}
+
+fn at_status(status: u8) -> &'static str {
+    match status {
+        0 => "OK",
+        1 => "ERROR",
+        2 => "INVALID COMMAND",
+        3 => "INVALID PARAMETER",
+        4 => "TX FAILURE",
+        _ => "UNKNOWN STATUS",
+    }
+}
+
+fn modem_status(status: u8) -> String {
+    let s = match status {
+        0x00 => "hardware reset",
+        0x01 => "watchdog reset",
+        0x02 => "joined network",
+        0x03 => "disassociated",
+        0x06 => "coordinator started",
+        0x07 => "network security key updated",
+        0x0D => "voltage supply limit exceeded",
+        0x11 => "configuration changed during join",
+        _ => return format!("status {status:02X}"),
+    };
+    s.to_owned()
+}
+
+fn delivery_status(status: u8) -> String {
+    let s = match status {
+        0x00 => "delivered",
+        0x01 => "MAC ACK failure",
+        0x02 => "CCA failure",
+        0x15 => "invalid destination endpoint",
+        0x21 => "network ACK failure",
+        0x22 => "not joined to network",
+        0x24 => "address not found",
+        0x25 => "route not found",
+        0x74 => "payload too large",
+        _ => return format!("delivery status {status:02X}"),
+    };
+    s.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<Record> {
+        let mut out = Vec::new();
+        Xbee::default().push(bytes, &mut out);
+        out
+    }
+
+    #[test]
+    fn builds_at_commands() {
+        let frame = Xbee::default().encode("AT NI").unwrap();
+        assert_eq!(frame, [0x7E, 0x00, 0x04, 0x08, 0x01, 0x4E, 0x49, 0x5F]);
+        let frame = Xbee::default().encode("at d0 04").unwrap();
+        assert_eq!(frame[3..8], [0x08, 0x01, b'D', b'0', 0x04]);
+        assert!(Xbee::default().encode("AT N").is_err());
+        assert!(Xbee::default().encode("").is_err());
+    }
+
+    #[test]
+    fn round_trips_frames() {
+        // an AT response with a 0x0A in its parameter
+        let frame = Xbee::default().encode("88 01 4E 49 00 0A 62").unwrap();
+        let records = decode(&frame);
+        assert_eq!(records.len(), 1);
+        let r = &records[0];
+        assert!(!r.error);
+        assert!(r.text.starts_with("AT Response"));
+        assert!(r.fields.contains(&("type", 0x88)));
+        assert!(r.fields.contains(&("frame_id", 1)));
+        assert!(r.fields.contains(&("status", 0)));
+    }
+
+    #[test]
+    fn flags_bad_checksums() {
+        let mut frame = Xbee::default().encode("8A 02").unwrap();
+        *frame.last_mut().unwrap() ^= 0xFF;
+        let records = decode(&frame);
+        assert!(records[0].error);
+        assert!(records[0].text.ends_with("(bad checksum)"));
+    }
+
+    #[test]
+    fn resyncs_after_garbage() {
+        let good = Xbee::default().encode("8A 02").unwrap();
+        // garbage, then a delimiter with an impossible length
+        let mut bytes = vec![0x11, 0x22, 0x7E, 0x00, 0x00];
+        bytes.extend_from_slice(&good);
+        let mut xbee = Xbee::default();
+        let mut out = Vec::new();
+        let (a, b) = bytes.split_at(bytes.len() - 2);
+        xbee.push(a, &mut out);
+        xbee.push(b, &mut out);
+        let last = out.pop().unwrap();
+        assert!(!last.error);
+        assert!(last.text.contains("joined network"));
+        assert!(!out.is_empty() && out.iter().all(|r| r.error));
+    }
+}
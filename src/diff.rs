@@ -0,0 +1,385 @@
+//! Comparing RX against another capture line by line, like a good boot against
+//! a failing one, with the timestamps of both left out
+use crate::decode::parse_hex;
+use crate::screen::overlay::{Outcome, Overlay};
+use crate::session::{self, Session};
+use crate::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Paragraph, Widget},
+};
+use regex::Regex;
+use std::path::Path;
+
+/// Most lines added or removed the diff looks for, beyond that the differing
+/// part is shown as replaced as a whole. It bounds the time and memory taken
+const MAX_EDITS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tag {
+    Same,
+    /// only in the capture compared against
+    Removed,
+    /// only in RX
+    Added,
+}
+
+/// bytes of a capture: RX of a session file, the bytes of a hex log or the
+/// file as it is
+pub fn load(path: &Path) -> Result<Vec<u8>, String> {
+    if path.extension().is_some_and(|e| e == session::EXTENSION) {
+        return parse_hex(&Session::load(path)?.rx);
+    }
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    Ok(from_hex_log(&bytes).unwrap_or(bytes))
+}
+
+/// bytes logged with `--log-format hex`, `None` if it isn't such a log
+fn from_hex_log(log: &[u8]) -> Option<Vec<u8>> {
+    let line = Regex::new(r"^\[\d\d:\d\d:\d\d\.\d{3}\]((?: [0-9A-F]{2})+)$").unwrap();
+    let text = std::str::from_utf8(log).ok()?;
+    let mut bytes = Vec::new();
+    let mut lines = text.lines().filter(|l| !l.is_empty()).peekable();
+    lines.peek()?;
+    for l in lines {
+        bytes.extend(parse_hex(line.captures(l)?.get(1)?.as_str()).ok()?);
+    }
+    Some(bytes)
+}
+
+/// lines of a capture without line breaks and timestamps at their start, the
+/// ones of text logs like `[14:25:01.123] ` and kernel ones like `[    1.234567] `
+pub fn lines(bytes: &[u8]) -> Vec<String> {
+    let time = Regex::new(r"^\[[0-9 .:]+\] ?").unwrap();
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(|l| time.replace(l, "").into_owned())
+        .collect()
+}
+
+/// the lines of `a` and `b` tagged as both have them, or only one, in order
+pub fn diff<'a>(a: &'a [String], b: &'a [String]) -> Vec<(Tag, &'a str)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a_rest, b_rest) = (&a[prefix..], &b[prefix..]);
+    let suffix = a_rest
+        .iter()
+        .rev()
+        .zip(b_rest.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (
+        &a_rest[..a_rest.len() - suffix],
+        &b_rest[..b_rest.len() - suffix],
+    );
+    let mut out = Vec::with_capacity(a.len().max(b.len()));
+    out.extend(a[..prefix].iter().map(|l| (Tag::Same, l.as_str())));
+    match myers(a_mid, b_mid) {
+        Some(mid) => out.extend(mid),
+        None => {
+            out.extend(a_mid.iter().map(|l| (Tag::Removed, l.as_str())));
+            out.extend(b_mid.iter().map(|l| (Tag::Added, l.as_str())));
+        }
+    }
+    out.extend(
+        a_rest[a_rest.len() - suffix..]
+            .iter()
+            .map(|l| (Tag::Same, l.as_str())),
+    );
+    out
+}
+
+/// shortest edit script from `a` to `b` with Myers' algorithm, `None` when it
+/// takes more than `MAX_EDITS`
+fn myers<'a>(a: &'a [String], b: &'a [String]) -> Option<Vec<(Tag, &'a str)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(MAX_EDITS) as isize;
+    // furthest x reached on every diagonal k = x - y, indexed by k + offset
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // v for -d..=d after every step d, to walk the path back
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let at = |v: &[isize], k: isize| v[(k + offset) as usize];
+    let mut end = None;
+    'search: for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && at(&v, k - 1) < at(&v, k + 1)) {
+                at(&v, k + 1)
+            } else {
+                at(&v, k - 1) + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(k + offset) as usize] = x;
+            if x >= n && y >= m {
+                trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+                end = Some(d);
+                break 'search;
+            }
+        }
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+    }
+    let end = end?;
+
+    let mut out = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..=end).rev() {
+        let prev = &trace[d as usize - 1];
+        let prev_at = |k: isize| prev[(k + d - 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && prev_at(k - 1) < prev_at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = prev_at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            out.push((Tag::Same, a[x as usize].as_str()));
+        }
+        if prev_k == k + 1 {
+            out.push((Tag::Added, b[prev_y as usize].as_str()));
+        } else {
+            out.push((Tag::Removed, a[prev_x as usize].as_str()));
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        out.push((Tag::Same, a[x as usize].as_str()));
+    }
+    out.reverse();
+    Some(out)
+}
+
+/// A row of the side by side view: the line of the other capture and of RX
+type Row = (Option<String>, Option<String>);
+
+/// Diff of another capture against RX, unified or side by side
+pub struct DiffView {
+    title: String,
+    unified: Vec<(Tag, String)>,
+    side: Vec<Row>,
+    /// rows of the unified view where a change starts, and of the side by side one
+    changes: (Vec<usize>, Vec<usize>),
+    side_by_side: bool,
+    /// first row shown
+    top: usize,
+    /// rows shown on the last draw, for paging
+    height: std::cell::Cell<usize>,
+}
+
+impl DiffView {
+    /// compares the lines of `other`, named `name`, against the ones of `rx`
+    pub fn new(name: &str, other: &[String], rx: &[String]) -> Self {
+        let unified = diff(other, rx)
+            .into_iter()
+            .map(|(tag, l)| (tag, l.to_owned()))
+            .collect::<Vec<_>>();
+        // removed lines are paired with the added lines right after them
+        let mut side: Vec<Row> = Vec::new();
+        let mut side_changes = Vec::new();
+        let mut i = 0;
+        while i < unified.len() {
+            if unified[i].0 == Tag::Same {
+                side.push((Some(unified[i].1.clone()), Some(unified[i].1.clone())));
+                i += 1;
+                continue;
+            }
+            side_changes.push(side.len());
+            let removed = unified[i..]
+                .iter()
+                .take_while(|(t, _)| *t == Tag::Removed)
+                .map(|(_, l)| l.clone())
+                .collect::<Vec<_>>();
+            i += removed.len();
+            let added = unified[i..]
+                .iter()
+                .take_while(|(t, _)| *t == Tag::Added)
+                .map(|(_, l)| l.clone())
+                .collect::<Vec<_>>();
+            i += added.len();
+            let (mut removed, mut added) = (removed.into_iter(), added.into_iter());
+            loop {
+                match (removed.next(), added.next()) {
+                    (None, None) => break,
+                    row => side.push(row),
+                }
+            }
+        }
+        let unified_changes = (0..unified.len())
+            .filter(|&i| unified[i].0 != Tag::Same && (i == 0 || unified[i - 1].0 == Tag::Same))
+            .collect();
+        Self {
+            title: format!("Diff: {name} (-) against RX (+)"),
+            unified,
+            side,
+            changes: (unified_changes, side_changes),
+            side_by_side: false,
+            top: 0,
+            height: std::cell::Cell::new(1),
+        }
+    }
+    fn rows(&self) -> usize {
+        match self.side_by_side {
+            true => self.side.len(),
+            false => self.unified.len(),
+        }
+    }
+    fn changes(&self) -> &[usize] {
+        match self.side_by_side {
+            true => &self.changes.1,
+            false => &self.changes.0,
+        }
+    }
+}
+
+impl Overlay for DiffView {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn size(&self) -> (u16, u16) {
+        (90, 80)
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let last = self.rows().saturating_sub(1);
+        let page = self.height.get().max(1);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Outcome::Cancel,
+            KeyCode::Char('d') if ctrl => self.top += page / 2,
+            KeyCode::Char('u') if ctrl => self.top = self.top.saturating_sub(page / 2),
+            KeyCode::Char('j') | KeyCode::Down => self.top += 1,
+            KeyCode::Char('k') | KeyCode::Up => self.top = self.top.saturating_sub(1),
+            KeyCode::PageDown => self.top += page,
+            KeyCode::PageUp => self.top = self.top.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => self.top = 0,
+            KeyCode::Char('G') | KeyCode::End => self.top = last,
+            KeyCode::Char('n') => {
+                if let Some(&row) = self.changes().iter().find(|&&row| row > self.top) {
+                    self.top = row;
+                }
+            }
+            KeyCode::Char('N') => {
+                if let Some(&row) = self.changes().iter().rev().find(|&&row| row < self.top) {
+                    self.top = row;
+                }
+            }
+            KeyCode::Char('s') => {
+                // stay at the same change, or near the same place
+                let passed = self.changes().partition_point(|&row| row <= self.top);
+                self.side_by_side = !self.side_by_side;
+                self.top = match passed.checked_sub(1) {
+                    Some(i) => self.changes()[i],
+                    None => 0,
+                };
+            }
+            _ => (),
+        }
+        self.top = self.top.min(last);
+        Outcome::Continue
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let height = area.height.saturating_sub(1) as usize;
+        self.height.set(height);
+        let style = |tag| match tag {
+            Tag::Same => Style::default(),
+            Tag::Removed => Style::default().fg(theme.removed),
+            Tag::Added => Style::default().fg(theme.added),
+        };
+        let body = Rect {
+            height: height as u16,
+            ..area
+        };
+        if self.side_by_side {
+            let half = area.width.saturating_sub(1) / 2;
+            let cell = |line: &Option<String>, tag| match line {
+                Some(l) => Span::styled(
+                    l.chars().take(half as usize).collect::<String>(),
+                    style(tag),
+                ),
+                None => Span::raw(""),
+            };
+            let (mut left, mut right) = (Vec::new(), Vec::new());
+            for (a, b) in self.side.iter().skip(self.top).take(height) {
+                let changed = a != b;
+                left.push(Spans::from(cell(
+                    a,
+                    if changed { Tag::Removed } else { Tag::Same },
+                )));
+                right.push(Spans::from(cell(
+                    b,
+                    if changed { Tag::Added } else { Tag::Same },
+                )));
+            }
+            let separator = vec![Spans::from("│"); left.len()];
+            Paragraph::new(left).render(
+                Rect {
+                    width: half,
+                    ..body
+                },
+                buf,
+            );
+            Paragraph::new(separator).render(
+                Rect {
+                    x: body.x + half,
+                    width: 1.min(body.width),
+                    ..body
+                },
+                buf,
+            );
+            Paragraph::new(right).render(
+                Rect {
+                    x: body.x + half + 1,
+                    width: body.width.saturating_sub(half + 1),
+                    ..body
+                },
+                buf,
+            );
+        } else {
+            let lines = self
+                .unified
+                .iter()
+                .skip(self.top)
+                .take(height)
+                .map(|(tag, l)| {
+                    let sign = match tag {
+                        Tag::Same => ' ',
+                        Tag::Removed => '-',
+                        Tag::Added => '+',
+                    };
+                    Spans::from(Span::styled(format!("{sign} {l}"), style(*tag)))
+                })
+                .collect::<Vec<_>>();
+            Paragraph::new(lines).render(body, buf);
+        }
+        let summary = match self.changes.0.len() {
+            0 => String::from("no differences"),
+            1 => String::from("1 change"),
+            n => format!("{n} changes"),
+        };
+        let hint = format!(
+            "{summary} | n/N: next/previous change | s: {} | q: close",
+            if self.side_by_side {
+                "unified"
+            } else {
+                "side by side"
+            }
+        );
+        let footer = Rect {
+            y: area.bottom().saturating_sub(1),
+            height: area.height.min(1),
+            ..area
+        };
+        Paragraph::new(Span::styled(hint, Style::default().fg(theme.hint))).render(footer, buf);
+    }
+}
@@ -1,4 +1,4 @@
-use serialport::{DataBits, SerialPort};
+use serialport::{DataBits, Parity, SerialPort, StopBits};
 use std::collections::VecDeque;
 use std::io::{Read, Write};
 use std::time::Duration;
@@ -8,6 +8,10 @@ pub struct DummySerial {
     buffer: VecDeque<u8>,
     baud_rate: u32,
     data_bits: DataBits,
+    parity: Parity,
+    stop_bits: StopBits,
+    dtr: bool,
+    rts: bool,
 }
 
 impl DummySerial {
@@ -16,6 +20,10 @@ impl DummySerial {
             buffer: VecDeque::new(),
             baud_rate,
             data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            dtr: true,
+            rts: true,
         }
     }
 }
@@ -92,36 +100,41 @@ impl SerialPort for DummySerial {
         Ok(())
     }
     fn parity(&self) -> serialport::Result<serialport::Parity> {
-        todo!()
+        Ok(self.parity)
     }
-    fn set_parity(&mut self, _parity: serialport::Parity) -> serialport::Result<()> {
-        todo!()
+    fn set_parity(&mut self, parity: serialport::Parity) -> serialport::Result<()> {
+        self.parity = parity;
+        Ok(())
     }
+    // wired back to itself like a null modem: DTR drives DSR and CD, RTS drives CTS
     fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
-        todo!()
+        Ok(self.dtr)
     }
     fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
-        todo!()
+        Ok(self.rts)
     }
     fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
-        todo!()
+        Ok(self.dtr)
     }
     fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
-        todo!()
+        Ok(false)
     }
-    fn set_stop_bits(&mut self, _stop_bits: serialport::StopBits) -> serialport::Result<()> {
-        todo!()
+    fn set_stop_bits(&mut self, stop_bits: serialport::StopBits) -> serialport::Result<()> {
+        self.stop_bits = stop_bits;
+        Ok(())
     }
     fn stop_bits(&self) -> serialport::Result<serialport::StopBits> {
-        todo!()
+        Ok(self.stop_bits)
     }
     fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
         Ok(Box::new(self.clone()))
     }
-    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
-        todo!()
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()> {
+        self.dtr = level;
+        Ok(())
     }
-    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
-        todo!()
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        self.rts = level;
+        Ok(())
     }
 }
@@ -0,0 +1,50 @@
+//! Telling how the device ends its lines from what it sends, for setting TX
+//! and RX to match without guessing
+use crate::line_edit::LineEnding;
+
+/// Line endings counted in RX
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineStats {
+    pub cr: u64,
+    pub lf: u64,
+    pub crlf: u64,
+    /// the last byte was a CR, which an LF may still follow
+    after_cr: bool,
+}
+
+impl LineStats {
+    /// lines seen before there's a verdict
+    const MIN_LINES: u64 = 4;
+    /// percentage of the lines that have to end the same way
+    const AGREE: u64 = 80;
+
+    /// counts the line endings in newly received `bytes`
+    pub fn push(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            match b {
+                b'\n' if self.after_cr => self.crlf += 1,
+                b'\n' => self.lf += 1,
+                // a CR that nothing but another CR followed
+                b'\r' if self.after_cr => self.cr += 1,
+                _ if self.after_cr => self.cr += 1,
+                _ => (),
+            }
+            self.after_cr = b == b'\r';
+        }
+    }
+    /// how most lines end, once there were enough of them and they agree
+    pub fn detected(&self) -> Option<LineEnding> {
+        let total = self.cr + self.lf + self.crlf;
+        if total < Self::MIN_LINES {
+            return None;
+        }
+        let (n, ending) = [
+            (self.crlf, LineEnding::CrLf),
+            (self.lf, LineEnding::Lf),
+            (self.cr, LineEnding::Cr),
+        ]
+        .into_iter()
+        .max_by_key(|&(n, _)| n)?;
+        (n * 100 >= total * Self::AGREE).then_some(ending)
+    }
+}
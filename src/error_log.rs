@@ -0,0 +1,112 @@
+//! Non-fatal errors, kept for the error panel instead of ending the session
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+pub struct ErrorLog {
+    entries: VecDeque<Entry>,
+    start: Instant,
+    /// errors logged since the panel was last opened
+    unseen: usize,
+    /// errors logged by source over the whole session, clearing doesn't reset them
+    totals: BTreeMap<&'static str, usize>,
+    /// errors logged since they were last taken for the trace, repeats included
+    fresh: Vec<String>,
+}
+
+pub struct Entry {
+    /// time since the session started
+    pub at: Duration,
+    /// what failed, like `write` or `decode`
+    pub source: &'static str,
+    pub text: String,
+    /// times the error happened in a row
+    pub count: u32,
+}
+
+impl ErrorLog {
+    /// oldest errors are dropped beyond this
+    const MAX: usize = 1000;
+
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            start: Instant::now(),
+            unseen: 0,
+            totals: BTreeMap::new(),
+            fresh: Vec::new(),
+        }
+    }
+    /// logs an error, counting repeats of the last one instead of adding it again
+    pub fn push(&mut self, source: &'static str, error: impl fmt::Display) {
+        let text = error.to_string();
+        let at = self.start.elapsed();
+        self.unseen += 1;
+        *self.totals.entry(source).or_default() += 1;
+        self.fresh.push(format!("{source}: {text}"));
+        if let Some(last) = self.entries.back_mut() {
+            if last.source == source && last.text == text {
+                last.count += 1;
+                last.at = at;
+                return;
+            }
+        }
+        if self.entries.len() == Self::MAX {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            at,
+            source,
+            text,
+            count: 1,
+        });
+    }
+    /// errors logged since the last call, as `source: text`
+    pub fn take_fresh(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.fresh)
+    }
+    pub fn unseen(&self) -> usize {
+        self.unseen
+    }
+    pub fn totals(&self) -> &BTreeMap<&'static str, usize> {
+        &self.totals
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.unseen = 0;
+    }
+    /// entries as lines for the error panel, marking them as seen
+    pub fn lines(&mut self) -> Vec<String> {
+        self.unseen = 0;
+        self.entries.iter().map(Entry::to_string).collect()
+    }
+}
+
+impl Default for ErrorLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// formatted as `+hh:mm:ss source: text (xN)`
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.at.as_secs();
+        write!(
+            f,
+            "+{:02}:{:02}:{:02} {}: {}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            self.source,
+            self.text
+        )?;
+        if self.count > 1 {
+            write!(f, " (x{})", self.count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Sends random or mutated byte sequences while enabled
+pub struct Fuzzer {
+    pub seed: u64,
+    pub len: RangeInclusive<usize>,
+    pub bytes: RangeInclusive<u8>,
+    pub interval: Duration,
+    /// when set, cases are mutations of this payload instead of random bytes
+    pub base: Option<Vec<u8>>,
+    pub enabled: bool,
+    log: Option<File>,
+    rng: XorShift,
+    cases: u64,
+    last: Instant,
+}
+
+impl Fuzzer {
+    pub fn new(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(1)
+        });
+        Self {
+            seed,
+            len: 1..=16,
+            bytes: 0..=255,
+            interval: Duration::from_millis(100),
+            base: None,
+            enabled: false,
+            log: None,
+            rng: XorShift::new(seed),
+            cases: 0,
+            last: Instant::now(),
+        }
+    }
+    /// logs every case as a hex line to `file`, starting with the seed so a run can be reproduced
+    pub fn log_to(&mut self, mut file: File) -> io::Result<()> {
+        writeln!(file, "# seed {}", self.seed)?;
+        self.log = Some(file);
+        Ok(())
+    }
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+    /// number of cases sent so far
+    pub fn cases(&self) -> u64 {
+        self.cases
+    }
+    /// returns the next case if it is due
+    pub fn poll(&mut self) -> Option<Vec<u8>> {
+        if !self.enabled || self.last.elapsed() < self.interval {
+            return None;
+        }
+        self.last = Instant::now();
+        let case = match self.base.clone() {
+            Some(base) => self.mutate(base),
+            None => self.random(),
+        };
+        self.cases += 1;
+        Some(case)
+    }
+    /// logs the last case as it went out on the wire, after TX mapped LF to
+    /// CR + LF if it does
+    pub fn log_case(&mut self, wire: &[u8]) -> io::Result<()> {
+        let Some(log) = &mut self.log else {
+            return Ok(());
+        };
+        write!(log, "{}:", self.cases)?;
+        for b in wire {
+            write!(log, " {b:02X}")?;
+        }
+        writeln!(log)
+    }
+
+    fn random(&mut self) -> Vec<u8> {
+        let len = self
+            .rng
+            .in_range(*self.len.start() as u64, *self.len.end() as u64) as usize;
+        (0..len).map(|_| self.random_byte()).collect()
+    }
+    fn mutate(&mut self, mut case: Vec<u8>) -> Vec<u8> {
+        // the base is brought inside the length and byte ranges first
+        case.truncate(*self.len.end());
+        for b in &mut case {
+            if !self.bytes.contains(b) {
+                *b = self.random_byte();
+            }
+        }
+        while case.len() < *self.len.start() {
+            let b = self.random_byte();
+            case.push(b);
+        }
+        let mutations = self.rng.in_range(1, 4);
+        for _ in 0..mutations {
+            let pos = self.rng.in_range(0, case.len() as u64) as usize;
+            match self.rng.in_range(0, 3) {
+                // flip a bit
+                0 if pos < case.len() => {
+                    let flipped = case[pos] ^ 1 << self.rng.in_range(0, 7);
+                    case[pos] = match self.bytes.contains(&flipped) {
+                        true => flipped,
+                        false => self.random_byte(),
+                    };
+                }
+                // replace a byte
+                1 if pos < case.len() => case[pos] = self.random_byte(),
+                // delete a byte
+                2 if pos < case.len() && case.len() > *self.len.start() => {
+                    case.remove(pos);
+                }
+                // insert a byte
+                _ if case.len() < *self.len.end() => case.insert(pos, self.random_byte()),
+                _ => (),
+            }
+        }
+        case
+    }
+    fn random_byte(&mut self) -> u8 {
+        self.rng
+            .in_range(*self.bytes.start() as u64, *self.bytes.end() as u64) as u8
+    }
+}
+
+/// xorshift64* generator, good enough for test traffic and reproducible from the seed
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // a splitmix64 step, so every seed starts somewhere else, and the
+        // state must never be zero
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Self((z ^ (z >> 31)).max(1))
+    }
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+    /// random number in `lo..=hi`
+    fn in_range(&mut self, lo: u64, hi: u64) -> u64 {
+        if hi <= lo {
+            return lo;
+        }
+        lo + self.next() % (hi - lo + 1)
+    }
+}
+
+/// parses `min-max` or a single length for `--fuzz-len`
+pub fn parse_len(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let (lo, hi) = s.split_once('-').unwrap_or((s, s));
+    let lo: usize = lo.parse().map_err(|_| format!("invalid length `{lo}`"))?;
+    let hi: usize = hi.parse().map_err(|_| format!("invalid length `{hi}`"))?;
+    if lo == 0 || lo > hi {
+        return Err(format!("invalid length range `{s}`"));
+    }
+    Ok(lo..=hi)
+}
+
+/// parses a hex byte range like `20-7E` for `--fuzz-bytes`
+pub fn parse_bytes(s: &str) -> Result<RangeInclusive<u8>, String> {
+    let (lo, hi) = s.split_once('-').unwrap_or((s, s));
+    let lo = u8::from_str_radix(lo, 16).map_err(|_| format!("invalid byte `{lo}`"))?;
+    let hi = u8::from_str_radix(hi, 16).map_err(|_| format!("invalid byte `{hi}`"))?;
+    if lo > hi {
+        return Err(format!("invalid byte range `{s}`"));
+    }
+    Ok(lo..=hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fuzzer(seed: u64, base: Option<&[u8]>) -> Fuzzer {
+        let mut fuzzer = Fuzzer::new(Some(seed));
+        fuzzer.interval = Duration::ZERO;
+        fuzzer.base = base.map(<[u8]>::to_vec);
+        fuzzer.enabled = true;
+        fuzzer
+    }
+
+    fn cases(fuzzer: &mut Fuzzer, n: usize) -> Vec<Vec<u8>> {
+        (0..n).map(|_| fuzzer.poll().unwrap()).collect()
+    }
+
+    #[test]
+    fn seed_reproduces_cases() {
+        for base in [None, Some(&b"AT+GMR"[..])] {
+            let run = cases(&mut fuzzer(42, base), 100);
+            assert_eq!(run, cases(&mut fuzzer(42, base), 100));
+            assert_ne!(run, cases(&mut fuzzer(43, base), 100));
+        }
+        assert_ne!(cases(&mut fuzzer(0, None), 10), cases(&mut fuzzer(1, None), 10));
+    }
+
+    #[test]
+    fn random_cases_stay_in_ranges() {
+        let mut fuzzer = fuzzer(7, None);
+        fuzzer.len = 3..=5;
+        fuzzer.bytes = 0x20..=0x7E;
+        for case in cases(&mut fuzzer, 1000) {
+            assert!((3..=5).contains(&case.len()), "{case:02X?}");
+            assert!(case.iter().all(|b| (0x20..=0x7E).contains(b)), "{case:02X?}");
+        }
+    }
+
+    #[test]
+    fn mutated_cases_stay_in_ranges() {
+        let bases: [&[u8]; 3] = [b"AT", b"\xC1\x00ATI\xFF\x80 too long", b""];
+        for (seed, base) in bases.into_iter().enumerate() {
+            let mut fuzzer = fuzzer(seed as u64, Some(base));
+            fuzzer.len = 2..=8;
+            fuzzer.bytes = 0x20..=0x7E;
+            for case in cases(&mut fuzzer, 1000) {
+                assert!((2..=8).contains(&case.len()), "{case:02X?}");
+                assert!(case.iter().all(|b| (0x20..=0x7E).contains(b)), "{case:02X?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Sending G-code to 3D printer and CNC controllers: a line at a time, each
+//! once the one before was acknowledged, like Marlin's and GRBL's `ok`
+use std::time::Instant;
+
+/// What a line from the controller means for the job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Ok,
+    /// GRBL's `error:N`, which takes the place of `ok`
+    Rejected(String),
+    /// Marlin's `Error:...`, an `ok` or a resend request follows it
+    Error(String),
+    /// the controller wants the lines again from this line number
+    Resend(u32),
+    /// Marlin's `!!`, the printer stopped
+    Halted(String),
+}
+
+impl Reply {
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line == "ok" || line.starts_with("ok ") {
+            Some(Self::Ok)
+        } else if line.starts_with("error:") {
+            Some(Self::Rejected(line.to_owned()))
+        } else if line.starts_with("Error:") {
+            Some(Self::Error(line.to_owned()))
+        } else if let Some(n) = line
+            .strip_prefix("Resend:")
+            .or_else(|| line.strip_prefix("rs "))
+        {
+            n.trim().parse().ok().map(Self::Resend)
+        } else if line.starts_with("!!") {
+            Some(Self::Halted(line.to_owned()))
+        } else {
+            None
+        }
+    }
+}
+
+/// `line` without its `;` and `(...)` comments, blanks squeezed
+pub fn strip_comments(line: &str) -> String {
+    let line = line.split(';').next().unwrap_or_default();
+    let mut out = String::with_capacity(line.len());
+    let mut depth = 0;
+    for c in line.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            c if depth == 0 => out.push(c),
+            _ => (),
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `cmd` numbered as line `n` with Marlin's checksum, the XOR of every byte
+/// before the `*`
+pub fn numbered(n: u32, cmd: &str) -> String {
+    let line = format!("N{n} {cmd}");
+    let checksum = line.bytes().fold(0, |acc, b| acc ^ b);
+    format!("{line}*{checksum}")
+}
+
+/// A G-code file being sent
+pub struct Job {
+    pub name: String,
+    /// commands without comments or blank lines
+    lines: Vec<String>,
+    /// index of the next line to send
+    next: usize,
+    /// with line numbers and checksums, after resetting the line number
+    numbered: bool,
+    /// a line was sent and its `ok` hasn't come yet
+    waiting: bool,
+    /// `M110 N0` still has to be sent first
+    reset: bool,
+    pub errors: usize,
+    pub started: Instant,
+    /// what arrived since the last line break
+    line: Vec<u8>,
+}
+
+impl Job {
+    /// longest controller reply kept
+    const MAX_LINE: usize = 256;
+
+    pub fn new(name: String, text: &str, numbered: bool) -> Self {
+        Self {
+            name,
+            lines: text
+                .lines()
+                .map(strip_comments)
+                .filter(|l| !l.is_empty() && l != "%")
+                .collect(),
+            next: 0,
+            numbered,
+            waiting: false,
+            reset: numbered,
+            errors: 0,
+            started: Instant::now(),
+            line: Vec::new(),
+        }
+    }
+    pub fn total(&self) -> usize {
+        self.lines.len()
+    }
+    /// lines acknowledged so far
+    pub fn acked(&self) -> usize {
+        self.next.saturating_sub(self.waiting as usize)
+    }
+    pub fn is_done(&self) -> bool {
+        self.next == self.lines.len() && !self.waiting
+    }
+    /// the next line to send, with its line ending, when the last one was
+    /// acknowledged
+    pub fn poll(&mut self) -> Option<Vec<u8>> {
+        if self.waiting {
+            return None;
+        }
+        let line = if std::mem::take(&mut self.reset) {
+            String::from("M110 N0")
+        } else {
+            let cmd = self.lines.get(self.next)?;
+            self.next += 1;
+            match self.numbered {
+                true => numbered(self.next as u32, cmd),
+                false => cmd.clone(),
+            }
+        };
+        self.waiting = true;
+        Some(format!("{line}\n").into_bytes())
+    }
+    /// the replies in newly received `bytes`, acknowledging lines as they go
+    pub fn scan(&mut self, bytes: &[u8]) -> Vec<Reply> {
+        let mut replies = Vec::new();
+        for &b in bytes {
+            if b != b'\n' {
+                if self.line.len() < Self::MAX_LINE {
+                    self.line.push(b);
+                }
+                continue;
+            }
+            let line = String::from_utf8_lossy(&self.line).into_owned();
+            self.line.clear();
+            let Some(reply) = Reply::parse(&line) else {
+                continue;
+            };
+            match &reply {
+                Reply::Ok | Reply::Rejected(_) => self.waiting = false,
+                // the line numbers start at 1, right after the reset
+                Reply::Resend(n) if self.numbered => {
+                    self.next = (*n as usize).saturating_sub(1).min(self.lines.len());
+                }
+                _ => (),
+            }
+            if matches!(reply, Reply::Rejected(_) | Reply::Error(_)) {
+                self.errors += 1;
+            }
+            replies.push(reply);
+        }
+        replies
+    }
+}
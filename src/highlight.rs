@@ -0,0 +1,130 @@
+//! Coloring decoded frames by the values of their fields, like `status != 0`
+//! in red, so the odd frame stands out among thousands of good ones
+use ratatui::style::Color;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    /// longest first, so `<=` isn't taken for `<`
+    const ALL: &'static [(&'static str, Self)] = &[
+        ("==", Self::Eq),
+        ("!=", Self::Ne),
+        ("<=", Self::Le),
+        (">=", Self::Ge),
+        ("<", Self::Lt),
+        (">", Self::Gt),
+    ];
+
+    fn holds(self, a: i64, b: i64) -> bool {
+        match self {
+            Self::Eq => a == b,
+            Self::Ne => a != b,
+            Self::Lt => a < b,
+            Self::Le => a <= b,
+            Self::Gt => a > b,
+            Self::Ge => a >= b,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    field: String,
+    op: Op,
+    value: i64,
+    pub color: Color,
+}
+
+impl Rule {
+    /// whether `fields` has the field and its value satisfies the rule
+    pub fn matches(&self, fields: &[(&str, i64)]) -> bool {
+        fields
+            .iter()
+            .find(|(name, _)| *name == self.field)
+            .is_some_and(|&(_, v)| self.op.holds(v, self.value))
+    }
+}
+
+/// parses `<field> <op> <value> -> <color>`, like `status != 0 -> red` or
+/// `id == 0x123 -> yellow`, with `==`, `!=`, `<`, `<=`, `>` or `>=`
+impl FromStr for Rule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (condition, color) = s
+            .split_once("->")
+            .ok_or_else(|| format!("missing `-> <color>` in `{s}`"))?;
+        let condition = condition.trim();
+        let (at, (op_str, op)) = Op::ALL
+            .iter()
+            .filter_map(|&(o, op)| condition.find(o).map(|at| (at, (o, op))))
+            .min_by_key(|&(at, (o, _))| (at, usize::MAX - o.len()))
+            .ok_or_else(|| format!("missing comparison in `{condition}`"))?;
+        let field = condition[..at].trim();
+        if field.is_empty() {
+            return Err(format!("missing field in `{condition}`"));
+        }
+        let value = condition[at + op_str.len()..].trim();
+        Ok(Self {
+            field: field.to_owned(),
+            op,
+            value: parse_value(value)?,
+            color: parse_color(color.trim())?,
+        })
+    }
+}
+
+/// decimal, or hex with `0x`
+fn parse_value(s: &str) -> Result<i64, String> {
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => i64::from_str_radix(hex, 16),
+        Some(_) => return Err(format!("invalid value `{s}`")),
+        None => s.parse(),
+    };
+    parsed.map_err(|_| format!("invalid value `{s}`"))
+}
+
+/// the 16 color names every terminal has, so no adapting to its depth is needed
+fn parse_color(s: &str) -> Result<Color, String> {
+    Ok(match s.to_ascii_lowercase().as_str() {
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        "gray" | "grey" => Color::Gray,
+        _ => {
+            return Err(format!(
+            "unknown color `{s}`, expected red, green, yellow, blue, magenta, cyan, white or gray"
+        ))
+        }
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Highlights {
+    pub rules: Vec<Rule>,
+}
+
+impl Highlights {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self { rules }
+    }
+    /// color of the first rule the named `fields` of a frame match
+    pub fn color(&self, fields: &[(&str, i64)]) -> Option<Color> {
+        self.rules
+            .iter()
+            .find(|r| r.matches(fields))
+            .map(|r| r.color)
+    }
+}
@@ -0,0 +1,217 @@
+//! Shell commands run on events, for custom alerting and pipelines
+//!
+//! Hooks are given as `event=command`, the command runs through the shell with
+//! `TUICOM_*` variables describing the event. Output is discarded and the app
+//! doesn't wait for it, failures end up in the error log.
+use regex::bytes::Regex;
+use std::io;
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// the port was opened, at startup, after a reconnect or a port switch
+    Connect,
+    Disconnect,
+    /// a breakpoint paused RX
+    Break,
+    /// the watchdog fired
+    Silence,
+    /// RX data matched the hook's pattern
+    Match,
+    /// an idle rule fired, its command is run rather than `--hook`s
+    Idle,
+    /// the log reached `--log-max-size` and was moved aside
+    Rotate,
+}
+
+impl Event {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Connect => "connect",
+            Self::Disconnect => "disconnect",
+            Self::Break => "break",
+            Self::Silence => "silence",
+            Self::Match => "match",
+            Self::Idle => "idle",
+            Self::Rotate => "rotate",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Hook {
+    pub event: Event,
+    /// what RX data has to match for `match` hooks
+    pub pattern: Option<Regex>,
+    pub command: String,
+}
+
+/// parses `event=command`, `match:<regex>=command` for pattern matches
+impl FromStr for Hook {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (event, command) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected `event=command`, got `{s}`"))?;
+        if command.trim().is_empty() {
+            return Err(format!("missing command in `{s}`"));
+        }
+        let (event, pattern) = match event.split_once(':') {
+            Some(("match", re)) => (
+                Event::Match,
+                Some(Regex::new(re).map_err(|e| e.to_string())?),
+            ),
+            Some(_) => return Err(format!("only `match` hooks take a pattern: `{event}`")),
+            None => match event {
+                "connect" => (Event::Connect, None),
+                "disconnect" => (Event::Disconnect, None),
+                "break" => (Event::Break, None),
+                "silence" => (Event::Silence, None),
+                "rotate" => (Event::Rotate, None),
+                "match" => {
+                    return Err(String::from(
+                        "`match` hooks need a pattern, `match:<regex>`",
+                    ))
+                }
+                _ => return Err(format!("unknown hook event `{event}`")),
+            },
+        };
+        Ok(Self {
+            event,
+            pattern,
+            command: command.to_owned(),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct Hooks {
+    hooks: Vec<Hook>,
+    /// commands not known to have exited yet
+    running: Vec<(String, Child)>,
+    /// end of the RX data scanned so far, so matches across reads are found
+    tail: Vec<u8>,
+    /// per hook, where in `tail` its last reported match ended, so a match
+    /// the next read makes longer doesn't run the hook again
+    reported: Vec<usize>,
+}
+
+impl Hooks {
+    /// RX bytes kept to match patterns spanning several reads
+    const LOOKBACK: usize = 256;
+
+    pub fn new(hooks: Vec<Hook>) -> Self {
+        Self {
+            hooks,
+            ..Self::default()
+        }
+    }
+    /// runs the hooks for `event`, with `vars` in their environment
+    pub fn fire(&mut self, event: Event, vars: &[(&str, String)]) -> Result<(), io::Error> {
+        let commands = self
+            .hooks
+            .iter()
+            .filter(|h| h.event == event && h.event != Event::Match)
+            .map(|h| h.command.clone())
+            .collect::<Vec<_>>();
+        for command in commands {
+            self.spawn(command, event, vars)?;
+        }
+        Ok(())
+    }
+    /// runs `match` hooks whose pattern matches in newly received `bytes`
+    pub fn scan(&mut self, bytes: &[u8], vars: &[(&str, String)]) -> Result<(), io::Error> {
+        if !self.hooks.iter().any(|h| h.event == Event::Match) {
+            return Ok(());
+        }
+        let old = self.tail.len();
+        self.tail.extend_from_slice(bytes);
+        self.reported.resize(self.hooks.len(), 0);
+        let mut runs = Vec::new();
+        for (hook, reported) in self.hooks.iter().zip(&mut self.reported) {
+            let Some(re) = &hook.pattern else {
+                continue;
+            };
+            // only matches ending in the new data and starting after the last
+            // one reported, earlier ones already ran
+            for m in re.find_iter(&self.tail) {
+                if m.end() <= old || m.start() < *reported {
+                    continue;
+                }
+                *reported = m.end();
+                let mut vars = vars.to_vec();
+                vars.push(("TUICOM_PATTERN", re.to_string()));
+                vars.push((
+                    "TUICOM_MATCH",
+                    String::from_utf8_lossy(m.as_bytes()).into_owned(),
+                ));
+                runs.push((hook.command.clone(), vars));
+            }
+        }
+        let keep = self.tail.len().saturating_sub(Self::LOOKBACK);
+        self.tail.drain(..keep);
+        for reported in &mut self.reported {
+            *reported = reported.saturating_sub(keep);
+        }
+        for (command, vars) in runs {
+            self.spawn(command, Event::Match, &vars)?;
+        }
+        Ok(())
+    }
+    /// runs `command` alongside the hooks, with `vars` in its environment
+    pub fn run(
+        &mut self,
+        command: String,
+        event: Event,
+        vars: &[(&str, String)],
+    ) -> Result<(), io::Error> {
+        self.spawn(command, event, vars)
+    }
+    /// collects exited commands, returning why the failed ones failed
+    pub fn reap(&mut self) -> Vec<String> {
+        let mut failures = Vec::new();
+        self.running
+            .retain_mut(|(command, child)| match child.try_wait() {
+                Ok(None) => true,
+                Ok(Some(status)) => {
+                    if !status.success() {
+                        failures.push(format!("`{command}` {status}"));
+                    }
+                    false
+                }
+                Err(e) => {
+                    failures.push(format!("`{command}`: {e}"));
+                    false
+                }
+            });
+        failures
+    }
+    fn spawn(
+        &mut self,
+        command: String,
+        event: Event,
+        vars: &[(&str, String)],
+    ) -> Result<(), io::Error> {
+        let mut cmd = if cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C");
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c");
+            cmd
+        };
+        let child = cmd
+            .arg(&command)
+            .env("TUICOM_EVENT", event.name())
+            .envs(vars.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("`{command}`: {e}")))?;
+        self.running.push((command, child));
+        Ok(())
+    }
+}
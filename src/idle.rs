@@ -0,0 +1,103 @@
+//! Nudging devices that doze off: rules that fire after seconds without RX
+//! data, and again every time that much more passes in silence
+use crate::periodic::Template;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// a wake sequence, with the escapes and counter of periodic sends
+    Send(Template),
+    /// DTR dropped for a moment, which wakes or resets many boards
+    PulseDtr,
+    /// a shell command, run like a hook
+    Hook(String),
+}
+
+/// What a rule that fired asks the app to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Nudge {
+    Send(Vec<u8>),
+    PulseDtr,
+    Hook(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub after: Duration,
+    pub action: Action,
+    /// when it last fired
+    fired: Option<Instant>,
+    counter: u64,
+}
+
+/// parses `secs:send:<payload>`, `secs:dtr` or `secs:hook:<command>`
+impl FromStr for Rule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let expected = || {
+            format!(
+                "expected `secs:send:<payload>`, `secs:dtr` or `secs:hook:<command>`, got `{s}`"
+            )
+        };
+        let (secs, action) = s.split_once(':').ok_or_else(expected)?;
+        let after = match secs.parse() {
+            Ok(secs) if secs > 0 => Duration::from_secs(secs),
+            _ => return Err(format!("invalid idle time `{secs}`")),
+        };
+        let action = match action.split_once(':') {
+            Some(("send", payload)) => Action::Send(payload.parse().map_err(|e| format!("{e}"))?),
+            Some(("hook", command)) if !command.trim().is_empty() => {
+                Action::Hook(command.to_owned())
+            }
+            None if action == "dtr" => Action::PulseDtr,
+            _ => return Err(expected()),
+        };
+        Ok(Self {
+            after,
+            action,
+            fired: None,
+            counter: 0,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct IdleRules {
+    rules: Vec<Rule>,
+    /// when DTR goes back up after a pulse
+    pub dtr_up_at: Option<Instant>,
+}
+
+impl IdleRules {
+    /// how long DTR stays down for a pulse
+    pub const PULSE: Duration = Duration::from_millis(100);
+
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self {
+            rules,
+            dtr_up_at: None,
+        }
+    }
+    /// what the rules due by now ask for, RX having been quiet since `last_recv`
+    pub fn poll(&mut self, last_recv: Instant) -> Vec<Nudge> {
+        let mut nudges = Vec::new();
+        for rule in &mut self.rules {
+            let since = rule.fired.map_or(last_recv, |fired| fired.max(last_recv));
+            if since.elapsed() < rule.after {
+                continue;
+            }
+            rule.fired = Some(Instant::now());
+            nudges.push(match &rule.action {
+                Action::Send(template) => {
+                    let bytes = template.render(rule.counter);
+                    rule.counter = rule.counter.wrapping_add(1);
+                    Nudge::Send(bytes)
+                }
+                Action::PulseDtr => Nudge::PulseDtr,
+                Action::Hook(command) => Nudge::Hook(command.clone()),
+            });
+        }
+        nudges
+    }
+}
@@ -1,13 +1,76 @@
+pub mod ack;
 pub mod app;
 pub mod args;
+pub mod capture;
+pub mod clipboard;
+pub mod command;
+pub mod decode;
+pub mod diff;
 pub mod dummy;
+pub mod eol;
+pub mod error_log;
+pub mod fuzz;
+pub mod gcode;
+pub mod highlight;
+pub mod hooks;
+pub mod idle;
+pub mod line_edit;
+pub mod link;
+pub mod login;
+pub mod notify;
+pub mod paste;
+pub mod perf;
+pub mod periodic;
+pub mod ports;
+pub mod preset;
+pub mod profile;
+pub mod prompt;
+pub mod region;
+pub mod remote;
 pub mod screen;
+pub mod search;
+pub mod secret;
+pub mod session;
+pub mod summary;
+pub mod sweep;
+pub mod template;
+pub mod theme;
+pub mod timeline;
+pub mod tls;
+pub mod toast;
+pub mod trace;
 pub mod ui;
+pub mod watch;
+pub mod watchdog;
+pub mod waveform;
+pub mod window;
 
+use ack::AckGate;
 use app::App;
 use args::Args;
+use capture::{BootLog, LogFormat, SessionLog, Snapshots};
+use fuzz::Fuzzer;
+use highlight::Highlights;
+use hooks::Hooks;
+use idle::IdleRules;
+use link::Link;
+use login::AutoLogin;
+use periodic::{Keepalive, Periodic};
+use ports::Resolved;
+use preset::Presets;
+use profile::Profile;
+use prompt::Prompt;
+use screen::overlay::{Outcome, Overlay, Picker, Reply};
+use screen::ByteFilter;
+use session::Session;
 use std::io;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use template::Templates;
+use theme::{ColorDepth, Theme};
+use tls::ClientTls;
+use watch::Watches;
+use watchdog::Watchdog;
 
 use ratatui::{
     backend::{Backend, CrosstermBackend},
@@ -15,7 +78,9 @@ use ratatui::{
 };
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -26,39 +91,317 @@ pub enum Error {
     InvalidPort(#[from] serialport::Error),
     #[error("invalid baud rate: {0}")]
     InvalidBaudRate(u16),
+    #[error("invalid periodic payload: {0}")]
+    InvalidTemplate(String),
+    #[error("no serial ports found")]
+    NoPorts,
+    #[error("no serial port matches `{0}`")]
+    NoPortMatch(String),
+    #[error("invalid profile {0:?}: {1}")]
+    InvalidProfile(PathBuf, String),
+    #[error("invalid profile name `{0}`: it can't be empty or contain `/`, `\\` or `..`")]
+    InvalidProfileName(String),
+    #[error("invalid frame templates {0:?}: {1}")]
+    InvalidTemplates(PathBuf, String),
+    #[error("invalid presets {0:?}: {1}")]
+    InvalidPresets(PathBuf, String),
+    #[error("unknown preset `{0}`")]
+    UnknownPreset(String),
+    #[error("invalid session {0:?}: {1}")]
+    InvalidSession(PathBuf, String),
+    #[error("can't serve the port: {0}")]
+    InvalidServe(String),
+    #[error("invalid TLS setup: {0}")]
+    InvalidTls(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
 type Result<T> = std::result::Result<T, Error>;
 
+/// most input events handled before a frame is drawn
+const EVENTS_PER_FRAME: usize = 64;
+
 /// Application entry point
 pub fn run_app() -> Result<()> {
     let args: Args = argh::from_env();
 
-    let port = if args.port == "dummy" {
-        Box::new(dummy::DummySerial::new(args.baud))
-    } else {
-        serialport::new(&args.port, args.baud)
-            .timeout(Duration::from_millis(500))
-            .open()?
+    let color_depth = args.colors.unwrap_or_else(ColorDepth::detect);
+    let theme = Theme::new(args.theme, color_depth);
+    let resolved = match &args.port {
+        Some(port) => ports::resolve(port),
+        None => ports::default_port(),
+    };
+    // connecting without asking deserves a notice when no port was given
+    let mut notice = None;
+    let port_name = match resolved {
+        Resolved::Port(port) => {
+            if args.port.is_none() {
+                notice = Some(format!("connected to {port}, the only USB serial port"));
+            }
+            port
+        }
+        Resolved::Ambiguous(ports) => match pick_port(ports, args.port.as_deref(), &theme)? {
+            Some(port) => port,
+            None => return Ok(()),
+        },
+        Resolved::None => {
+            return Err(match args.port {
+                Some(port) => Error::NoPortMatch(port),
+                None => Error::NoPorts,
+            })
+        }
+    };
+    let mut link = Link::new(port_name.clone(), args.baud);
+    link.token = args.token.clone();
+    link.tls = ClientTls {
+        ca: args.tls_ca.clone(),
+        insecure: args.tls_insecure,
+    };
+    if let Some(addr) = &args.serve {
+        let tls = match (&args.tls_cert, &args.tls_key) {
+            (Some(cert), Some(key)) => {
+                Some(tls::server_config(cert, key).map_err(Error::InvalidTls)?)
+            }
+            (None, None) => None,
+            _ => {
+                let e = "--tls-cert and --tls-key go together";
+                return Err(Error::InvalidTls(e.into()));
+            }
+        };
+        let addr = remote::bind_address(addr);
+        if !args.serve_public && !remote::is_loopback(&addr)? {
+            let e = format!(
+                "{addr} can be reached from other machines, allow that with --serve-public"
+            );
+            return Err(Error::InvalidServe(e));
+        }
+        let token = match args.token.clone() {
+            Some(token) if token.is_empty() => {
+                return Err(Error::InvalidServe(String::from("the token is empty")))
+            }
+            Some(token) => token,
+            None => {
+                let token = remote::generate_token()?;
+                eprintln!("no --token given, clients have to use --token {token}");
+                token
+            }
+        };
+        return Ok(remote::serve(&mut link, &addr, &token, tls)?);
+    }
+    let port = link.open()?;
+    let periodic = match &args.periodic {
+        Some(p) => Some(Periodic::new(
+            p.parse()?,
+            Duration::from_millis(args.interval),
+        )),
+        None => None,
+    };
+    let keepalive = match &args.keepalive {
+        Some(k) => Some(Keepalive::new(
+            k.parse()?,
+            Duration::from_secs(args.keepalive_idle),
+        )),
+        None => None,
+    };
+    let mut fuzzer = Fuzzer::new(args.fuzz_seed);
+    fuzzer.len = args.fuzz_len;
+    fuzzer.bytes = args.fuzz_bytes;
+    fuzzer.interval = Duration::from_millis(args.fuzz_rate);
+    if let Some(base) = &args.fuzz_base {
+        fuzzer.base = Some(base.parse::<periodic::Template>()?.render(0));
+    }
+    if let Some(path) = &args.fuzz_log {
+        fuzzer.log_to(std::fs::File::create(path)?)?;
+    }
+    let profile_name = args
+        .profile
+        .clone()
+        .unwrap_or_else(|| profile::default_name(&port_name));
+    let mut profile = Profile::load(&profile_name)?;
+    let templates = match args.templates.clone().or_else(template::default_path) {
+        Some(path) => Templates::load(&path).map_err(|e| Error::InvalidTemplates(path, e))?,
+        None => Templates::default(),
+    };
+    let presets = match args.presets.clone().or_else(preset::default_path) {
+        Some(path) => Presets::load(&path).map_err(|e| Error::InvalidPresets(path, e))?,
+        None => Presets::default(),
+    };
+    let preset_names = profile.presets.iter().chain(&args.preset).cloned();
+    let preset_names = preset_names.collect::<Vec<_>>();
+    if let Some(name) = preset_names.iter().find(|&n| presets.get(n).is_none()) {
+        return Err(Error::UnknownPreset(name.clone()));
+    }
+    let session = match args.session.clone() {
+        Some(path) => Some(Session::load(&path).map_err(|e| Error::InvalidSession(path, e))?),
+        None => None,
     };
     let mut terminal = start_tui()?;
     // little trick to replace `try` block
-    let res = (|| {
-        let mut app = App::new(port);
+    let res: Result<String> = (|| {
+        let mut app = App::new(port, link);
+        app.periodic = periodic;
+        app.keepalive = keepalive;
+        app.idle = IdleRules::new(args.idle);
+        app.fuzzer = fuzzer;
+        if let Some(path) = args.fuzz_log {
+            app.files.push(("fuzz log", path));
+        }
+        app.watchdog = args.watchdog.map(|secs| {
+            let mut dog = Watchdog::new(Duration::from_secs(secs));
+            dog.notify = args.watchdog_notify;
+            dog
+        });
+        app.hooks = Hooks::new(args.hooks);
+        app.watches = Watches::new(args.watches);
+        app.prompt = args.prompt.map(|p| Prompt::new(p, args.prompt_gate));
+        app.gcode_checksums = args.gcode_checksums;
+        app.tx.ack = args
+            .ack
+            .map(|pattern| AckGate::new(pattern, args.ack_window));
+        // checked when the profile was loaded
+        app.login = profile.login.as_ref().and_then(|l| {
+            let mut login = AutoLogin::new(l).ok()?;
+            login.keyring = l
+                .keyring
+                .then(|| format!("login/{profile_name}/{}", l.user));
+            Some(login)
+        });
+        for rule in args.redact {
+            app.secrets.rule(rule);
+        }
+        // checked when the profile was loaded
+        for rule in &profile.redact {
+            if let Ok(rule) = regex::bytes::Regex::new(rule) {
+                app.secrets.rule(rule);
+            }
+        }
+        app.snapshots = args
+            .snapshot_dir
+            .map(|dir| Snapshots::new(dir, Duration::from_secs(args.snapshot_idle * 60)));
+        app.boot_log = args
+            .boot_log
+            .map(|dir| BootLog::new(dir, Duration::from_secs(args.boot_secs)));
+        let log = profile.log.clone().unwrap_or_default();
+        app.log_format = args.log_format.or(log.format).unwrap_or(LogFormat::Raw);
+        app.log_flush = Duration::from_millis(args.log_flush);
+        if let Some(boot_log) = &mut app.boot_log {
+            boot_log.flush = app.log_flush;
+        }
+        app.log_max_size = args.log_max_size.or(log.max_size).map(|kib| kib * 1024);
+        let start_log = args.log.is_some() || log.start;
+        app.log_path = args.log.or(log.path);
+        if start_log {
+            let path = app.next_log_path();
+            app.log_path = Some(path.clone());
+            let mut log = SessionLog::open(path.clone(), app.log_format, app.log_flush)?;
+            log.max_size = app.log_max_size;
+            app.log = Some(log);
+            app.files.push(("log", path));
+        }
+        app.templates = templates.templates;
+        app.wave_field = args.wave_field;
+        app.rx.display.fade = args.fade.filter(|&s| s > 0).map(Duration::from_secs);
+        app.rx.breakpoints = args.breakpoints;
+        app.rx.breakpoints.extend(args.break_hex);
+        app.highlights = Highlights::new(args.highlights);
+        app.apply_layout(&profile.layout)?;
+        if args.decode.is_some() {
+            app.set_decoder(args.decode)?;
+        }
+        if let Some(session) = session {
+            // the session's layout takes over from the profile's
+            if let Err(e) = app.restore_session(session) {
+                app.errors.push("session", e);
+            }
+        }
+        app.color_depth = color_depth;
+        app.theme = theme;
+        app.ctrl_c = args.ctrl_c;
+        app.bell = args.bell;
+        app.startup = profile.startup.clone();
+        // before the presets, which add their ranges to it
+        app.rx.filter = ByteFilter::new(args.drop);
+        app.presets = presets;
+        for name in preset_names {
+            // known to exist, the same one twice stays on
+            if !app.active_presets.contains(&name) {
+                app.toggle_preset(&name)?;
+            }
+        }
+        app.escape = args.escape;
+        app.line_mode |= args.line_mode;
+        app.tx.shaping = args.shape_tx;
+        if let Some(line_ending) = args.line_ending {
+            app.tx.line_ending = line_ending;
+        }
+        app.connected();
+        if let Some(notice) = notice {
+            app.toasts.info(notice);
+        }
 
         loop {
-            let ev = if event::poll(Duration::from_millis(1000 / 60))? {
-                Some(event::read()?)
-            } else {
-                None
-            };
-            if app.update(ev)?.exit() {
+            let mut events = Vec::new();
+            if event::poll(Duration::from_millis(1000 / 60))? {
+                events.push(event::read()?);
+                // whatever else is queued goes into the same frame, drawn once
+                while events.len() < EVENTS_PER_FRAME && event::poll(Duration::ZERO)? {
+                    events.push(event::read()?);
+                }
+            }
+            let start = Instant::now();
+            if app.update(events)?.exit() {
                 break;
             }
+            let update = start.elapsed();
             terminal.draw(|f| ui::draw(f, &mut app))?;
+            let draw = start.elapsed() - update;
+            if let Some(dropped) = app.frame_times.record(update, draw) {
+                app.trace.record(
+                    trace::Kind::Frame,
+                    format!(
+                        "{dropped} frames dropped, update {:.1}ms, draw {:.1}ms",
+                        update.as_secs_f64() * 1e3,
+                        draw.as_secs_f64() * 1e3
+                    ),
+                );
+            }
+        }
+        app.flush_logs();
+        profile.layout = app.layout();
+        profile.save(&profile_name)?;
+        Ok(summary::report(&app))
+    })();
+    leave_tui(terminal)?;
+    let report = res?;
+    match &args.summary {
+        Some(path) if path.as_os_str() == "-" => print!("{report}"),
+        Some(path) => std::fs::write(path, report)?,
+        None => (),
+    }
+    Ok(())
+}
+
+/// lets the user pick one of `ports`, which `arg` left to pick from, finding
+/// them again on `r`, `None` if they cancel
+fn pick_port(mut ports: Vec<String>, arg: Option<&str>, theme: &Theme) -> Result<Option<String>> {
+    let picker =
+        |ports: &[String]| Picker::new("Pick a port", ports::labels(ports)).action('r', "refresh");
+    let mut current = picker(&ports);
+    let mut terminal = start_tui()?;
+    let res = (|| loop {
+        terminal.draw(|f| ui::draw_overlay(f, &current, theme))?;
+        if let event::Event::Key(key) = event::read()? {
+            match current.handle_key(key) {
+                Outcome::Continue => (),
+                Outcome::Cancel => return Ok(None),
+                Outcome::Submit(Reply::Choice(i)) => return Ok(Some(ports[i].clone())),
+                Outcome::Submit(Reply::Action(_)) => {
+                    ports = ports::candidates(arg);
+                    current = picker(&ports);
+                }
+                Outcome::Submit(_) => unreachable!("pickers only submit choices"),
+            }
         }
-        Ok(())
     })();
     leave_tui(terminal)?;
     res
@@ -67,7 +410,12 @@ pub fn run_app() -> Result<()> {
 fn start_tui() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let terminal = Terminal::new(backend)?;
     Ok(terminal)
@@ -78,7 +426,8 @@ fn leave_tui<B: Backend + std::io::Write>(mut terminal: Terminal<B>) -> Result<(
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     Ok(())
 }
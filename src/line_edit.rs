@@ -0,0 +1,136 @@
+//! Line mode input: a line is edited before anything is sent, then sent at once
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+/// What ends a line sent in line mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    None,
+    Cr,
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub const ALL: &'static [Self] = &[Self::None, Self::Cr, Self::Lf, Self::CrLf];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Cr => "cr",
+            Self::Lf => "lf",
+            Self::CrLf => "crlf",
+        }
+    }
+    pub fn bytes(self) -> &'static [u8] {
+        match self {
+            Self::None => b"",
+            Self::Cr => b"\r",
+            Self::Lf => b"\n",
+            Self::CrLf => b"\r\n",
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LineEnding {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|e| e.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown line ending `{s}`, expected none, cr, lf or crlf"))
+    }
+}
+
+/// The line being edited, with a cursor and a way through the lines sent before
+#[derive(Debug, Clone, Default)]
+pub struct LineEdit {
+    pub text: String,
+    /// character the cursor is on, `text.len()` at the end
+    cursor: usize,
+    /// history entry shown, counted back from the newest
+    browsing: Option<usize>,
+    /// what was typed before going through the history
+    draft: String,
+}
+
+impl LineEdit {
+    /// byte offset of the cursor in `text`
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+    pub fn backspace(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+    pub fn left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+    pub fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+    /// deletes everything before the cursor, like Ctrl+U in a shell
+    pub fn kill_to_start(&mut self) {
+        self.text.drain(..self.cursor);
+        self.cursor = 0;
+    }
+    /// takes the line to send it, leaving an empty one
+    pub fn take(&mut self) -> String {
+        self.browsing = None;
+        self.draft.clear();
+        self.cursor = 0;
+        std::mem::take(&mut self.text)
+    }
+    /// goes `delta` entries back (negative) or forward through `history`,
+    /// back to the line being typed after the newest
+    pub fn recall(&mut self, history: &VecDeque<Vec<u8>>, delta: i32) {
+        let back = self.browsing.map_or(0, |b| b + 1) as i64 - delta as i64;
+        let back = back.clamp(0, history.len() as i64) as usize;
+        if self.browsing.is_none() && back > 0 {
+            self.draft = self.text.clone();
+        }
+        self.browsing = back.checked_sub(1);
+        self.text = match self.browsing {
+            Some(b) => {
+                let entry = &history[history.len() - 1 - b];
+                let entry = entry.strip_suffix(b"\n").unwrap_or(entry);
+                let entry = entry.strip_suffix(b"\r").unwrap_or(entry);
+                String::from_utf8_lossy(entry).into_owned()
+            }
+            None => std::mem::take(&mut self.draft),
+        };
+        self.cursor = self.text.len();
+    }
+}
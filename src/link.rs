@@ -0,0 +1,268 @@
+//! Connection to the serial port, and reopening it after it goes away
+use crate::decode::LineSettings;
+use crate::dummy::DummySerial;
+use crate::ports;
+use crate::remote::{self, RemotePort};
+use crate::tls::ClientTls;
+use serialport::{DataBits, FlowControl, Parity, SerialPort, SerialPortType, StopBits};
+use std::io;
+use std::time::{Duration, Instant};
+
+pub struct Link {
+    /// path of the port, `dummy`, or a port served by another tuicom like
+    /// `tuicom://host:7878`, or `tuicoms://host:7878` over TLS
+    pub port: String,
+    /// human readable name of the port, if it has one
+    pub description: Option<String>,
+    /// what the port is reopened with, taken from it before it's replaced
+    pub settings: LineSettings,
+    pub flow_control: FlowControl,
+    /// set while the port is gone
+    pub down: Option<Down>,
+    /// times the port went away and came back
+    pub disconnects: u32,
+    pub reconnects: u32,
+    /// token remote ports are opened with
+    pub token: Option<String>,
+    /// how the certificate of `tuicoms://` ports is checked
+    pub tls: ClientTls,
+}
+
+pub struct Down {
+    pub since: Instant,
+    /// next automatic reconnect
+    pub retry_at: Instant,
+    backoff: Duration,
+    /// why the port went away or the last reconnect failed
+    pub error: String,
+}
+
+impl Link {
+    const FIRST_RETRY: Duration = Duration::from_secs(1);
+    const MAX_RETRY: Duration = Duration::from_secs(10);
+    /// read timeout of opened ports
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    pub fn new(port: impl Into<String>, baud_rate: u32) -> Self {
+        let port = port.into();
+        Self {
+            description: ports::friendly_name(&port),
+            port,
+            settings: LineSettings {
+                baud_rate,
+                data_bits: DataBits::Eight,
+                parity: Parity::None,
+                stop_bits: StopBits::One,
+            },
+            flow_control: FlowControl::None,
+            down: None,
+            disconnects: 0,
+            reconnects: 0,
+            token: None,
+            tls: ClientTls::default(),
+        }
+    }
+    pub fn open(&self) -> Result<Box<dyn SerialPort>, serialport::Error> {
+        if self.port == "dummy" {
+            return Ok(Box::new(DummySerial::new(self.settings.baud_rate)));
+        }
+        let remote = match self.port.strip_prefix(remote::TLS_SCHEME) {
+            Some(addr) => Some((addr, Some(&self.tls))),
+            None => self.port.strip_prefix(remote::SCHEME).map(|a| (a, None)),
+        };
+        if let Some((addr, tls)) = remote {
+            let port = RemotePort::connect(
+                addr,
+                self.token.as_deref(),
+                tls,
+                self.settings,
+                self.flow_control,
+            )?;
+            return Ok(Box::new(port));
+        }
+        serialport::new(&self.port, self.settings.baud_rate)
+            .data_bits(self.settings.data_bits)
+            .parity(self.settings.parity)
+            .stop_bits(self.settings.stop_bits)
+            .flow_control(self.flow_control)
+            .timeout(Self::TIMEOUT)
+            .open()
+    }
+    /// keeps the settings of `port` for reopening, as far as they can still be read
+    pub fn remember(&mut self, port: &dyn SerialPort) {
+        let s = &mut self.settings;
+        s.baud_rate = port.baud_rate().unwrap_or(s.baud_rate);
+        s.data_bits = port.data_bits().unwrap_or(s.data_bits);
+        s.parity = port.parity().unwrap_or(s.parity);
+        s.stop_bits = port.stop_bits().unwrap_or(s.stop_bits);
+        self.flow_control = port.flow_control().unwrap_or(self.flow_control);
+    }
+    /// applies settings to the open port, the ones that could be set are
+    /// kept for reopening
+    pub fn configure(
+        &mut self,
+        port: &mut dyn SerialPort,
+        settings: LineSettings,
+        flow_control: FlowControl,
+    ) -> Result<(), serialport::Error> {
+        port.set_baud_rate(settings.baud_rate)?;
+        port.set_data_bits(settings.data_bits)?;
+        port.set_parity(settings.parity)?;
+        port.set_stop_bits(settings.stop_bits)?;
+        port.set_flow_control(flow_control)?;
+        self.settings = settings;
+        self.flow_control = flow_control;
+        Ok(())
+    }
+    pub fn is_down(&self) -> bool {
+        self.down.is_some()
+    }
+    /// whether the next automatic reconnect is due
+    pub fn is_due(&self) -> bool {
+        self.down
+            .as_ref()
+            .is_some_and(|d| d.retry_at <= Instant::now())
+    }
+    pub fn lost(&mut self, error: impl ToString) {
+        self.disconnects += 1;
+        let now = Instant::now();
+        self.down = Some(Down {
+            since: now,
+            retry_at: now + Self::FIRST_RETRY,
+            backoff: Self::FIRST_RETRY,
+            error: error.to_string(),
+        });
+    }
+    /// schedules the next reconnect after a failed one
+    pub fn failed(&mut self, error: impl ToString) {
+        if let Some(down) = &mut self.down {
+            down.backoff = (down.backoff * 2).min(Self::MAX_RETRY);
+            down.retry_at = Instant::now() + down.backoff;
+            down.error = error.to_string();
+        }
+    }
+    /// everything known about the device, for the device info panel
+    pub fn describe(&self, port: &dyn SerialPort) -> Vec<String> {
+        let mut lines = vec![format!("port: {}", self.port)];
+        if self.is_down() {
+            lines.push(String::from("state: disconnected"));
+        }
+        // remote ports aren't in the OS's port list and have no driver here
+        if self.port.starts_with(remote::SCHEME) {
+            lines.push(String::from("type: remote tuicom"));
+        } else if self.port.starts_with(remote::TLS_SCHEME) {
+            lines.push(String::from("type: remote tuicom over TLS"));
+        } else {
+            let info = serialport::available_ports()
+                .ok()
+                .and_then(|ports| ports.into_iter().find(|p| p.port_name == self.port));
+            match info.map(|p| p.port_type) {
+                Some(SerialPortType::UsbPort(usb)) => {
+                    lines.push(String::from("type: USB"));
+                    lines.push(format!("VID:PID: {:04x}:{:04x}", usb.vid, usb.pid));
+                    let unknown = || String::from("unknown");
+                    lines.push(format!(
+                        "manufacturer: {}",
+                        usb.manufacturer.unwrap_or_else(unknown)
+                    ));
+                    lines.push(format!("product: {}", usb.product.unwrap_or_else(unknown)));
+                    lines.push(format!(
+                        "serial number: {}",
+                        usb.serial_number.unwrap_or_else(unknown)
+                    ));
+                }
+                Some(SerialPortType::PciPort) => lines.push(String::from("type: PCI")),
+                Some(SerialPortType::BluetoothPort) => lines.push(String::from("type: Bluetooth")),
+                Some(SerialPortType::Unknown) | None => lines.push(String::from("type: unknown")),
+            }
+            if let Some(driver) = driver(&self.port) {
+                lines.push(format!("driver: {driver}"));
+            }
+        }
+        if !self.is_down() {
+            let settings = LineSettings {
+                baud_rate: port.baud_rate().unwrap_or(self.settings.baud_rate),
+                data_bits: port.data_bits().unwrap_or(self.settings.data_bits),
+                parity: port.parity().unwrap_or(self.settings.parity),
+                stop_bits: port.stop_bits().unwrap_or(self.settings.stop_bits),
+            };
+            lines.push(format!("line settings: {settings}"));
+            if let Ok(flow) = port.flow_control() {
+                lines.push(format!("flow control: {flow}"));
+            }
+        } else {
+            lines.push(format!("line settings: {}", self.settings));
+        }
+        lines
+    }
+}
+
+/// baud rates offered by the settings dialog, others can be typed in
+pub const BAUD_RATES: &[u32] = &[
+    300, 1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600, 1000000,
+    2000000, 3000000,
+];
+pub const DATA_BITS: &[(&str, DataBits)] = &[
+    ("5", DataBits::Five),
+    ("6", DataBits::Six),
+    ("7", DataBits::Seven),
+    ("8", DataBits::Eight),
+];
+pub const PARITIES: &[(&str, Parity)] = &[
+    ("none", Parity::None),
+    ("odd", Parity::Odd),
+    ("even", Parity::Even),
+];
+pub const STOP_BITS: &[(&str, StopBits)] = &[("1", StopBits::One), ("2", StopBits::Two)];
+pub const FLOW_CONTROLS: &[(&str, FlowControl)] = &[
+    ("none", FlowControl::None),
+    ("software", FlowControl::Software),
+    ("hardware", FlowControl::Hardware),
+];
+
+/// name of `value` in one of the tables above
+pub fn name_of<T: PartialEq>(table: &[(&'static str, T)], value: T) -> &'static str {
+    table
+        .iter()
+        .find(|(_, v)| *v == value)
+        .map_or("", |(name, _)| name)
+}
+
+/// names in one of the tables above, as offered by the settings dialog
+pub fn names<T>(table: &[(&str, T)]) -> Vec<String> {
+    table.iter().map(|(name, _)| name.to_string()).collect()
+}
+
+/// value named `name` in one of the tables above
+pub fn parse_name<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+}
+
+/// kernel driver behind a tty, like `ftdi_sio` or `cdc_acm`
+#[cfg(target_os = "linux")]
+fn driver(port: &str) -> Option<String> {
+    let name = std::path::Path::new(port).file_name()?;
+    let link = std::path::Path::new("/sys/class/tty")
+        .join(name)
+        .join("device/driver");
+    let driver = std::fs::read_link(link).ok()?;
+    Some(driver.file_name()?.to_string_lossy().into_owned())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn driver(_port: &str) -> Option<String> {
+    None
+}
+
+/// errors meaning the port is gone, like a USB adapter being unplugged
+pub fn is_disconnect(e: &io::Error) -> bool {
+    // EIO, ENXIO and ENODEV
+    #[cfg(unix)]
+    if matches!(e.raw_os_error(), Some(5 | 6 | 19)) {
+        return true;
+    }
+    matches!(
+        e.kind(),
+        io::ErrorKind::BrokenPipe | io::ErrorKind::NotConnected | io::ErrorKind::UnexpectedEof
+    )
+}
@@ -0,0 +1,162 @@
+//! Logging in to a console by itself: the user name is sent when the device
+//! asks for it and the password when it asks for that, so a reattached
+//! embedded Linux console doesn't need the credentials typed again
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// The `[login]` table of a profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Login {
+    pub user: String,
+    /// regexes matched against the last line received
+    pub user_prompt: String,
+    pub password_prompt: String,
+    /// regex for what the device says when the password was wrong
+    pub failure: String,
+    /// keeps the password in the OS keyring rather than asking every run
+    pub keyring: bool,
+}
+
+impl Default for Login {
+    fn default() -> Self {
+        Self {
+            user: String::new(),
+            user_prompt: String::from(r"^([\w.-]+ )?login: ?$"),
+            password_prompt: String::from("[Pp]assword: ?$"),
+            failure: String::from("Login incorrect|[Aa]uthentication fail"),
+            keyring: false,
+        }
+    }
+}
+
+/// what ends the user name and password, like pressing enter on a terminal
+pub const ENTER: &[u8] = b"\r";
+
+/// What the app does next for a login
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// bytes to send, ending in [`ENTER`]
+    SendUser(Vec<u8>),
+    /// the password isn't known yet, it's looked up or the user is asked for it
+    AskPassword,
+    /// the password, sent followed by [`ENTER`]
+    SendPassword(String),
+    /// the device said the password was wrong
+    Failed,
+    /// the device asked for the user name again right after the password
+    /// without saying why, so logging in stops but the password is kept
+    Reprompted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// waiting for the user prompt
+    Idle,
+    UserSent,
+    PasswordSent(Instant),
+    /// a login failed, nothing is tried again until the port is reopened
+    Off,
+}
+
+pub struct AutoLogin {
+    user: Vec<u8>,
+    user_prompt: Regex,
+    password_prompt: Regex,
+    failure: Regex,
+    /// asked for once and kept for the rest of the run
+    pub password: Option<String>,
+    /// keyring entry the password is kept in, if it's kept there
+    pub keyring: Option<String>,
+    state: State,
+    /// what arrived since the last line break
+    line: Vec<u8>,
+}
+
+impl AutoLogin {
+    /// longest last line kept, prompts are shorter
+    const MAX_LINE: usize = 256;
+    /// the user prompt or a failure message this soon after the password
+    /// means it wasn't taken
+    const FAILED_WITHIN: Duration = Duration::from_secs(15);
+    /// `Last login: <date>` after logging in looks like a user prompt when a
+    /// read ends right after `login: `
+    const LAST_LOGIN: &'static [u8] = b"Last login";
+
+    pub fn new(login: &Login) -> Result<Self, String> {
+        if login.user.is_empty() {
+            return Err(String::from("login without a user"));
+        }
+        let regex = |s: &str| Regex::new(s).map_err(|e| format!("login prompt `{s}`: {e}"));
+        Ok(Self {
+            user: login.user.as_bytes().to_vec(),
+            user_prompt: regex(&login.user_prompt)?,
+            password_prompt: regex(&login.password_prompt)?,
+            failure: regex(&login.failure)?,
+            password: None,
+            keyring: None,
+            state: State::Idle,
+            line: Vec::new(),
+        })
+    }
+    /// logs in again on the next user prompt, after a reconnect
+    pub fn rearm(&mut self) {
+        self.state = State::Idle;
+        self.line.clear();
+    }
+    /// looks for a prompt at the end of newly received `bytes`
+    pub fn scan(&mut self, bytes: &[u8]) -> Option<Step> {
+        if bytes.is_empty() || self.state == State::Off {
+            return None;
+        }
+        if let State::PasswordSent(at) = self.state {
+            // the message usually ends in a line break, so it's looked for
+            // before the line is cut there. Later on it's a shell's output
+            if at.elapsed() < Self::FAILED_WITHIN
+                && self.failure.is_match(&[&self.line, bytes].concat())
+            {
+                self.line.clear();
+                self.state = State::Off;
+                self.password = None;
+                return Some(Step::Failed);
+            }
+        }
+        match bytes.iter().rposition(|&b| b == b'\n') {
+            Some(i) => {
+                self.line.clear();
+                self.line.extend_from_slice(&bytes[i + 1..]);
+            }
+            None => self.line.extend_from_slice(bytes),
+        }
+        let keep = self.line.len().saturating_sub(Self::MAX_LINE);
+        self.line.drain(..keep);
+        if self.user_prompt.is_match(&self.line) && !self.line.starts_with(Self::LAST_LOGIN) {
+            self.line.clear();
+            if let State::PasswordSent(at) = self.state {
+                if at.elapsed() < Self::FAILED_WITHIN {
+                    self.state = State::Off;
+                    return Some(Step::Reprompted);
+                }
+            }
+            self.state = State::UserSent;
+            return Some(Step::SendUser([&self.user, ENTER].concat()));
+        }
+        if self.state == State::UserSent && self.password_prompt.is_match(&self.line) {
+            self.line.clear();
+            return Some(match &self.password {
+                Some(password) => {
+                    self.state = State::PasswordSent(Instant::now());
+                    Step::SendPassword(password.clone())
+                }
+                None => Step::AskPassword,
+            });
+        }
+        None
+    }
+    /// the password looked up or typed in, which is sent now
+    pub fn answer(&mut self, password: &str) {
+        self.state = State::PasswordSent(Instant::now());
+        self.password = Some(password.to_owned());
+    }
+}
@@ -0,0 +1,59 @@
+//! Getting the user's attention through the terminal
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// What a BEL (0x07) from the device does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bell {
+    /// rings the terminal's own bell
+    Ring,
+    /// flashes the screen instead, for quiet places
+    Flash,
+    Off,
+}
+
+impl Bell {
+    pub const ALL: &'static [Self] = &[Self::Ring, Self::Flash, Self::Off];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ring => "ring",
+            Self::Flash => "flash",
+            Self::Off => "off",
+        }
+    }
+}
+
+impl fmt::Display for Bell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Bell {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|b| b.name() == s)
+            .ok_or_else(|| format!("unknown bell `{s}`, expected ring, flash or off"))
+    }
+}
+
+/// rings the terminal bell
+pub fn bell() -> io::Result<()> {
+    let mut out = io::stdout();
+    out.write_all(b"\x07")?;
+    out.flush()
+}
+
+/// raises a desktop notification with OSC 9, terminals without support ignore it
+pub fn notify(text: &str) -> io::Result<()> {
+    let mut out = io::stdout();
+    // control characters would end the sequence early
+    let text = text.replace(|c: char| c.is_control(), " ");
+    write!(out, "\x1b]9;{text}\x07")?;
+    out.flush()
+}
@@ -0,0 +1,110 @@
+//! Pasting several lines at once into CLIs too slow to take them as fast as
+//! they come: comments and blank lines can be left out, the lines sent a
+//! while apart or each after the device shows its prompt again
+use crate::decode::parse_hex;
+use crate::line_edit::LineEnding;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How pasted lines are changed and sent, kept from one paste to the next
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// lines starting with this, after leading blanks, are left out
+    pub comment: Option<String>,
+    pub strip_blank: bool,
+    /// what ends every line, whatever ended it in the pasted text
+    pub line_ending: LineEnding,
+    /// time between lines
+    pub delay: Duration,
+    /// every line after the first waits for the device's prompt
+    pub wait_prompt: bool,
+}
+
+/// comment prefixes offered by the paste dialog: shell, Cisco style, C and
+/// INI style
+pub const COMMENTS: &[&str] = &["#", "!", "//", ";"];
+
+impl Options {
+    /// `text` split into lines with these changes made, each ending in
+    /// `line_ending`
+    pub fn lines(&self, text: &str) -> Vec<Vec<u8>> {
+        text.lines()
+            .filter(|line| !(self.strip_blank && line.trim().is_empty()))
+            .filter(|line| {
+                self.comment
+                    .as_ref()
+                    .is_none_or(|c| !line.trim_start().starts_with(c.as_str()))
+            })
+            .map(|line| [line.as_bytes(), self.line_ending.bytes()].concat())
+            .collect()
+    }
+}
+
+/// the bytes in hex text copied from other tools, like `DE AD BE EF`,
+/// `0xde, 0xad`, `{ 0xDE, 0xAD }`, `\xde\xad` or `deadbeef`
+pub fn hex(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let text = text.replace("\\x", " 0x");
+    let separator = |c: char| c.is_whitespace() || ",;:[]{}()".contains(c);
+    for token in text.split(separator).filter(|t| !t.is_empty()) {
+        let digits = token
+            .strip_prefix("0x")
+            .or_else(|| token.strip_prefix("0X"));
+        match digits {
+            // prefixed, a byte that may leave out its leading zero
+            Some(d) if (1..=2).contains(&d.len()) && d.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                bytes.push(u8::from_str_radix(d, 16).unwrap())
+            }
+            Some(_) => return Err(format!("invalid hex `{token}`")),
+            None => bytes.extend(parse_hex(token)?),
+        }
+    }
+    if bytes.is_empty() {
+        return Err(String::from("no hex bytes in the paste"));
+    }
+    Ok(bytes)
+}
+
+/// Pasted lines being sent one at a time
+pub struct Paste {
+    lines: VecDeque<Vec<u8>>,
+    pub total: usize,
+    delay: Duration,
+    wait_prompt: bool,
+    /// when the last line went out, `None` before the first
+    sent_at: Option<Instant>,
+    /// the profile's startup commands rather than pasted text
+    pub startup: bool,
+}
+
+impl Paste {
+    pub fn new(lines: Vec<Vec<u8>>, options: &Options) -> Self {
+        Self {
+            total: lines.len(),
+            lines: lines.into(),
+            delay: options.delay,
+            wait_prompt: options.wait_prompt,
+            sent_at: None,
+            startup: false,
+        }
+    }
+    /// lines sent so far
+    pub fn sent(&self) -> usize {
+        self.total - self.lines.len()
+    }
+    pub fn is_done(&self) -> bool {
+        self.lines.is_empty()
+    }
+    /// the next line if it's due, `ready` telling whether the device shows
+    /// its prompt
+    pub fn poll(&mut self, ready: bool) -> Option<Vec<u8>> {
+        if let Some(at) = self.sent_at {
+            if at.elapsed() < self.delay || (self.wait_prompt && !ready) {
+                return None;
+            }
+        }
+        let line = self.lines.pop_front()?;
+        self.sent_at = Some(Instant::now());
+        Some(line)
+    }
+}
@@ -0,0 +1,67 @@
+//! Frame time instrumentation for the debug overlay
+use std::time::{Duration, Instant};
+
+pub struct FrameTimes {
+    /// whether the debug overlay is drawn
+    pub shown: bool,
+    /// moving averages in seconds
+    update: f64,
+    draw: f64,
+    frame: f64,
+    last: Instant,
+}
+
+impl FrameTimes {
+    /// weight of the newest frame in the moving averages
+    const WEIGHT: f64 = 0.1;
+    /// time between frames at 60 fps
+    const FRAME: Duration = Duration::from_micros(16_667);
+    /// frames missed by a hitch worth tracing, fewer go unnoticed
+    const HITCH: u32 = 3;
+
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            update: 0.0,
+            draw: 0.0,
+            frame: 0.0,
+            last: Instant::now(),
+        }
+    }
+    /// records how long updating the app and drawing the last frame took,
+    /// returning the frames that were dropped meanwhile if it's a hitch
+    pub fn record(&mut self, update: Duration, draw: Duration) -> Option<u32> {
+        let avg = |avg: &mut f64, d: Duration| {
+            *avg += (d.as_secs_f64() - *avg) * Self::WEIGHT;
+        };
+        avg(&mut self.update, update);
+        avg(&mut self.draw, draw);
+        avg(&mut self.frame, self.last.elapsed());
+        self.last = Instant::now();
+        let dropped = ((update + draw).as_nanos() / Self::FRAME.as_nanos()) as u32;
+        (dropped >= Self::HITCH).then_some(dropped)
+    }
+}
+
+/// formatted like `update 0.12ms | draw 1.40ms | 59 fps`
+impl std::fmt::Display for FrameTimes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fps = if self.frame > 0.0 {
+            1.0 / self.frame
+        } else {
+            0.0
+        };
+        write!(
+            f,
+            "update {:.2}ms | draw {:.2}ms | {fps:.0} fps",
+            self.update * 1e3,
+            self.draw * 1e3
+        )
+    }
+}
+
+impl Default for FrameTimes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{Error, Result};
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Sends a payload every `interval` while enabled
+pub struct Periodic {
+    pub template: Template,
+    pub interval: Duration,
+    pub enabled: bool,
+    counter: u64,
+    last: Instant,
+}
+
+impl Periodic {
+    pub fn new(template: Template, interval: Duration) -> Self {
+        Self {
+            template,
+            interval,
+            enabled: false,
+            counter: 0,
+            last: Instant::now(),
+        }
+    }
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        // send right away when enabled
+        self.last = Instant::now() - self.interval;
+    }
+    /// number of payloads sent so far
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+    /// returns the next payload if it is due
+    pub fn poll(&mut self) -> Option<Vec<u8>> {
+        if !self.enabled || self.last.elapsed() < self.interval {
+            return None;
+        }
+        self.last = Instant::now();
+        let out = self.template.render(self.counter);
+        self.counter = self.counter.wrapping_add(1);
+        Some(out)
+    }
+}
+
+/// Sends a payload once the link has been quiet for `idle`, keeping it from being dropped
+pub struct Keepalive {
+    pub template: Template,
+    pub idle: Duration,
+    counter: u64,
+}
+
+impl Keepalive {
+    pub fn new(template: Template, idle: Duration) -> Self {
+        Self {
+            template,
+            idle,
+            counter: 0,
+        }
+    }
+    /// returns the next payload if nothing was sent or received since `idle` before now
+    pub fn poll(&mut self, last_traffic: Instant) -> Option<Vec<u8>> {
+        if last_traffic.elapsed() < self.idle {
+            return None;
+        }
+        let out = self.template.render(self.counter);
+        self.counter = self.counter.wrapping_add(1);
+        Some(out)
+    }
+}
+
+/// Payload with escapes and counter tokens already parsed.
+///
+/// Supported escapes are `\n`, `\r`, `\t`, `\\`, `\{` and `\xNN`.
+/// `{n}` expands to the counter, optionally formatted like `{n:04}`, `{n:x}`,
+/// `{n:02X}`, `{n:o}` or `{n:08b}`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Bytes(Vec<u8>),
+    Counter(CounterFormat),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CounterFormat {
+    width: usize,
+    zero: bool,
+    radix: Radix,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Radix {
+    Dec,
+    LowerHex,
+    UpperHex,
+    Octal,
+    Binary,
+}
+
+impl Template {
+    pub fn render(&self, counter: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        for part in &self.parts {
+            match part {
+                Part::Bytes(b) => out.extend_from_slice(b),
+                Part::Counter(fmt) => out.extend_from_slice(fmt.format(counter).as_bytes()),
+            }
+        }
+        out
+    }
+}
+
+impl CounterFormat {
+    /// widest a counter is padded to, a u64 in binary
+    const MAX_WIDTH: usize = 64;
+
+    fn format(&self, n: u64) -> String {
+        let mut s = String::new();
+        let w = self.width;
+        let _ = match (self.radix, self.zero) {
+            (Radix::Dec, true) => write!(s, "{n:0w$}"),
+            (Radix::Dec, false) => write!(s, "{n:w$}"),
+            (Radix::LowerHex, true) => write!(s, "{n:0w$x}"),
+            (Radix::LowerHex, false) => write!(s, "{n:w$x}"),
+            (Radix::UpperHex, true) => write!(s, "{n:0w$X}"),
+            (Radix::UpperHex, false) => write!(s, "{n:w$X}"),
+            (Radix::Octal, true) => write!(s, "{n:0w$o}"),
+            (Radix::Octal, false) => write!(s, "{n:w$o}"),
+            (Radix::Binary, true) => write!(s, "{n:0w$b}"),
+            (Radix::Binary, false) => write!(s, "{n:w$b}"),
+        };
+        s
+    }
+}
+
+impl FromStr for CounterFormat {
+    type Err = Error;
+    /// parses the spec after the colon in `{n:spec}`
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = || Error::InvalidTemplate(format!("bad counter format `{spec}`"));
+        let (digits, radix) = match spec.char_indices().last() {
+            Some((i, 'x')) => (&spec[..i], Radix::LowerHex),
+            Some((i, 'X')) => (&spec[..i], Radix::UpperHex),
+            Some((i, 'o')) => (&spec[..i], Radix::Octal),
+            Some((i, 'b')) => (&spec[..i], Radix::Binary),
+            _ => (spec, Radix::Dec),
+        };
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let zero = digits.starts_with('0');
+        let width = if digits.is_empty() {
+            0
+        } else {
+            digits.parse().map_err(|_| invalid())?
+        };
+        // every tick allocates this much
+        if width > Self::MAX_WIDTH {
+            return Err(invalid());
+        }
+        Ok(Self { width, zero, radix })
+    }
+}
+
+/// writes `bytes` as a payload that parses back to them, escaping what isn't printable
+pub fn escape(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for &b in bytes {
+        match b {
+            b'\n' => s.push_str("\\n"),
+            b'\r' => s.push_str("\\r"),
+            b'\t' => s.push_str("\\t"),
+            b'\\' => s.push_str("\\\\"),
+            b'{' => s.push_str("\\{"),
+            b' '..=b'~' => s.push(b as char),
+            _ => {
+                let _ = write!(s, "\\x{b:02X}");
+            }
+        }
+    }
+    s
+}
+
+impl FromStr for Template {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut bytes = Vec::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => bytes.push(b'\n'),
+                    Some('r') => bytes.push(b'\r'),
+                    Some('t') => bytes.push(b'\t'),
+                    Some('\\') => bytes.push(b'\\'),
+                    Some('{') => bytes.push(b'{'),
+                    Some('x') => {
+                        let hex: String = chars.by_ref().take(2).collect();
+                        if hex.len() != 2 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                            return Err(Error::InvalidTemplate(format!(
+                                "bad escape `\\x{hex}`, expected two hex digits"
+                            )));
+                        }
+                        bytes.push(u8::from_str_radix(&hex, 16).unwrap());
+                    }
+                    Some(c) => {
+                        return Err(Error::InvalidTemplate(format!("unknown escape `\\{c}`")))
+                    }
+                    None => return Err(Error::InvalidTemplate("trailing `\\`".into())),
+                },
+                '{' => {
+                    let rest = chars.as_str();
+                    let Some(end) = rest.find('}') else {
+                        return Err(Error::InvalidTemplate(format!("unclosed `{{{rest}`")));
+                    };
+                    let token = rest[..end].to_owned();
+                    chars = rest[end + 1..].chars();
+                    let fmt = match token.split_once(':') {
+                        Some(("n", spec)) => spec.parse()?,
+                        None if token == "n" => "".parse()?,
+                        _ => {
+                            return Err(Error::InvalidTemplate(format!(
+                                "unknown token `{{{token}}}`"
+                            )))
+                        }
+                    };
+                    if !bytes.is_empty() {
+                        parts.push(Part::Bytes(std::mem::take(&mut bytes)));
+                    }
+                    parts.push(Part::Counter(fmt));
+                }
+                c => {
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        }
+        if !bytes.is_empty() {
+            parts.push(Part::Bytes(bytes));
+        }
+        Ok(Self { parts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(s: &str, n: u64) -> Vec<u8> {
+        s.parse::<Template>().unwrap().render(n)
+    }
+
+    #[test]
+    fn counter_formats() {
+        assert_eq!(render("PING {n}\r\n", 7), b"PING 7\r\n");
+        assert_eq!(render("{n:04}", 42), b"0042");
+        assert_eq!(render("{n:4}", 42), b"  42");
+        assert_eq!(render("{n:x}", 255), b"ff");
+        assert_eq!(render("{n:04X}", 255), b"00FF");
+        assert_eq!(render("{n:o}", 8), b"10");
+        assert_eq!(render("{n:08b}", 5), b"00000101");
+        assert_eq!(render("a{n}b{n}", 3), b"a3b3");
+        assert_eq!(render("{n:064b}", u64::MAX), [b'1'; 64]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(render(r"\n\r\t\\\{n}", 0), b"\n\r\t\\{n}");
+        assert_eq!(render(r"\x00\xfF", 0), [0x00, 0xFF]);
+        assert_eq!(render("é", 0), "é".as_bytes());
+    }
+
+    #[test]
+    fn escape_round_trips() {
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(render(&escape(&bytes), 0), bytes);
+    }
+
+    #[test]
+    fn rejects_bad_templates() {
+        for bad in [
+            r"\x",
+            r"\x4",
+            r"\x4g",
+            r"\x+1",
+            r"\q",
+            "trailing \\",
+            "{n",
+            "{n:04",
+            "{m}",
+            "{n:zz}",
+            "{n:-4}",
+            "{n:+4}",
+            "{n:+4x}",
+            "{n:65}",
+            "{n:99999999999}",
+        ] {
+            assert!(bad.parse::<Template>().is_err(), "`{bad}` parsed");
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Finding the port to connect to from what was typed on the command line
+use crate::remote;
+use serialport::SerialPortType;
+use std::path::Path;
+
+/// What a port argument refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolved {
+    Port(String),
+    /// several ports match, the user picks one
+    Ambiguous(Vec<String>),
+    None,
+}
+
+/// resolves `arg` against the enumerated ports.
+///
+/// Paths that exist are used as they are. Otherwise `arg` is a glob like
+/// `/dev/ttyUSB*` or a prefix like `ttyACM`, matched against the full port
+/// names and their file names.
+pub fn resolve(arg: &str) -> Resolved {
+    let arg = &normalize(arg);
+    let remote = arg.starts_with(remote::SCHEME) || arg.starts_with(remote::TLS_SCHEME);
+    if arg == "dummy" || remote || Path::new(arg).exists() {
+        return Resolved::Port(arg.to_owned());
+    }
+    let names = serialport::available_ports()
+        .map(|ports| ports.into_iter().map(|p| p.port_name).collect::<Vec<_>>())
+        .unwrap_or_default();
+    // `COM1` is a prefix of `COM12` too
+    if names.iter().any(|name| name == arg) {
+        return Resolved::Port(arg.to_owned());
+    }
+    let is_glob = arg.contains(['*', '?']);
+    let mut matches = names
+        .into_iter()
+        .filter(|name| {
+            [name.as_str(), base_name(name)].into_iter().any(|n| {
+                if is_glob {
+                    glob(arg, n)
+                } else {
+                    n.starts_with(arg)
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    match matches.len() {
+        // might still open, like a port enumeration doesn't know about
+        0 if !is_glob => Resolved::Port(arg.to_owned()),
+        0 => Resolved::None,
+        1 => Resolved::Port(matches.remove(0)),
+        _ => Resolved::Ambiguous(matches),
+    }
+}
+
+/// the port to use when none was given: the only USB serial port, otherwise
+/// any port the user picks
+pub fn default_port() -> Resolved {
+    let ports = serialport::available_ports().unwrap_or_default();
+    let mut usb = ports
+        .iter()
+        .filter(|p| matches!(p.port_type, SerialPortType::UsbPort(_)));
+    if let (Some(port), None) = (usb.next(), usb.next()) {
+        return Resolved::Port(port.port_name.clone());
+    }
+    match ports.len() {
+        0 => Resolved::None,
+        _ => Resolved::Ambiguous(ports.into_iter().map(|p| p.port_name).collect()),
+    }
+}
+
+/// how Windows users may type a port, made into what serialport opens: `com12`
+/// becomes `COM12`, and a `\\.\` prefix, needed by other tools for ports above
+/// `COM9`, is dropped since serialport adds it to every port itself
+#[cfg(windows)]
+pub fn normalize(arg: &str) -> String {
+    let arg = arg.strip_prefix(r"\\.\").unwrap_or(arg);
+    match arg.get(..3) {
+        Some(com) if com.eq_ignore_ascii_case("com") => format!("COM{}", &arg[3..]),
+        _ => arg.to_owned(),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn normalize(arg: &str) -> String {
+    arg.to_owned()
+}
+
+/// names of the ports there are now, enumerated again on every call so
+/// adapters plugged in after startup show up
+pub fn names() -> Result<Vec<String>, String> {
+    serialport::available_ports()
+        .map(|ports| ports.into_iter().map(|p| p.port_name).collect())
+        .map_err(|e| e.to_string())
+}
+
+/// the ports a port argument, or none, leaves to pick from now
+pub fn candidates(arg: Option<&str>) -> Vec<String> {
+    let resolved = match arg {
+        Some(arg) => resolve(arg),
+        None => default_port(),
+    };
+    match resolved {
+        Resolved::Port(port) => vec![port],
+        Resolved::Ambiguous(ports) => ports,
+        Resolved::None => Vec::new(),
+    }
+}
+
+/// human readable name of a port, like `USB Serial Port (COM12)` on Windows or
+/// the product name of USB adapters elsewhere
+pub fn friendly_name(port: &str) -> Option<String> {
+    let ports = serialport::available_ports().ok()?;
+    let info = ports.into_iter().find(|p| p.port_name == port)?;
+    match info.port_type {
+        SerialPortType::UsbPort(usb) => usb.product,
+        _ => None,
+    }
+}
+
+/// picker entries for `ports`, with their friendly names
+pub fn labels(ports: &[String]) -> Vec<String> {
+    let infos = serialport::available_ports().unwrap_or_default();
+    ports
+        .iter()
+        .map(|name| {
+            let product =
+                infos
+                    .iter()
+                    .find(|p| p.port_name == *name)
+                    .and_then(|p| match &p.port_type {
+                        SerialPortType::UsbPort(usb) => usb.product.as_deref(),
+                        _ => None,
+                    });
+            match product {
+                Some(product) => format!("{name}  {product}"),
+                None => name.clone(),
+            }
+        })
+        .collect()
+}
+
+fn base_name(port: &str) -> &str {
+    port.rsplit(['/', '\\']).next().unwrap_or(port)
+}
+
+/// matches `name` against a pattern where `*` stands for any text and `?` for one character
+fn glob(pattern: &str, name: &str) -> bool {
+    let (p, n) = (pattern.as_bytes(), name.as_bytes());
+    let (mut pi, mut ni) = (0, 0);
+    // where the last `*` was and how much of the name it covers so far
+    let mut star = None;
+    while ni < n.len() {
+        match p.get(pi) {
+            Some(b'*') => {
+                star = Some((pi, ni));
+                pi += 1;
+            }
+            Some(&c) if c == b'?' || c == n[ni] => {
+                pi += 1;
+                ni += 1;
+            }
+            _ => match star {
+                // let the `*` cover one more character
+                Some((sp, sn)) => {
+                    star = Some((sp, sn + 1));
+                    pi = sp + 1;
+                    ni = sn + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == b'*')
+}
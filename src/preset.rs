@@ -0,0 +1,105 @@
+//! Presets: watches, dropped bytes, windows and a decoder that go together for
+//! a kind of device, turned on and off as a bundle
+//!
+//! Presets are read from a TOML file like
+//! ```toml
+//! [[preset]]
+//! name = "zephyr-logs"
+//! watches = ["err=<err>", "wrn=<wrn>"]
+//! windows = ["grep <err>|<wrn>"]
+//!
+//! [[preset]]
+//! name = "slcan-bus"
+//! decoder = "slcan"
+//! drop = ["00"]
+//! ```
+use crate::decode::DecoderKind;
+use crate::fuzz::parse_bytes;
+use crate::watch::Watch;
+use crate::window;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Presets {
+    #[serde(default, rename = "preset")]
+    pub presets: Vec<Preset>,
+}
+
+impl Presets {
+    /// loads presets from `path`, none if it doesn't exist
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(s) => {
+                let presets: Self = toml::from_str(&s).map_err(|e| e.to_string())?;
+                // bad patterns are caught now rather than when the preset is used
+                for preset in &presets.presets {
+                    preset
+                        .parts()
+                        .map_err(|e| format!("preset `{}`, {e}", preset.name))?;
+                }
+                Ok(presets)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+}
+
+/// `<config dir>/tuicom/presets.toml`, where presets are read from by default
+pub fn default_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("tuicom");
+    path.push("presets.toml");
+    Some(path)
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    pub name: String,
+    /// as given to `--watch`
+    pub watches: Vec<String>,
+    /// bytes left out of RX, as given to `--drop`
+    pub drop: Vec<String>,
+    /// as given to `:window`
+    pub windows: Vec<String>,
+    pub decoder: Option<DecoderKind>,
+}
+
+/// What a preset turns on, parsed
+pub struct Parts {
+    pub watches: Vec<Watch>,
+    pub drop: Vec<RangeInclusive<u8>>,
+    pub windows: Vec<window::Kind>,
+    pub decoder: Option<DecoderKind>,
+}
+
+impl Preset {
+    pub fn parts(&self) -> Result<Parts, String> {
+        Ok(Parts {
+            watches: self
+                .watches
+                .iter()
+                .map(|w| w.parse().map_err(|e| format!("watch `{w}`: {e}")))
+                .collect::<Result<_, _>>()?,
+            drop: self
+                .drop
+                .iter()
+                .map(|b| parse_bytes(b).map_err(|e| format!("drop `{b}`: {e}")))
+                .collect::<Result<_, _>>()?,
+            windows: self
+                .windows
+                .iter()
+                .map(|w| w.parse().map_err(|e| format!("window `{w}`: {e}")))
+                .collect::<Result<_, _>>()?,
+            decoder: self.decoder,
+        })
+    }
+}
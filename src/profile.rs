@@ -0,0 +1,155 @@
+//! Per connection settings, stored as TOML in the user's config directory
+use crate::capture::LogFormat;
+use crate::decode::DecoderKind;
+use crate::line_edit::LineEnding;
+use crate::login::{AutoLogin, Login};
+use crate::{Error, Result};
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub layout: Layout,
+    /// logs in when the device asks, if set
+    pub login: Option<Login>,
+    /// redaction rules, used besides --redact
+    pub redact: Vec<String>,
+    /// commands sent right after connecting, if set
+    pub startup: Option<Startup>,
+    /// presets turned on when the profile is opened, besides --preset
+    pub presets: Vec<String>,
+    /// how this port is logged, if set
+    pub log: Option<Log>,
+}
+
+/// The `[log]` table of a profile: where and how this port's RX is logged, so
+/// every device keeps its own logs. The `--log` options take precedence
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Log {
+    /// file `O` logs to
+    pub path: Option<PathBuf>,
+    pub format: Option<LogFormat>,
+    /// size in KiB the log is rotated at
+    pub max_size: Option<u64>,
+    /// logs from the start, like `--log`
+    pub start: bool,
+}
+
+/// The `[startup]` table of a profile: commands sent every time the port is
+/// opened, like turning off the device's echo, a line each
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Startup {
+    pub commands: Vec<String>,
+    /// milliseconds between commands
+    pub delay: u64,
+    /// every command after the first waits for the `--prompt`, if there's one
+    pub wait_prompt: bool,
+}
+
+/// View state restored when the profile is opened again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Layout {
+    pub split: u16,
+    pub tx_hex: bool,
+    pub rx_hex: bool,
+    pub tx_wrap: bool,
+    pub rx_wrap: bool,
+    pub lf_crlf: bool,
+    /// a CR breaks RX lines
+    pub cr_lines: bool,
+    pub line_ending: LineEnding,
+    pub line_mode: bool,
+    pub decoder: Option<DecoderKind>,
+    pub terminal: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            split: 50,
+            tx_hex: false,
+            rx_hex: false,
+            tx_wrap: true,
+            rx_wrap: true,
+            lf_crlf: false,
+            cr_lines: false,
+            line_ending: LineEnding::default(),
+            line_mode: false,
+            decoder: None,
+            terminal: false,
+        }
+    }
+}
+
+impl Profile {
+    /// loads a profile, returning the defaults if it doesn't exist yet
+    pub fn load(name: &str) -> Result<Self> {
+        let Some(path) = path(name)? else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(s) => {
+                let profile: Self = toml::from_str(&s)
+                    .map_err(|e| Error::InvalidProfile(path.clone(), e.to_string()))?;
+                if let Some(login) = &profile.login {
+                    AutoLogin::new(login).map_err(|e| Error::InvalidProfile(path.clone(), e))?;
+                }
+                for rule in &profile.redact {
+                    Regex::new(rule).map_err(|e| {
+                        Error::InvalidProfile(path.clone(), format!("redact `{rule}`: {e}"))
+                    })?;
+                }
+                Ok(profile)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+    pub fn save(&self, name: &str) -> Result<()> {
+        let Some(path) = path(name)? else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let s = toml::to_string_pretty(self)
+            .map_err(|e| Error::InvalidProfile(path.clone(), e.to_string()))?;
+        fs::write(path, s)?;
+        Ok(())
+    }
+}
+
+/// profile name used when none is given, derived from the port so each device gets its own
+pub fn default_name(port: &str) -> String {
+    let base = port.rsplit(['/', '\\']).next().unwrap_or(port);
+    base.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// `<config dir>/tuicom/profiles/<name>.toml`, for names that stay in that
+/// directory
+fn path(name: &str) -> Result<Option<PathBuf>> {
+    if name.is_empty() || name.contains(['/', '\\', '\0']) || name.contains("..") {
+        return Err(Error::InvalidProfileName(name.to_owned()));
+    }
+    let Some(mut path) = dirs::config_dir() else {
+        return Ok(None);
+    };
+    path.push("tuicom");
+    path.push("profiles");
+    path.push(format!("{name}.toml"));
+    Ok(Some(path))
+}
@@ -0,0 +1,48 @@
+//! Recognizing the device's prompt, to tell when it's ready for input
+use regex::bytes::Regex;
+use std::time::Instant;
+
+pub struct Prompt {
+    /// matched against the last line received, like `\$ $` or `=> $`
+    pub pattern: Regex,
+    /// periodic sends wait for the prompt
+    pub gate: bool,
+    /// what arrived since the last line break
+    line: Vec<u8>,
+    /// when the prompt was last shown, until more arrives after it
+    shown: Option<Instant>,
+}
+
+impl Prompt {
+    /// longest last line kept, prompts are shorter
+    const MAX_LINE: usize = 256;
+
+    pub fn new(pattern: Regex, gate: bool) -> Self {
+        Self {
+            pattern,
+            gate,
+            line: Vec::new(),
+            shown: None,
+        }
+    }
+    /// looks for the prompt at the end of newly received `bytes`
+    pub fn scan(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        match bytes.iter().rposition(|&b| b == b'\n') {
+            Some(i) => {
+                self.line.clear();
+                self.line.extend_from_slice(&bytes[i + 1..]);
+            }
+            None => self.line.extend_from_slice(bytes),
+        }
+        let keep = self.line.len().saturating_sub(Self::MAX_LINE);
+        self.line.drain(..keep);
+        self.shown = self.pattern.is_match(&self.line).then(Instant::now);
+    }
+    /// whether the prompt is shown and nothing was sent since `last_write`
+    pub fn is_ready(&self, last_write: Instant) -> bool {
+        self.shown.is_some_and(|shown| shown > last_write)
+    }
+}
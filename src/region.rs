@@ -0,0 +1,150 @@
+//! Reading a region of RX another way than the pane shows it: as hex, in
+//! another text encoding or through a decoder, leaving the display as it is
+use crate::decode::{DecoderKind, Record};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// offsets, hex bytes and their ASCII, like `hexdump -C`
+    Hex,
+    Utf8,
+    Latin1,
+    Utf16Le,
+    Utf16Be,
+    Decoded(DecoderKind),
+}
+
+impl View {
+    /// views other than decoders, with the keys switching to them
+    pub const KEYS: &'static [(char, Self)] = &[
+        ('h', Self::Hex),
+        ('t', Self::Utf8),
+        ('l', Self::Latin1),
+        ('u', Self::Utf16Le),
+        ('U', Self::Utf16Be),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hex => "hex",
+            Self::Utf8 => "utf8",
+            Self::Latin1 => "latin1",
+            Self::Utf16Le => "utf16le",
+            Self::Utf16Be => "utf16be",
+            Self::Decoded(kind) => kind.name(),
+        }
+    }
+    /// `bytes` as seen through this view
+    pub fn lines(self, bytes: &[u8]) -> Vec<String> {
+        match self {
+            Self::Hex => hex_lines(bytes, 0..bytes.len().div_ceil(HEX_ROW)),
+            Self::Utf8 => text_lines(&String::from_utf8_lossy(bytes)),
+            Self::Latin1 => text_lines(&bytes.iter().map(|&b| b as char).collect::<String>()),
+            Self::Utf16Le | Self::Utf16Be => {
+                // an odd byte at the end is half a unit, left out
+                let units = bytes.chunks_exact(2).map(|pair| match self {
+                    Self::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                });
+                let text = char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect::<String>();
+                text_lines(&text)
+            }
+            Self::Decoded(kind) => {
+                let mut records = Vec::new();
+                kind.build().push(bytes, &mut records);
+                // marked like in the decoded view, not only by color
+                records
+                    .iter()
+                    .map(|Record { text, error, .. }| match error {
+                        true => format!("! {}", printable(text)),
+                        false => format!("  {}", printable(text)),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for View {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for View {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let view = Self::KEYS
+            .iter()
+            .map(|&(_, view)| view)
+            .find(|v| v.name() == s);
+        match view {
+            Some(view) => Ok(view),
+            None => s.parse().map(Self::Decoded).map_err(|_| {
+                format!(
+                    "unknown view `{s}`, expected hex, utf8, latin1, utf16le, utf16be or a decoder"
+                )
+            }),
+        }
+    }
+}
+
+/// bytes on a line of the hex view
+pub const HEX_ROW: usize = 16;
+
+/// lines `rows` of the hexdump of `bytes`
+pub fn hex_lines(bytes: &[u8], rows: Range<usize>) -> Vec<String> {
+    let start = (rows.start * HEX_ROW).min(bytes.len());
+    let end = (rows.end * HEX_ROW).min(bytes.len());
+    bytes[start..end]
+        .chunks(HEX_ROW)
+        .enumerate()
+        .map(|(i, row)| hex_row(start + i * HEX_ROW, row))
+        .collect()
+}
+
+/// `00000010  48 65 6C 6C 6F 0D 0A  |Hello..|`
+fn hex_row(offset: usize, row: &[u8]) -> String {
+    use std::fmt::Write;
+    let mut line = format!("{offset:08X} ");
+    for (i, b) in row.iter().enumerate() {
+        // an extra space halfway, like hexdump
+        if i == HEX_ROW / 2 {
+            line.push(' ');
+        }
+        write!(line, " {b:02X}").unwrap();
+    }
+    let missing = HEX_ROW - row.len();
+    let pad = 3 * missing + usize::from(row.len() <= HEX_ROW / 2);
+    line.extend(std::iter::repeat_n(' ', pad));
+    line.push_str("  |");
+    line.extend(row.iter().map(|&b| match b {
+        0x20..=0x7E => b as char,
+        _ => '.',
+    }));
+    line.push('|');
+    line
+}
+
+/// `text` split at line breaks
+fn text_lines(text: &str) -> Vec<String> {
+    text.lines().map(printable).collect()
+}
+
+/// `line` with control characters shown as symbols like `␛`, so they can't
+/// mess up the screen
+fn printable(line: &str) -> String {
+    line.replace('\t', "    ")
+        .chars()
+        .map(|c| match c {
+            '\x00'..='\x1F' => char::from_u32(0x2400 + c as u32).unwrap_or(c),
+            '\x7F' => '␡',
+            c if c.is_control() => '·',
+            c => c,
+        })
+        .collect()
+}
@@ -0,0 +1,709 @@
+//! Remote link: `--serve` makes tuicom share its port over TCP without a UI,
+//! and another tuicom opening `tuicom://host:port` works with that port as if
+//! it were plugged in locally
+//!
+//! Both ends exchange frames of a kind byte, a big endian `u32` length and the
+//! payload. The client says hello with its token first, the server welcomes it
+//! or tells it why not, then data goes both ways and the client sends line
+//! settings whenever they change. With `--tls-cert` the server speaks TLS and
+//! clients reach it as `tuicoms://host:port`.
+use crate::decode::LineSettings;
+use crate::link::{self, Link};
+use crate::tls::{self, ClientTls};
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, ServerConfig, ServerConnection, StreamOwned};
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Prefix of remote port names, like `tuicom://raspberrypi:7878`
+pub const SCHEME: &str = "tuicom://";
+/// Prefix of remote port names reached over TLS, like `tuicoms://raspberrypi:7878`
+pub const TLS_SCHEME: &str = "tuicoms://";
+/// Protocol version sent in the hello, bumped on incompatible changes and
+/// new frame kinds
+const VERSION: u8 = 2;
+/// Oldest version still spoken, with the frame kinds it knows
+const MIN_VERSION: u8 = 1;
+/// Largest frame accepted, anything bigger means the other end isn't tuicom
+const MAX_FRAME: usize = 1 << 20;
+/// How long the handshake may take
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a write may wait for the other end to take data
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// What a frame carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum Kind {
+    /// client to server: version and token
+    Hello = 1,
+    /// server to client: name of the port served
+    Welcome = 2,
+    /// server to client: why the client isn't let in
+    Denied = 3,
+    /// bytes from or for the port
+    Data = 4,
+    /// client to server: line settings and flow control
+    Settings = 5,
+    /// server to client: what went wrong with the port, before it hangs up
+    Error = 6,
+    /// client to server: OS buffers of the port to discard, 1 input, 2 output,
+    /// 3 both, since version 2
+    Clear = 7,
+}
+
+impl Kind {
+    const ALL: &'static [Self] = &[
+        Self::Hello,
+        Self::Welcome,
+        Self::Denied,
+        Self::Data,
+        Self::Settings,
+        Self::Error,
+        Self::Clear,
+    ];
+
+    fn from_byte(b: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|&k| k as u8 == b)
+    }
+}
+
+fn frame(kind: Kind, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(5 + payload.len());
+    frame.push(kind as u8);
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Splits a byte stream into frames
+#[derive(Default)]
+struct Frames {
+    buf: Vec<u8>,
+}
+
+impl Frames {
+    fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+    /// the next complete frame, if one arrived
+    fn next(&mut self) -> io::Result<Option<(Kind, Vec<u8>)>> {
+        let Some(header) = self.buf.get(..5) else {
+            return Ok(None);
+        };
+        let kind = match Kind::from_byte(header[0]) {
+            Some(kind) => kind,
+            // TLS records start with 0x14 to 0x17
+            None if (0x14..=0x17).contains(&header[0]) => {
+                return Err(invalid(format!(
+                    "the other end speaks TLS, open {TLS_SCHEME}"
+                )))
+            }
+            None => return Err(invalid(format!("unknown frame kind {}", header[0]))),
+        };
+        let len = u32::from_be_bytes(header[1..5].try_into().unwrap()) as usize;
+        if len > MAX_FRAME {
+            return Err(invalid(format!("frame of {len} bytes is too large")));
+        }
+        if self.buf.len() < 5 + len {
+            return Ok(None);
+        }
+        let payload = self.buf[5..5 + len].to_vec();
+        self.buf.drain(..5 + len);
+        Ok(Some((kind, payload)))
+    }
+}
+
+/// What the remote link goes over, a TCP stream or TLS on top of one
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+/// A non-blocking connection to the other end, split into frames
+struct Conn {
+    stream: Box<dyn Stream>,
+    frames: Frames,
+}
+
+impl Conn {
+    /// sets `tcp` up, with TLS on top of it when `tls` is given
+    fn new(tcp: TcpStream, tls: Option<Tls>) -> io::Result<Self> {
+        tcp.set_nonblocking(true)?;
+        tcp.set_nodelay(true)?;
+        let stream: Box<dyn Stream> = match tls {
+            None => Box::new(tcp),
+            Some(Tls::Client(config, name)) => {
+                let conn = ClientConnection::new(config, name).map_err(io::Error::other)?;
+                Box::new(StreamOwned::new(conn, tcp))
+            }
+            Some(Tls::Server(config)) => {
+                let conn = ServerConnection::new(config).map_err(io::Error::other)?;
+                Box::new(StreamOwned::new(conn, tcp))
+            }
+        };
+        Ok(Self {
+            stream,
+            frames: Frames::default(),
+        })
+    }
+    /// reads whatever arrived, `Ok(false)` when the other end hung up
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut buf = [0; 4096];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Ok(false),
+                // TLS without a goodbye, which frames make harmless: a cut
+                // short one is never taken
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+                Ok(n) => self.frames.push(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    fn next(&mut self) -> io::Result<Option<(Kind, Vec<u8>)>> {
+        self.frames.next()
+    }
+    /// sends a frame, waiting for room for up to `timeout`. Only a frame
+    /// none of which went out times out: the other end would take the next
+    /// one as the rest of a cut short one, so that breaks the connection
+    fn send(&mut self, kind: Kind, payload: &[u8], timeout: Duration) -> io::Result<()> {
+        let frame = frame(kind, payload);
+        let mut bytes = &frame[..];
+        let deadline = Instant::now() + timeout;
+        let wait = |started: bool| match Instant::now() < deadline {
+            true => {
+                thread::sleep(Duration::from_millis(1));
+                Ok(())
+            }
+            false if started => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "timed out in the middle of a frame",
+            )),
+            false => Err(io::Error::from(io::ErrorKind::TimedOut)),
+        };
+        while !bytes.is_empty() {
+            match self.stream.write(bytes) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => bytes = &bytes[n..],
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    wait(bytes.len() < frame.len())?
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        // TLS keeps records until they are flushed
+        loop {
+            match self.stream.flush() {
+                Ok(()) => return Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => wait(true)?,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    /// waits for the next frame during the handshake
+    fn expect(&mut self) -> io::Result<(Kind, Vec<u8>)> {
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        loop {
+            // a denial comes right before the other end hangs up
+            let open = self.fill()?;
+            if let Some(frame) = self.next()? {
+                return Ok(frame);
+            }
+            if !open {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if Instant::now() >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no handshake from the other end",
+                ));
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+/// TLS a connection is wrapped in
+#[derive(Clone)]
+enum Tls {
+    Client(Arc<ClientConfig>, ServerName<'static>),
+    Server(Arc<ServerConfig>),
+}
+
+/// position of `value` in one of the link tables, which both ends share
+fn index<T: PartialEq>(table: &[(&str, T)], value: T) -> u8 {
+    table.iter().position(|(_, v)| *v == value).unwrap_or(0) as u8
+}
+
+fn encode_settings(settings: LineSettings, flow_control: FlowControl) -> Vec<u8> {
+    let mut payload = settings.baud_rate.to_be_bytes().to_vec();
+    payload.extend([
+        index(link::DATA_BITS, settings.data_bits),
+        index(link::PARITIES, settings.parity),
+        index(link::STOP_BITS, settings.stop_bits),
+        index(link::FLOW_CONTROLS, flow_control),
+    ]);
+    payload
+}
+
+fn decode_settings(payload: &[u8]) -> Option<(LineSettings, FlowControl)> {
+    let [b0, b1, b2, b3, data_bits, parity, stop_bits, flow_control] = *payload else {
+        return None;
+    };
+    let settings = LineSettings {
+        baud_rate: u32::from_be_bytes([b0, b1, b2, b3]),
+        data_bits: link::DATA_BITS.get(data_bits as usize)?.1,
+        parity: link::PARITIES.get(parity as usize)?.1,
+        stop_bits: link::STOP_BITS.get(stop_bits as usize)?.1,
+    };
+    Some((settings, link::FLOW_CONTROLS.get(flow_control as usize)?.1))
+}
+
+/// compares tokens in time independent of where they differ
+fn same_token(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |d, (x, y)| d | (x ^ y)) == 0
+}
+
+/// a random token for a server started without one
+pub fn generate_token() -> io::Result<String> {
+    let mut bytes = [0; 12];
+    rustls::crypto::ring::default_provider()
+        .secure_random
+        .fill(&mut bytes)
+        .map_err(|_| io::Error::other("no randomness for a token"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// `addr` to listen on, a bare port like `7878` meaning this machine only
+pub fn bind_address(addr: &str) -> String {
+    match addr.parse::<u16>() {
+        Ok(port) => format!("127.0.0.1:{port}"),
+        Err(_) => addr.to_owned(),
+    }
+}
+
+/// whether listening on `addr` keeps the port to this machine
+pub fn is_loopback(addr: &str) -> io::Result<bool> {
+    Ok(addr.to_socket_addrs()?.all(|a| a.ip().is_loopback()))
+}
+
+/// Serves `link`'s port on `addr` to one client at a time until killed, over
+/// TLS when `tls` is given. The port is opened when a client is let in and
+/// closed when it leaves, so it can be unplugged and plugged back in between clients
+pub fn serve(
+    link: &mut Link,
+    addr: &str,
+    token: &str,
+    tls: Option<Arc<ServerConfig>>,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!(
+        "serving {} on {}{}",
+        link.port,
+        listener.local_addr()?,
+        if tls.is_some() { " with TLS" } else { "" }
+    );
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("accept failed: {e}");
+                continue;
+            }
+        };
+        let peer = stream
+            .peer_addr()
+            .map_or_else(|_| String::from("client"), |a| a.to_string());
+        let res = Conn::new(stream, tls.clone().map(Tls::Server))
+            .and_then(|mut conn| client(link, &mut conn, token));
+        match res {
+            Ok(()) => eprintln!("{peer} left"),
+            Err(e) => eprintln!("{peer}: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// tells the client why it isn't let in, returning that as an error
+fn deny(conn: &mut Conn, reason: &str) -> io::Error {
+    let _ = conn.send(Kind::Denied, reason.as_bytes(), HANDSHAKE_TIMEOUT);
+    io::Error::new(io::ErrorKind::PermissionDenied, reason.to_owned())
+}
+
+/// Why relaying stopped
+enum End {
+    /// the client left, or its connection failed
+    Client(io::Error),
+    /// the port failed, which the client is told about
+    Port(io::Error),
+}
+
+/// lets one client in and relays between it and the port until either goes away
+fn client(link: &mut Link, conn: &mut Conn, token: &str) -> io::Result<()> {
+    let (kind, hello) = conn.expect()?;
+    let version = match (kind, hello.split_first()) {
+        (Kind::Hello, Some((&v, []))) if (MIN_VERSION..=VERSION).contains(&v) => {
+            return Err(deny(conn, "a token is needed"))
+        }
+        (Kind::Hello, Some((&v, given))) if (MIN_VERSION..=VERSION).contains(&v) => {
+            if !same_token(token.as_bytes(), given) {
+                return Err(deny(conn, "wrong token"));
+            }
+            v
+        }
+        (Kind::Hello, Some((v, _))) => {
+            let reason = format!("protocol version {v} isn't supported, this is {VERSION}");
+            return Err(deny(conn, &reason));
+        }
+        _ => return Err(deny(conn, "expected a hello")),
+    };
+    let mut port = match link.open() {
+        Ok(port) => port,
+        Err(e) => return Err(deny(conn, &format!("can't open {}: {e}", link.port))),
+    };
+    // version 1 clients take the welcome as it was, the port name alone
+    let mut welcome = match version {
+        1 => Vec::new(),
+        _ => vec![VERSION],
+    };
+    welcome.extend_from_slice(link.port.as_bytes());
+    conn.send(Kind::Welcome, &welcome, HANDSHAKE_TIMEOUT)?;
+    eprintln!("client connected, {} opened", link.port);
+    match relay(link, port.as_mut(), conn) {
+        Ok(()) => Ok(()),
+        Err(End::Client(e)) => Err(e),
+        Err(End::Port(e)) => {
+            let _ = conn.send(Kind::Error, e.to_string().as_bytes(), HANDSHAKE_TIMEOUT);
+            Err(e)
+        }
+    }
+}
+
+fn relay(link: &mut Link, port: &mut dyn SerialPort, conn: &mut Conn) -> Result<(), End> {
+    let mut buf = vec![0; 4096];
+    loop {
+        let mut idle = true;
+        let available = port.bytes_to_read().map_err(|e| End::Port(e.into()))?;
+        if available > 0 {
+            let n = port
+                .read(&mut buf[..(available as usize).min(4096)])
+                .map_err(End::Port)?;
+            conn.send(Kind::Data, &buf[..n], WRITE_TIMEOUT)
+                .map_err(End::Client)?;
+            idle = false;
+        }
+        let open = conn.fill().map_err(End::Client)?;
+        while let Some((kind, payload)) = conn.next().map_err(End::Client)? {
+            idle = false;
+            match kind {
+                Kind::Data => port.write_all(&payload).map_err(End::Port)?,
+                Kind::Settings => match decode_settings(&payload) {
+                    Some((settings, flow_control)) => link
+                        .configure(port, settings, flow_control)
+                        .map_err(|e| End::Port(e.into()))?,
+                    None => return Err(End::Client(invalid("malformed settings"))),
+                },
+                Kind::Clear => {
+                    let buffer = match payload[..] {
+                        [1] => ClearBuffer::Input,
+                        [2] => ClearBuffer::Output,
+                        [3] => ClearBuffer::All,
+                        _ => return Err(End::Client(invalid("malformed clear"))),
+                    };
+                    port.clear(buffer).map_err(|e| End::Port(e.into()))?;
+                }
+                _ => {
+                    let e = invalid(format!("unexpected {kind:?} frame"));
+                    return Err(End::Client(e));
+                }
+            }
+        }
+        if !open {
+            return Ok(());
+        }
+        if idle {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+/// connects and says hello with `version`, returning the connection and the
+/// version both ends speak
+fn hello(addr: &str, token: Option<&str>, tls: Option<Tls>, version: u8) -> io::Result<(Conn, u8)> {
+    let mut conn = Conn::new(TcpStream::connect(addr)?, tls)?;
+    let mut hello = vec![version];
+    hello.extend_from_slice(token.unwrap_or_default().as_bytes());
+    conn.send(Kind::Hello, &hello, HANDSHAKE_TIMEOUT)?;
+    match conn.expect()? {
+        // a version 1 welcome is the port name alone
+        (Kind::Welcome, _) if version == 1 => Ok((conn, 1)),
+        (Kind::Welcome, welcome) => {
+            let theirs = welcome.first().copied().unwrap_or(MIN_VERSION);
+            Ok((conn, theirs.min(version)))
+        }
+        (Kind::Denied, reason) => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            String::from_utf8_lossy(&reason).into_owned(),
+        )),
+        (kind, _) => Err(invalid(format!("unexpected {kind:?} frame"))),
+    }
+}
+
+/// A port served by another tuicom, used like a local one
+pub struct RemotePort {
+    addr: String,
+    tls: bool,
+    /// the connection and what came over it, filled when asked how much there is to read
+    io: RefCell<Io>,
+    settings: LineSettings,
+    flow_control: FlowControl,
+    timeout: Duration,
+    /// protocol version both ends speak, what frames can be sent
+    version: u8,
+}
+
+struct Io {
+    conn: Conn,
+    data: VecDeque<u8>,
+    /// why the connection ended, once it has
+    closed: Option<String>,
+}
+
+impl RemotePort {
+    /// connects to the tuicom serving at `addr`, over TLS when `tls` is given,
+    /// and sets its port up with `settings`
+    pub fn connect(
+        addr: &str,
+        token: Option<&str>,
+        tls: Option<&ClientTls>,
+        settings: LineSettings,
+        flow_control: FlowControl,
+    ) -> io::Result<Self> {
+        let secure = tls.is_some();
+        let tls = match tls {
+            Some(tls) => {
+                let config = tls.config().map_err(io::Error::other)?;
+                let name = tls::server_name(addr).map_err(io::Error::other)?;
+                Some(Tls::Client(config, name))
+            }
+            None => None,
+        };
+        let (conn, version) = match hello(addr, token, tls.clone(), VERSION) {
+            // older servers turn down versions they don't know, and speak the oldest
+            Err(e) if e.to_string().starts_with("protocol version") => {
+                hello(addr, token, tls, MIN_VERSION)?
+            }
+            result => result?,
+        };
+        let port = Self {
+            addr: addr.to_owned(),
+            tls: secure,
+            io: RefCell::new(Io {
+                conn,
+                data: VecDeque::new(),
+                closed: None,
+            }),
+            settings,
+            flow_control,
+            timeout: WRITE_TIMEOUT,
+            version,
+        };
+        port.send_settings()?;
+        Ok(port)
+    }
+    fn send_settings(&self) -> io::Result<()> {
+        let payload = encode_settings(self.settings, self.flow_control);
+        self.send(Kind::Settings, &payload)
+    }
+    /// sends a frame, closing the connection on anything but a clean time out
+    fn send(&self, kind: Kind, payload: &[u8]) -> io::Result<()> {
+        let io = &mut *self.io.borrow_mut();
+        if let Some(e) = &io.closed {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, e.clone()));
+        }
+        let result = io.conn.send(kind, payload, self.timeout);
+        match &result {
+            Err(e) if e.kind() != io::ErrorKind::TimedOut => io.closed = Some(e.to_string()),
+            _ => (),
+        }
+        result
+    }
+    /// takes in whatever arrived, failing once the connection is gone
+    fn poll(&self) -> io::Result<()> {
+        let io = &mut *self.io.borrow_mut();
+        if io.closed.is_none() {
+            match io.conn.fill() {
+                Ok(true) => (),
+                Ok(false) => io.closed = Some(format!("{} hung up", self.addr)),
+                Err(e) => io.closed = Some(e.to_string()),
+            }
+            loop {
+                match io.conn.next() {
+                    Ok(Some((Kind::Data, data))) => io.data.extend(data),
+                    Ok(Some((Kind::Error, e))) => {
+                        io.closed = Some(String::from_utf8_lossy(&e).into_owned())
+                    }
+                    Ok(Some((kind, _))) => io.closed = Some(format!("unexpected {kind:?} frame")),
+                    Ok(None) => break,
+                    Err(e) => {
+                        io.closed = Some(e.to_string());
+                        break;
+                    }
+                }
+            }
+        }
+        // what arrived before the end is read first
+        match &io.closed {
+            Some(e) if io.data.is_empty() => {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, e.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Read for RemotePort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.poll()?;
+        let data = &mut self.io.get_mut().data;
+        let n = buf.len().min(data.len());
+        for (b, d) in buf.iter_mut().zip(data.drain(..n)) {
+            *b = d;
+        }
+        Ok(n)
+    }
+}
+
+impl Write for RemotePort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let buf = &buf[..buf.len().min(MAX_FRAME)];
+        self.send(Kind::Data, buf)?;
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// the settings are kept here and sent to the server on every change
+impl SerialPort for RemotePort {
+    fn name(&self) -> Option<String> {
+        let scheme = if self.tls { TLS_SCHEME } else { SCHEME };
+        Some(format!("{scheme}{}", self.addr))
+    }
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(self.settings.baud_rate)
+    }
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(self.settings.data_bits)
+    }
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(self.flow_control)
+    }
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(self.settings.parity)
+    }
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(self.settings.stop_bits)
+    }
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.settings.baud_rate = baud_rate;
+        Ok(self.send_settings()?)
+    }
+    fn set_data_bits(&mut self, data_bits: DataBits) -> serialport::Result<()> {
+        self.settings.data_bits = data_bits;
+        Ok(self.send_settings()?)
+    }
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> serialport::Result<()> {
+        self.flow_control = flow_control;
+        Ok(self.send_settings()?)
+    }
+    fn set_parity(&mut self, parity: Parity) -> serialport::Result<()> {
+        self.settings.parity = parity;
+        Ok(self.send_settings()?)
+    }
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> serialport::Result<()> {
+        self.settings.stop_bits = stop_bits;
+        Ok(self.send_settings()?)
+    }
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Err(unsupported())
+    }
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Err(unsupported())
+    }
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Err(unsupported())
+    }
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Err(unsupported())
+    }
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Err(unsupported())
+    }
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Err(unsupported())
+    }
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        self.poll()?;
+        Ok(self.io.borrow().data.len() as u32)
+    }
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+    /// what already arrived is dropped here, the server clears the port's buffers
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        let which = match buffer_to_clear {
+            ClearBuffer::Input => 1,
+            ClearBuffer::Output => 2,
+            ClearBuffer::All => 3,
+        };
+        if self.version < 2 {
+            return Err(serialport::Error::new(
+                serialport::ErrorKind::Unknown,
+                "the remote tuicom is too old to discard the port's buffers",
+            ));
+        }
+        self.send(Kind::Clear, &[which])?;
+        if matches!(buffer_to_clear, ClearBuffer::Input | ClearBuffer::All) {
+            self.io.borrow_mut().data.clear();
+        }
+        Ok(())
+    }
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Err(unsupported())
+    }
+    fn set_break(&self) -> serialport::Result<()> {
+        Err(unsupported())
+    }
+    fn clear_break(&self) -> serialport::Result<()> {
+        Err(unsupported())
+    }
+}
+
+fn unsupported() -> serialport::Error {
+    serialport::Error::new(
+        serialport::ErrorKind::Unknown,
+        "not supported over a remote link",
+    )
+}
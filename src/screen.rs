@@ -1,20 +1,218 @@
-use serialport::SerialPort;
+pub mod overlay;
+
+use crate::ack::AckGate;
+use crate::decode::{parse_hex, Decoder, DecoderKind, LineSettings, Record};
+use crate::eol::LineStats;
+use crate::line_edit::LineEnding;
+use regex::bytes::Regex;
+use serialport::{DataBits, Parity, SerialPort, StopBits};
+use std::borrow::Cow;
+use std::cell::{Ref, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
 use std::io;
+use std::ops::{Range, RangeInclusive};
+use std::time::{Duration, Instant};
 
 /// TX console
 pub struct Tx {
     pub display: Display,
     pub lf_crlf: bool,
+    /// what ends lines sent in line mode
+    pub line_ending: LineEnding,
+    /// bytes a transient write error kept from being sent, sent before anything new
+    queue: VecDeque<u8>,
+    retry: Option<Retry>,
+    /// large send being written from the queue
+    transfer: Option<Transfer>,
+    /// file whose transfer went out completely, until it's taken
+    sent_file: Option<String>,
+    /// writes everything through the queue no faster than the line carries
+    /// it, so the OS buffer stays empty and dropping the queue stops a send
+    pub shaping: bool,
+    /// bytes the line could have carried since the last flush, while shaping
+    credit: f64,
+    credited_at: Instant,
+    /// transfers wait for the device to acknowledge what it got
+    pub ack: Option<AckGate>,
+    /// bytes of the acknowledged unit still to write
+    unit_left: usize,
+    /// when bytes were last written
+    pub last_write: Instant,
+    pub bytes_sent: u64,
+    /// what was sent as it went out, oldest first: typed lines and payloads
+    /// sent at once
+    pub history: VecDeque<Vec<u8>>,
+    /// lines and frames the user sent, oldest first, what line mode recalls,
+    /// without periodic sends, keepalives and other payloads sent on their own
+    pub sent: VecDeque<Vec<u8>>,
+    /// bytes typed since the last history entry
+    line: Vec<u8>,
+}
+
+/// When the queued bytes are written next
+struct Retry {
+    at: Instant,
+    backoff: Duration,
+}
+
+impl Retry {
+    const FIRST: Duration = Duration::from_millis(50);
+    const MAX: Duration = Duration::from_secs(2);
+
+    fn new() -> Self {
+        Self {
+            at: Instant::now() + Self::FIRST,
+            backoff: Self::FIRST,
+        }
+    }
+    fn back_off(&mut self) {
+        self.backoff = (self.backoff * 2).min(Self::MAX);
+        self.at = Instant::now() + self.backoff;
+    }
+}
+
+/// A send too large to write at once, written from the queue a chunk per
+/// update so the UI keeps running
+struct Transfer {
+    /// name of the file being sent
+    file: Option<String>,
+    /// bytes sent and still queued
+    total: usize,
+    started: Instant,
+    /// bytes per second the line carries at its configured baud rate
+    line_rate: f64,
+}
+
+/// How far a transfer got
+pub struct Progress {
+    pub file: Option<String>,
+    pub sent: usize,
+    pub total: usize,
+    /// bytes per second
+    pub rate: f64,
+    pub eta: Duration,
+}
+
+/// formatted like `SEND 12.0/40.0 KiB 11.2 KiB/s ETA 0:03`
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let eta = self.eta.as_secs();
+        f.write_str("SEND ")?;
+        if let Some(file) = &self.file {
+            write!(f, "{file} ")?;
+        }
+        write!(
+            f,
+            "{}/{} {}/s ETA {}:{:02}",
+            size(self.sent as f64),
+            size(self.total as f64),
+            size(self.rate),
+            eta / 60,
+            eta % 60
+        )
+    }
+}
+
+/// `bytes` in B, KiB or MiB
+pub fn size(bytes: f64) -> String {
+    match bytes {
+        b if b < 1024.0 => format!("{b:.0} B"),
+        b if b < 1024.0 * 1024.0 => format!("{:.1} KiB", b / 1024.0),
+        b => format!("{:.1} MiB", b / (1024.0 * 1024.0)),
+    }
+}
+
+/// bytes per second `port` carries at its configured line settings
+fn line_rate(port: &dyn SerialPort) -> f64 {
+    let settings = LineSettings {
+        baud_rate: port.baud_rate().unwrap_or(9600),
+        data_bits: port.data_bits().unwrap_or(DataBits::Eight),
+        parity: port.parity().unwrap_or(Parity::None),
+        stop_bits: port.stop_bits().unwrap_or(StopBits::One),
+    };
+    settings.byte_rate()
 }
+
+/// errors worth writing again after a while, like a full output buffer
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+    )
+}
+
 impl Tx {
+    /// history entries kept, older ones are dropped
+    const MAX_HISTORY: usize = 10_000;
+    /// sends larger than this become transfers
+    pub const LARGE_SEND: usize = 1024;
+
     pub fn new() -> Self {
         Self {
             display: Display::new(),
             lf_crlf: false,
+            line_ending: LineEnding::default(),
+            queue: VecDeque::new(),
+            retry: None,
+            transfer: None,
+            sent_file: None,
+            shaping: false,
+            credit: 0.0,
+            credited_at: Instant::now(),
+            ack: None,
+            unit_left: 0,
+            last_write: Instant::now(),
+            bytes_sent: 0,
+            history: VecDeque::new(),
+            sent: VecDeque::new(),
+            line: Vec::new(),
+        }
+    }
+    pub fn clear(&mut self) {
+        self.display.clear();
+        self.history.clear();
+        self.line.clear();
+    }
+    /// ends the line being typed, making it a history entry
+    pub fn end_line(&mut self) {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.record(&line);
+            self.push_entry(line);
+        }
+    }
+    /// the last line or frame the user sent, what `r` sends again
+    pub fn last_user(&self) -> Option<&[u8]> {
+        self.sent.back().map(Vec::as_slice)
+    }
+    /// keeps `entry` as sent by the user, to be recalled later
+    pub fn record(&mut self, entry: &[u8]) {
+        if self.sent.len() == Self::MAX_HISTORY {
+            self.sent.pop_front();
         }
+        self.sent.push_back(entry.to_vec());
     }
-    pub fn with_cursor<'a>(&'a mut self, cursor: char) -> WithCursor<'a> {
-        WithCursor::new(&mut self.display.show, cursor)
+    fn push_entry(&mut self, entry: Vec<u8>) {
+        if self.history.len() == Self::MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(entry);
+    }
+    /// history entries as shown in the TX pane, then the line being typed
+    pub fn lines(&self) -> Vec<String> {
+        let hex = self.is_hex();
+        let mut lines = self
+            .history
+            .iter()
+            .map(|entry| format_entry(entry, hex))
+            .collect::<Vec<_>>();
+        let mut line = format_entry(&self.line, hex);
+        if let DisplayMode::Hex(ByteBuffer { buf: Some(nibble) }) = self.display.display_mode {
+            line.push(char::from_digit(nibble as u32, 16).map_or('?', |c| c.to_ascii_uppercase()));
+        }
+        lines.push(line);
+        lines
     }
     pub fn is_hex(&self) -> bool {
         match &self.display.display_mode {
@@ -29,33 +227,394 @@ impl Tx {
         }
     }
     pub fn send(&mut self, ch: u8, port: &mut dyn SerialPort) -> Result<(), io::Error> {
-        let Some(c) = self.display.push_char(ch) else {return Ok(())};
+        let Some(c) = self.display.push_char(ch) else {
+            return Ok(());
+        };
 
         let res = if self.lf_crlf && c == b'\n' {
-            port.write_all(b"\r\n")
+            self.write(b"\r\n", port)
         } else {
-            port.write_all(&[c])
+            self.write(&[c], port)
         };
-        if let Err(_) = &res {
+        if res.is_err() {
             self.display.pop();
+        } else {
+            // kept as it went out, so sending the line again repeats it exactly
+            if self.lf_crlf && c == b'\n' {
+                self.line.push(b'\r');
+            }
+            self.line.push(c);
+            if c == b'\n' {
+                self.end_line();
+            }
         }
         res
     }
+    /// sends raw bytes, bypassing hex input parsing
+    pub fn send_bytes(&mut self, bytes: &[u8], port: &mut dyn SerialPort) -> Result<(), io::Error> {
+        let wire = self.wire(bytes);
+        self.write(&wire, port)?;
+        for &b in bytes {
+            self.display.push_byte(b);
+        }
+        self.push_entry(wire);
+        Ok(())
+    }
+    /// `bytes` as `send_bytes` writes them, with LF mapped to CR + LF if it's on
+    pub fn wire(&self, bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(bytes.len());
+        for &b in bytes {
+            if self.lf_crlf && b == b'\n' {
+                out.extend_from_slice(b"\r\n");
+            } else {
+                out.push(b);
+            }
+        }
+        out
+    }
+    /// sends a line edited in line mode followed by `line_ending`, as is: LF
+    /// isn't mapped to CR + LF
+    pub fn send_line(&mut self, line: &[u8], port: &mut dyn SerialPort) -> Result<(), io::Error> {
+        let bytes = [line, self.line_ending.bytes()].concat();
+        self.send_raw(&bytes, port)?;
+        self.record(&bytes);
+        Ok(())
+    }
+    /// sends `bytes` as they are, like a pasted line that already ends the
+    /// way it should
+    pub fn send_raw(&mut self, bytes: &[u8], port: &mut dyn SerialPort) -> Result<(), io::Error> {
+        self.write(bytes, port)?;
+        for &b in bytes {
+            self.display.push_byte(b);
+        }
+        self.push_entry(bytes.to_vec());
+        Ok(())
+    }
+    /// sends a secret like a password as it is, without showing it or
+    /// keeping it in the history
+    pub fn send_secret(
+        &mut self,
+        secret: &[u8],
+        port: &mut dyn SerialPort,
+    ) -> Result<(), io::Error> {
+        self.write(secret, port)
+    }
+    /// queues what is written from now on as a transfer, until the queue runs empty
+    pub fn begin_transfer(&mut self, port: &dyn SerialPort) {
+        if self.transfer.is_some() {
+            return;
+        }
+        if let Some(ack) = &mut self.ack {
+            ack.reset();
+        }
+        self.unit_left = 0;
+        self.transfer = Some(Transfer {
+            file: None,
+            total: self.queue.len(),
+            started: Instant::now(),
+            line_rate: line_rate(port),
+        });
+    }
+    /// sends the contents of a file as a transfer, without showing it or
+    /// keeping it in the history
+    pub fn send_file(
+        &mut self,
+        name: String,
+        bytes: &[u8],
+        port: &mut dyn SerialPort,
+    ) -> Result<(), io::Error> {
+        self.begin_transfer(port);
+        if let Some(transfer) = &mut self.transfer {
+            transfer.file = Some(name);
+        }
+        self.write(bytes, port)
+    }
+    /// name of a file that finished sending since the last call
+    pub fn take_sent_file(&mut self) -> Option<String> {
+        self.sent_file.take()
+    }
+    /// progress of the running transfer
+    pub fn progress(&self) -> Option<Progress> {
+        let transfer = self.transfer.as_ref()?;
+        let sent = transfer.total - self.queue.len();
+        let elapsed = transfer.started.elapsed().as_secs_f64();
+        // go by the baud rate until enough went out to measure
+        let rate = if elapsed >= 1.0 && sent > 0 {
+            sent as f64 / elapsed
+        } else {
+            transfer.line_rate
+        };
+        Some(Progress {
+            file: transfer.file.clone(),
+            sent,
+            total: transfer.total,
+            rate,
+            eta: Duration::from_secs_f64(self.queue.len() as f64 / rate.max(1.0)),
+        })
+    }
+    /// writes `bytes`, queueing what a transient error kept from being sent
+    fn write(&mut self, bytes: &[u8], port: &mut dyn SerialPort) -> Result<(), io::Error> {
+        if bytes.len() > Self::LARGE_SEND {
+            self.begin_transfer(port);
+        }
+        // keep the order, nothing goes out before the queue
+        if !self.queue.is_empty() || self.transfer.is_some() || self.shaping {
+            self.queue.extend(bytes);
+            if let Some(transfer) = &mut self.transfer {
+                transfer.total += bytes.len();
+            }
+            return Ok(());
+        }
+        let mut sent = 0;
+        while sent < bytes.len() {
+            match port.write(&bytes[sent..]) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    sent += n;
+                    self.bytes_sent += n as u64;
+                    self.last_write = Instant::now();
+                }
+                Err(e) if is_transient(&e) => {
+                    self.queue.extend(&bytes[sent..]);
+                    self.retry = Some(Retry::new());
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+    /// writes the queue: a chunk of a running transfer every call, everything
+    /// else once its retry is due, backing off while errors stay transient.
+    /// Other errors drop the queue
+    pub fn flush(&mut self, port: &mut dyn SerialPort) -> Result<(), io::Error> {
+        if self.retry.as_ref().is_some_and(|r| r.at > Instant::now()) {
+            return Ok(());
+        }
+        // about 50ms of line time per call, so updates aren't held up
+        let mut budget = match &self.transfer {
+            Some(transfer) => ((transfer.line_rate / 20.0) as usize).max(16),
+            None if self.retry.is_some() || self.shaping => usize::MAX,
+            None => return Ok(()),
+        };
+        if self.shaping {
+            // no more than the line carried since the last call, and no
+            // bursts of more than 50ms after a pause
+            let rate = line_rate(port);
+            let now = Instant::now();
+            let elapsed = now.duration_since(self.credited_at).as_secs_f64();
+            self.credit = (self.credit + rate * elapsed).min((rate / 20.0).max(1.0));
+            self.credited_at = now;
+            budget = budget.min(self.credit as usize);
+        }
+        let budgeted = budget;
+        let mut stalled = false;
+        let gated = self.transfer.is_some() && self.ack.is_some();
+        while budget > 0 && !self.queue.is_empty() {
+            let mut limit = budget;
+            if let Some(ack) = self.ack.as_mut().filter(|_| gated) {
+                if self.unit_left == 0 {
+                    if !ack.can_send() {
+                        break;
+                    }
+                    ack.sent();
+                    self.unit_left = self
+                        .queue
+                        .iter()
+                        .take(AckGate::BLOCK)
+                        .position(|&b| b == b'\n')
+                        .map_or(self.queue.len().min(AckGate::BLOCK), |i| i + 1);
+                }
+                limit = limit.min(self.unit_left);
+            }
+            let (front, _) = self.queue.as_slices();
+            match port.write(&front[..front.len().min(limit)]) {
+                Ok(0) => {
+                    stalled = true;
+                    break;
+                }
+                Ok(n) => {
+                    self.queue.drain(..n);
+                    budget -= n;
+                    self.unit_left = self.unit_left.saturating_sub(n);
+                    self.bytes_sent += n as u64;
+                    self.last_write = Instant::now();
+                }
+                Err(e) if is_transient(&e) => {
+                    stalled = true;
+                    break;
+                }
+                Err(e) => {
+                    let dropped = self.drop_queue();
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!("{e}, dropped {dropped} queued bytes"),
+                    ));
+                }
+            }
+        }
+        if self.shaping {
+            self.credit -= (budgeted - budget) as f64;
+        }
+        if self.queue.is_empty() {
+            self.retry = None;
+            self.sent_file = self.transfer.take().and_then(|t| t.file);
+        } else if stalled {
+            match &mut self.retry {
+                Some(retry) => retry.back_off(),
+                None => self.retry = Some(Retry::new()),
+            }
+        } else {
+            self.retry = None;
+        }
+        Ok(())
+    }
+    /// bytes waiting to be written again
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+    /// gives up on the queued bytes, returning how many there were
+    pub fn drop_queue(&mut self) -> usize {
+        self.retry = None;
+        self.transfer = None;
+        self.unit_left = 0;
+        if let Some(ack) = &mut self.ack {
+            ack.reset();
+        }
+        self.queue.drain(..).count()
+    }
 }
 
 /// RX console
 pub struct Rx {
     pub display: Display,
+    /// bytes left out of the display, like idle padding
+    pub filter: ByteFilter,
+    pub breakpoints: Vec<Regex>,
+    /// last breakpoint hit, the display is paused while this is set
+    pub hit: Option<BreakHit>,
+    /// records decoded by the active decoder
+    pub records: Vec<Record>,
+    decoder: Option<(DecoderKind, Box<dyn Decoder>)>,
+    /// terminal emulator rendering the RX pane instead of the plain text view
+    pub term: Option<vt100::Parser>,
+    /// fixed buffer reads go through, reused so nothing is zeroed or reallocated per read
+    chunk: Box<[u8]>,
+    /// bytes processed by the last `recv`, cleared but not freed between reads
     recv_buf: Vec<u8>,
+    /// bytes read but left for later frames, when more came in than one frame processes
+    backlog: Vec<u8>,
+    /// bytes received while paused
+    held: Vec<u8>,
+    /// when bytes were last received
+    pub last_recv: Instant,
+    pub bytes_received: u64,
+    /// BELs shown since the app last looked, outside escape sequences
+    pub bells: usize,
+    /// line endings the device sent
+    pub line_stats: LineStats,
+    /// a CR breaks the line in the display, for devices ending lines with it
+    pub cr_lines: bool,
+    /// the last byte shown was a CR made a line break, an LF after it is dropped
+    after_cr: bool,
+}
+
+/// A breakpoint match that paused the RX display
+#[derive(Debug, Clone)]
+pub struct BreakHit {
+    /// index into `Rx::breakpoints`
+    pub breakpoint: usize,
+    pub at: Instant,
 }
 
 impl Rx {
+    /// how far back into the displayed data a match may start, so patterns split between two reads are still found
+    const BREAK_LOOKBACK: usize = 256;
+    /// initial terminal size, adjusted to the pane when drawn
+    const TERM_ROWS: u16 = 24;
+    const TERM_COLS: u16 = 80;
+    /// size of the buffer a read goes through, larger reads take several chunks
+    const CHUNK: usize = 4096;
+    /// most bytes processed per `recv`, the rest is carried over so a flood
+    /// doesn't stall input handling and drawing
+    const FRAME_BYTES: usize = 64 * 1024;
+
     pub fn new() -> Self {
         Self {
             display: Display::new(),
+            filter: ByteFilter::default(),
+            breakpoints: Vec::new(),
+            hit: None,
+            records: Vec::new(),
+            decoder: None,
+            term: None,
+            chunk: vec![0; Self::CHUNK].into_boxed_slice(),
             recv_buf: Vec::new(),
+            backlog: Vec::new(),
+            held: Vec::new(),
+            last_recv: Instant::now(),
+            bytes_received: 0,
+            bells: 0,
+            line_stats: LineStats::default(),
+            cr_lines: false,
+            after_cr: false,
         }
     }
+    pub fn clear(&mut self) {
+        self.display.clear();
+        self.records.clear();
+        if let Some((kind, decoder)) = &mut self.decoder {
+            *decoder = kind.build();
+        }
+        if let Some(term) = &mut self.term {
+            let (rows, cols) = term.screen().size();
+            *term = vt100::Parser::new(rows, cols, 0);
+        }
+    }
+    /// switches terminal emulation, replaying everything received so far
+    pub fn toggle_term(&mut self) {
+        self.term = match self.term {
+            Some(_) => None,
+            None => {
+                let mut term = vt100::Parser::new(Self::TERM_ROWS, Self::TERM_COLS, 0);
+                term.process(&self.display.buffer);
+                self.set_decoder(None);
+                Some(term)
+            }
+        };
+    }
+    pub fn is_term(&self) -> bool {
+        self.term.is_some()
+    }
+    pub fn decoder_kind(&self) -> Option<DecoderKind> {
+        self.decoder.as_ref().map(|(kind, _)| *kind)
+    }
+    pub fn decoder(&self) -> Option<&dyn Decoder> {
+        self.decoder.as_ref().map(|(_, decoder)| decoder.as_ref())
+    }
+    /// switches decoders, decoding everything received so far again
+    pub fn set_decoder(&mut self, kind: Option<DecoderKind>) {
+        if kind.is_some() {
+            self.term = None;
+        }
+        self.records.clear();
+        self.decoder = kind.map(|kind| (kind, kind.build()));
+        if let Some((_, decoder)) = &mut self.decoder {
+            decoder.push(&self.display.buffer, &mut self.records);
+        }
+    }
+    pub fn is_paused(&self) -> bool {
+        self.hit.is_some()
+    }
+    /// number of bytes received but not shown since the display was paused
+    pub fn held(&self) -> usize {
+        self.held.len()
+    }
+    /// resumes the display until the next breakpoint
+    pub fn resume(&mut self) {
+        self.hit = None;
+        self.release();
+    }
     pub fn is_hex(&self) -> bool {
         match &self.display.display_mode {
             DisplayMode::Ascii => false,
@@ -68,25 +627,221 @@ impl Rx {
             DisplayMode::Hex(_) => false,
         }
     }
-    pub fn with_cursor<'a>(&'a mut self, cursor: char) -> WithCursor<'a> {
-        WithCursor::new(&mut self.display.show, cursor)
-    }
-    pub fn recv(&mut self, port: &mut dyn SerialPort) -> Result<(), io::Error> {
+    /// reads what the port has, returning the number of bytes processed, at
+    /// most `FRAME_BYTES` with the rest carried over to the next call
+    pub fn recv(&mut self, port: &mut dyn SerialPort) -> Result<usize, io::Error> {
         let bytes = port.bytes_to_read()? as usize;
-        self.recv_buf.resize(bytes as usize, 0);
-        port.read_exact(&mut self.recv_buf[..])?;
-        for &b in &self.recv_buf {
-            self.display.push_byte(b);
+        let mut left = bytes;
+        while left > 0 {
+            let chunk = &mut self.chunk[..left.min(Self::CHUNK)];
+            port.read_exact(chunk)?;
+            self.backlog.extend_from_slice(chunk);
+            left -= chunk.len();
+        }
+        self.bytes_received += bytes as u64;
+        if bytes > 0 {
+            self.last_recv = Instant::now();
+        }
+        let take = self.backlog.len().min(Self::FRAME_BYTES);
+        self.recv_buf.clear();
+        self.recv_buf.extend(self.backlog.drain(..take));
+        self.held.extend_from_slice(&self.recv_buf);
+        if !self.is_paused() {
+            self.release();
+        }
+
+        Ok(take)
+    }
+    /// bytes read but not processed yet
+    pub fn backlog(&self) -> usize {
+        self.backlog.len()
+    }
+    /// everything received since the last clear, including bytes held by a breakpoint
+    pub fn contents(&self) -> Vec<u8> {
+        [&self.display.buffer[..], &self.held, &self.backlog].concat()
+    }
+    /// bytes read by the last `recv`
+    pub fn received(&self) -> &[u8] {
+        &self.recv_buf
+    }
+    /// replaces what was received with `bytes` from a saved session, with the
+    /// times its lines arrived
+    pub fn restore(&mut self, bytes: &[u8], arrivals: Vec<(usize, Instant)>) {
+        self.clear();
+        self.hit = None;
+        self.held.clear();
+        self.display.restore(bytes, arrivals);
+        if let Some((_, decoder)) = &mut self.decoder {
+            decoder.push(bytes, &mut self.records);
+        }
+        if let Some(term) = &mut self.term {
+            term.process(bytes);
+        }
+    }
+    /// moves held bytes to the display, stopping right after the first breakpoint match
+    fn release(&mut self) {
+        let mut cut = self.held.len();
+        if !self.breakpoints.is_empty() && !self.held.is_empty() {
+            let shown = &self.display.buffer;
+            let tail = &shown[shown.len().saturating_sub(Self::BREAK_LOOKBACK)..];
+            let mut window = Vec::with_capacity(tail.len() + self.held.len());
+            window.extend_from_slice(tail);
+            window.extend_from_slice(&self.held);
+
+            // earliest match that ends in the new data
+            let first = self
+                .breakpoints
+                .iter()
+                .enumerate()
+                .filter_map(|(i, re)| {
+                    re.find_iter(&window)
+                        .find(|m| m.end() > tail.len())
+                        .map(|m| (m.end() - tail.len(), i))
+                })
+                .min();
+            if let Some((end, breakpoint)) = first {
+                cut = end;
+                self.hit = Some(BreakHit {
+                    breakpoint,
+                    at: Instant::now(),
+                });
+            }
+        }
+        let shown: Vec<u8> = self.held.drain(..cut).collect();
+        self.line_stats.push(&shown);
+        if let Some((_, decoder)) = &mut self.decoder {
+            // decoders get every byte, padding can be part of a frame
+            decoder.push(&shown, &mut self.records);
+        }
+        let shown = self.filter.apply(&shown);
+        if !shown.is_empty() {
+            self.display.mark_fresh();
+        }
+        for &b in shown.iter() {
+            match b {
+                b'\r' if self.cr_lines => self.display.push_byte(b'\n'),
+                b'\n' if self.after_cr => (),
+                _ => self.display.push_byte(b),
+            }
+            self.after_cr = self.cr_lines && b == b'\r';
+        }
+        if let Some(term) = &mut self.term {
+            // BEL also ends OSC sequences like window titles, which don't ring
+            let before = term.screen().audible_bell_count();
+            term.process(&shown);
+            self.bells += term.screen().audible_bell_count().wrapping_sub(before);
+        } else if self.decoder.is_none() {
+            self.bells += shown.iter().filter(|&&b| b == BEL).count();
         }
+    }
+}
+
+/// Bytes left out of the RX display and the text log, for lines where idle
+/// padding like NULs or 0xFF drowns the data
+#[derive(Debug, Clone, Default)]
+pub struct ByteFilter {
+    ranges: Vec<RangeInclusive<u8>>,
+}
 
+impl ByteFilter {
+    pub fn new(ranges: Vec<RangeInclusive<u8>>) -> Self {
+        Self { ranges }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+    pub fn ranges(&self) -> &[RangeInclusive<u8>] {
+        &self.ranges
+    }
+    pub fn drops(&self, byte: u8) -> bool {
+        self.ranges.iter().any(|r| r.contains(&byte))
+    }
+    /// `bytes` without the ones dropped, borrowed when nothing is
+    pub fn apply<'a>(&self, bytes: &'a [u8]) -> Cow<'a, [u8]> {
+        match bytes.iter().any(|&b| self.drops(b)) {
+            true => Cow::Owned(bytes.iter().copied().filter(|&b| !self.drops(b)).collect()),
+            false => Cow::Borrowed(bytes),
+        }
+    }
+}
+
+/// formatted like `00 80-9F`, as given to `--drop`
+impl fmt::Display for ByteFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, r) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            match r.start() == r.end() {
+                true => write!(f, "{:02X}", r.start())?,
+                false => write!(f, "{:02X}-{:02X}", r.start(), r.end())?,
+            }
+        }
         Ok(())
     }
 }
 
+/// parses a `--break` regex
+pub fn parse_breakpoint(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|e| e.to_string())
+}
+
+/// parses a `--break-hex` byte pattern like `DE AD BE EF` into a regex matching those bytes
+pub fn parse_hex_breakpoint(s: &str) -> Result<Regex, String> {
+    use std::fmt::Write;
+    let bytes = parse_hex(s)?;
+    if bytes.is_empty() {
+        return Err(format!("empty hex pattern `{s}`"));
+    }
+    let mut pattern = String::from("(?-u)");
+    for b in bytes {
+        write!(pattern, "\\x{b:02X}").unwrap();
+    }
+    parse_breakpoint(&pattern)
+}
+
 pub struct Display {
     buffer: Vec<u8>,
     show: String,
+    /// lines of `show`, indexed as they are pushed
+    lines: Vec<Line>,
+    /// offsets in `buffer` where lines of received bytes start, and when they arrived
+    arrivals: Vec<(usize, Instant)>,
     display_mode: DisplayMode,
+    /// whether long lines wrap, rather than being cut at the pane edge
+    pub wrap: bool,
+    /// how long just received text stays highlighted, not at all without
+    pub fade: Option<Duration>,
+    /// offsets in `show` where text received within `fade` starts, and when it came
+    fresh: VecDeque<(usize, Instant)>,
+    /// times the lines were cleared or made again, so views of them know
+    generation: u64,
+    /// rows of the lines as last drawn, so only new lines are counted
+    rows: RefCell<Rows>,
+}
+
+/// Row every line starts at for a width, kept between frames
+#[derive(Debug, Default)]
+struct Rows {
+    width: u16,
+    wrap: bool,
+    generation: u64,
+    /// as returned by [`Display::rows`], the last line counted again next time
+    /// since it may have grown
+    starts: Vec<usize>,
+}
+
+/// Where a line of the shown text is, so lines are found without scanning the text
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    /// byte offset of its start in the shown text
+    start: usize,
+    /// offset of its first byte in the buffer
+    offset: usize,
+    /// characters shown, not counting the line break
+    chars: usize,
+    /// ends with `\r`, which isn't shown before a line break
+    cr: bool,
 }
 
 impl Display {
@@ -94,13 +849,207 @@ impl Display {
         Self {
             buffer: Vec::new(),
             show: String::new(),
+            lines: Vec::new(),
+            arrivals: Vec::new(),
             display_mode: DisplayMode::Ascii,
+            wrap: true,
+            fade: None,
+            fresh: VecDeque::new(),
+            generation: 0,
+            rows: RefCell::default(),
+        }
+    }
+    /// text as shown, with hex formatting applied
+    pub fn text(&self) -> &str {
+        &self.show
+    }
+    pub fn bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+    /// where the byte at `offset` is in the shown text, in the hex view
+    pub fn hex_range(&self, offset: usize) -> Option<Range<usize>> {
+        match self.display_mode {
+            DisplayMode::Hex(_) if offset < self.buffer.len() => Some(3 * offset..3 * offset + 2),
+            _ => None,
+        }
+    }
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+    /// line `i` of the text as shown, without its line break
+    pub fn line(&self, i: usize) -> &str {
+        let start = self.lines[i].start;
+        let end = self.lines.get(i + 1).map_or(self.show.len(), |l| l.start);
+        let line = &self.show[start..end];
+        match line.strip_suffix('\n') {
+            Some(line) => line.strip_suffix('\r').unwrap_or(line),
+            None => line,
+        }
+    }
+    pub fn lines(&self) -> impl Iterator<Item = &str> + '_ {
+        (0..self.lines.len()).map(|i| self.line(i))
+    }
+    /// text of the lines in `range`, with their line breaks
+    pub fn lines_text(&self, range: Range<usize>) -> &str {
+        let start = self
+            .lines
+            .get(range.start)
+            .map_or(self.show.len(), |l| l.start);
+        let end = self
+            .lines
+            .get(range.end)
+            .map_or(self.show.len(), |l| l.start);
+        &self.show[start..end.max(start)]
+    }
+    /// characters line `i` takes on screen
+    pub fn line_width(&self, i: usize) -> usize {
+        let line = &self.lines[i];
+        let broken = i + 1 < self.lines.len() || self.show.ends_with('\n');
+        line.chars - usize::from(line.cr && broken)
+    }
+    /// offset of the first byte of line `i` in the buffer
+    pub fn line_offset(&self, i: usize) -> usize {
+        self.lines[i].offset
+    }
+    /// when the first byte of line `i` was pushed
+    pub fn line_time(&self, i: usize) -> Option<Instant> {
+        let offset = self.lines.get(i)?.offset;
+        let n = self.arrivals.partition_point(|&(o, _)| o <= offset);
+        Some(self.arrivals.get(n.checked_sub(1)?)?.1)
+    }
+    /// offsets in the buffer where lines start, and when they arrived
+    pub fn arrivals(&self) -> &[(usize, Instant)] {
+        &self.arrivals
+    }
+    /// offsets in the shown text where text received within `fade` starts,
+    /// and when it came
+    pub fn fresh(&self) -> &VecDeque<(usize, Instant)> {
+        &self.fresh
+    }
+    /// notes that what's shown next was just received, while fading is on
+    fn mark_fresh(&mut self) {
+        let Some(fade) = self.fade else {
+            return;
+        };
+        while self
+            .fresh
+            .front()
+            .is_some_and(|&(_, at)| at.elapsed() >= fade)
+        {
+            self.fresh.pop_front();
         }
+        self.fresh.push_back((self.show.len(), Instant::now()));
+    }
+    /// row every line starts at when drawn `width` columns wide, followed by the
+    /// number of rows of all of them. Only lines added since the last call are
+    /// counted, unless the width or the lines changed otherwise
+    pub fn rows(&self, width: u16, wrap: bool) -> Ref<'_, [usize]> {
+        {
+            let mut rows = self.rows.borrow_mut();
+            if (rows.width, rows.wrap, rows.generation) != (width, wrap, self.generation)
+                || rows.starts.is_empty()
+            {
+                *rows = Rows {
+                    width,
+                    wrap,
+                    generation: self.generation,
+                    starts: vec![0],
+                };
+            }
+            let from = (rows.starts.len() - 1)
+                .saturating_sub(1)
+                .min(self.lines.len().saturating_sub(1));
+            rows.starts.truncate(from + 1);
+            let mut row = rows.starts[from];
+            for i in from..self.lines.len() {
+                row += self.line_rows(i, width, wrap);
+                rows.starts.push(row);
+            }
+        }
+        Ref::map(self.rows.borrow(), |rows| &rows.starts[..])
+    }
+    fn line_rows(&self, i: usize, width: u16, wrap: bool) -> usize {
+        match (wrap, &self.display_mode) {
+            (false, _) => 1,
+            // bytes wrap as whole words
+            (true, DisplayMode::Hex(_)) => {
+                let bytes = self.line_width(i).div_ceil(3);
+                bytes.div_ceil(hex_row_bytes(width)).max(1)
+            }
+            (true, DisplayMode::Ascii) => rows_for(self.line_width(i), width),
+        }
+    }
+    /// the part of the shown text on rows `top..top + height`, given `rows` from
+    /// [`Self::rows`], and how many rows of it are above `top`. Wrapped lines
+    /// are cut where a row starts, so a hex view or a stream without line
+    /// breaks, all one line, isn't copied whole to draw a screenful of it
+    pub fn visible(
+        &self,
+        rows: &[usize],
+        (top, height): (usize, usize),
+        width: u16,
+    ) -> (Range<usize>, usize) {
+        let n = self.lines.len();
+        let bottom = top + height;
+        let first = rows.partition_point(|&r| r <= top).saturating_sub(1).min(n);
+        let last = rows.partition_point(|&r| r < bottom).min(n);
+        let line_start = |i: usize| self.lines.get(i).map_or(self.show.len(), |l| l.start);
+        let (mut start, mut end) = (line_start(first), line_start(last));
+        let mut skip = top.saturating_sub(rows[first]);
+        if self.wrap && first < n && skip > 0 {
+            start = self.row_start(first, skip, width);
+            skip = 0;
+        }
+        if self.wrap && last > 0 && last > first {
+            let shown = bottom - rows[last - 1];
+            if rows[last - 1] + shown < rows[last] {
+                end = self.row_start(last - 1, shown, width);
+            }
+        }
+        (start..end.max(start), skip)
+    }
+    /// where row `row` of wrapped line `i` starts in the shown text
+    fn row_start(&self, i: usize, row: usize, width: u16) -> usize {
+        let per_row = match self.display_mode {
+            DisplayMode::Hex(_) => 3 * hex_row_bytes(width),
+            DisplayMode::Ascii => width.max(1) as usize,
+        };
+        let line = &self.lines[i];
+        let end = self.lines.get(i + 1).map_or(self.show.len(), |l| l.start);
+        let chars = (row * per_row).min(line.chars);
+        let text = &self.show[line.start..end];
+        // most lines are ASCII, a character a byte
+        if text.len() - usize::from(text.ends_with('\n')) == line.chars {
+            return line.start + chars;
+        }
+        line.start
+            + text
+                .char_indices()
+                .nth(chars)
+                .map_or(text.len(), |(at, _)| at)
     }
     pub fn clear(&mut self) {
         self.buffer.clear();
         self.show.clear();
+        self.lines.clear();
+        self.arrivals.clear();
+        self.fresh.clear();
         self.display_mode.clear();
+        self.generation += 1;
+    }
+    /// changes whenever the lines are cleared or made again, rather than
+    /// only added to
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// replaces the buffer with `bytes`, lines arriving as in `arrivals`
+    pub fn restore(&mut self, bytes: &[u8], arrivals: Vec<(usize, Instant)>) {
+        self.clear();
+        for (offset, &b) in bytes.iter().enumerate() {
+            self.buffer.push(b);
+            self.show_push(b, offset);
+        }
+        self.arrivals = arrivals;
     }
     pub fn switch_hex(&mut self) {
         self.display_mode = match self.display_mode {
@@ -108,15 +1057,11 @@ impl Display {
             DisplayMode::Hex(_) => DisplayMode::Ascii,
         };
         self.show.clear();
-
-        if let DisplayMode::Ascii = self.display_mode {
-            for &b in &self.buffer {
-                push_ascii(&mut self.show, b);
-            }
-        } else {
-            for &b in &self.buffer {
-                push_hex(&mut self.show, b);
-            }
+        self.lines.clear();
+        self.fresh.clear();
+        self.generation += 1;
+        for offset in 0..self.buffer.len() {
+            self.show_push(self.buffer[offset], offset);
         }
     }
     pub fn pop(&mut self) -> Option<u8> {
@@ -130,13 +1075,44 @@ impl Display {
                 self.show.pop();
             }
         }
-        self.buffer.pop()
+        // index the last line again, whatever is left of it
+        while self
+            .lines
+            .last()
+            .is_some_and(|l| l.start >= self.show.len())
+        {
+            self.lines.pop();
+        }
+        while self
+            .fresh
+            .back()
+            .is_some_and(|&(o, _)| o >= self.show.len())
+        {
+            self.fresh.pop_back();
+        }
+        if let Some(line) = self.lines.pop() {
+            self.index(line.start, line.offset);
+        }
+        // rows of the lines that changed are counted again
+        let keep = self.lines.len().max(1);
+        self.rows.get_mut().starts.truncate(keep);
+        let byte = self.buffer.pop();
+        if self
+            .arrivals
+            .last()
+            .is_some_and(|&(o, _)| o >= self.buffer.len())
+        {
+            self.arrivals.pop();
+        }
+        byte
     }
     /// pushes an ASCII digit to buffer and display, accounting for HEX mode
     pub fn push_char(&mut self, ch: u8) -> Option<u8> {
         let mut out = None;
+        let (from, offset) = (self.show.len(), self.buffer.len());
         match &mut self.display_mode {
             DisplayMode::Ascii => {
+                self.arrive();
                 self.buffer.push(ch);
                 push_ascii(&mut self.show, ch);
                 out = Some(ch);
@@ -146,6 +1122,7 @@ impl Display {
                 if let Some(c) = (ch as char).to_digit(16) {
                     let mut complete = false;
                     if let Some(b) = byte_buf.push(c as u8) {
+                        self.arrive();
                         self.buffer.push(b);
                         out = Some(b);
                         complete = true;
@@ -157,26 +1134,100 @@ impl Display {
                 }
             }
         }
+        self.index(from, offset);
         out
     }
     pub fn push_byte(&mut self, byte: u8) {
+        self.arrive();
         self.buffer.push(byte);
-        self.show_push(byte);
+        self.show_push(byte, self.buffer.len() - 1);
+    }
+    /// notes the time when the next byte starts a line of the buffer
+    fn arrive(&mut self) {
+        if self.buffer.last().is_none_or(|&b| b == b'\n') {
+            self.arrivals.push((self.buffer.len(), Instant::now()));
+        }
     }
-    fn show_push(&mut self, byte: u8) {
+    /// shows the byte at `offset` of the buffer
+    fn show_push(&mut self, byte: u8, offset: usize) {
+        let from = self.show.len();
         match &mut self.display_mode {
             DisplayMode::Ascii => push_ascii(&mut self.show, byte),
             DisplayMode::Hex(_) => push_hex(&mut self.show, byte),
         }
+        self.index(from, offset);
+    }
+    /// indexes the text shown from byte `from` on, for the byte at `offset`
+    fn index(&mut self, from: usize, offset: usize) {
+        let mut line_start = from == 0 || self.show.as_bytes()[from - 1] == b'\n';
+        for (i, c) in self.show[from..].char_indices() {
+            if line_start {
+                self.lines.push(Line {
+                    start: from + i,
+                    offset,
+                    chars: 0,
+                    cr: false,
+                });
+            }
+            line_start = c == '\n';
+            if c != '\n' {
+                // a line was pushed at the latest right above
+                let line = self.lines.last_mut().unwrap();
+                line.chars += 1;
+                line.cr = c == '\r';
+            }
+        }
     }
 }
+const BEL: u8 = 0x07;
+
 fn push_hex(s: &mut String, byte: u8) {
     use std::fmt::Write;
     write!(s, "{byte:02X} ").unwrap();
 }
+/// bytes on a row of the hex view `width` columns wide, the space after the last one left out
+pub fn hex_row_bytes(width: u16) -> usize {
+    ((width as usize + 1) / 3).max(1)
+}
+/// number of rows a line takes when wrapped to `width` columns
+pub fn wrapped_rows(line: &str, width: u16) -> usize {
+    rows_for(line.chars().count(), width)
+}
+/// number of rows `len` characters take when wrapped to `width` columns
+pub fn rows_for(len: usize, width: u16) -> usize {
+    if width == 0 || len == 0 {
+        1
+    } else {
+        len.div_ceil(width as usize)
+    }
+}
+
+/// a history entry on one line, as hex or text with its line ending left out
+fn format_entry(entry: &[u8], hex: bool) -> String {
+    let mut s = String::new();
+    if hex {
+        for &b in entry {
+            push_hex(&mut s, b);
+        }
+        return s;
+    }
+    let entry = entry.strip_suffix(b"\n").unwrap_or(entry);
+    let entry = entry.strip_suffix(b"\r").unwrap_or(entry);
+    for &b in entry {
+        match b {
+            b'\n' => s.push('⏎'),
+            b'\r' => s.push('␍'),
+            _ => push_ascii(&mut s, b),
+        }
+    }
+    s
+}
 fn push_ascii(s: &mut String, byte: u8) {
     if byte == b'\t' {
         s.push_str("    ");
+    } else if byte == BEL {
+        // the terminal would ring it, whatever `--bell` says
+        s.push('␇');
     } else {
         s.push(byte.into());
     }
@@ -212,41 +1263,3 @@ impl ByteBuffer {
         }
     }
 }
-
-/// Helper struct to add cursor only when rendering
-pub struct WithCursor<'a> {
-    s: &'a mut String,
-}
-
-impl<'a> WithCursor<'a> {
-    pub fn new(s: &'a mut String, cursor: char) -> Self {
-        s.push(cursor);
-        Self { s }
-    }
-}
-impl<'a> Drop for WithCursor<'a> {
-    fn drop(&mut self) {
-        self.s.pop();
-    }
-}
-
-impl<'a> AsRef<String> for WithCursor<'a> {
-    fn as_ref(&self) -> &String {
-        self.s
-    }
-}
-impl<'a> AsRef<str> for WithCursor<'a> {
-    fn as_ref(&self) -> &str {
-        self.s
-    }
-}
-impl<'a> AsMut<String> for WithCursor<'a> {
-    fn as_mut(&mut self) -> &mut String {
-        self.s
-    }
-}
-impl<'a> AsMut<str> for WithCursor<'a> {
-    fn as_mut(&mut self) -> &mut str {
-        self.s
-    }
-}
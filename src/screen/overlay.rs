@@ -0,0 +1,898 @@
+//! Modal overlays drawn over the panes, like confirmations, inputs and pickers
+//!
+//! Overlays are kept in a stack: the top one gets every key and is drawn last.
+//! Each entry carries a tag telling the app what a submitted reply is for.
+use crate::template::{Check, Endian};
+use crate::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+pub trait Overlay {
+    fn title(&self) -> &str;
+    /// width and height in percent of the screen
+    fn size(&self) -> (u16, u16) {
+        (30, 20)
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome;
+    /// draws the contents inside the border
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme);
+    /// shows why a submitted reply was rejected, keeping the overlay open
+    fn set_error(&mut self, _error: String) {}
+}
+
+/// What handling a key did to an overlay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Continue,
+    /// closed without a reply
+    Cancel,
+    Submit(Reply),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Yes,
+    Text(String),
+    /// index of the picked item
+    Choice(usize),
+    /// key of an action offered by the overlay
+    Action(char),
+    /// values of the fields of a form, in order
+    Fields(Vec<String>),
+    /// bytes edited in a hex editor, and what's sent: them and their checksum
+    Frame {
+        bytes: Vec<u8>,
+        check: Option<(Check, Endian)>,
+        frame: Vec<u8>,
+    },
+}
+
+pub struct OverlayStack<T> {
+    entries: Vec<(T, Box<dyn Overlay>)>,
+}
+
+impl<T: Copy> OverlayStack<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+    pub fn push(&mut self, tag: T, overlay: impl Overlay + 'static) {
+        self.entries.push((tag, Box::new(overlay)));
+    }
+    pub fn push_boxed(&mut self, tag: T, overlay: Box<dyn Overlay>) {
+        self.entries.push((tag, overlay));
+    }
+    pub fn pop(&mut self) -> Option<(T, Box<dyn Overlay>)> {
+        self.entries.pop()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn top_mut(&mut self) -> Option<&mut (dyn Overlay + 'static)> {
+        self.entries.last_mut().map(|(_, o)| o.as_mut())
+    }
+    /// whether an overlay with `tag` is open anywhere in the stack
+    pub fn contains(&self, tag: T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.entries.iter().any(|(t, _)| *t == tag)
+    }
+    /// overlays from the bottom of the stack to the top
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Overlay> {
+        self.entries.iter().map(|(_, o)| o.as_ref())
+    }
+}
+
+impl<T: Copy> Default for OverlayStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Yes/no question, `y` submits
+pub struct Confirm {
+    title: String,
+    message: String,
+}
+
+impl Confirm {
+    pub fn new(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Overlay for Confirm {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        match key.code {
+            KeyCode::Char('y') => Outcome::Submit(Reply::Yes),
+            KeyCode::Esc | KeyCode::Char('n' | 'q') => Outcome::Cancel,
+            _ => Outcome::Continue,
+        }
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, _theme: &Theme) {
+        Paragraph::new(format!("{} (y/n)", self.message))
+            .wrap(Wrap { trim: true })
+            .render(area, buf);
+    }
+}
+
+/// Single line text input with an optional hint above it
+pub struct Input {
+    title: String,
+    label: String,
+    hint: Option<String>,
+    value: String,
+    /// characters that can be typed
+    accept: fn(char) -> bool,
+    /// shows `*` for every character, for passwords
+    masked: bool,
+    error: Option<String>,
+    size: (u16, u16),
+}
+
+impl Input {
+    pub fn new(title: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            label: label.into(),
+            hint: None,
+            value: String::new(),
+            accept: |_| true,
+            masked: false,
+            error: None,
+            size: (30, 20),
+        }
+    }
+    pub fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+    pub fn value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self
+    }
+    pub fn accept(mut self, accept: fn(char) -> bool) -> Self {
+        self.accept = accept;
+        self
+    }
+    pub fn size(mut self, width: u16, height: u16) -> Self {
+        self.size = (width, height);
+        self
+    }
+    pub fn masked(mut self) -> Self {
+        self.masked = true;
+        self
+    }
+}
+
+impl Overlay for Input {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn size(&self) -> (u16, u16) {
+        self.size
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        match key.code {
+            KeyCode::Esc => return Outcome::Cancel,
+            KeyCode::Enter => return Outcome::Submit(Reply::Text(self.value.clone())),
+            KeyCode::Char(c) if (self.accept)(c) => self.value.push(c),
+            KeyCode::Backspace => {
+                self.value.pop();
+            }
+            _ => (),
+        }
+        self.error = None;
+        Outcome::Continue
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        use ratatui::symbols::block::SEVEN_EIGHTHS as cursor;
+        let mut lines = Vec::new();
+        if let Some(hint) = &self.hint {
+            lines.push(Spans::from(Span::styled(
+                hint.as_str(),
+                Style::default().fg(theme.hint),
+            )));
+        }
+        let value = match self.masked {
+            true => "*".repeat(self.value.chars().count()),
+            false => self.value.clone(),
+        };
+        lines.push(Spans::from(vec![
+            Span::raw(format!("{}: ", self.label)),
+            Span::raw(value),
+            Span::styled(cursor, Style::default().add_modifier(Modifier::SLOW_BLINK)),
+        ]));
+        if let Some(e) = &self.error {
+            lines.push(Spans::from(Span::styled(
+                format!("error: {e}"),
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .render(area, buf);
+    }
+    fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+}
+
+/// List to pick one item from with `j`/`k` or the arrow keys, with optional
+/// single key actions
+pub struct Picker {
+    title: String,
+    items: Vec<String>,
+    selected: usize,
+    actions: Vec<(char, &'static str)>,
+}
+
+impl Picker {
+    pub fn new(title: impl Into<String>, items: Vec<String>) -> Self {
+        Self {
+            title: title.into(),
+            items,
+            selected: 0,
+            actions: Vec::new(),
+        }
+    }
+    /// offers an action submitted as `Reply::Action(key)`
+    pub fn action(mut self, key: char, name: &'static str) -> Self {
+        self.actions.push((key, name));
+        self
+    }
+}
+
+impl Overlay for Picker {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn size(&self) -> (u16, u16) {
+        (40, 40)
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Outcome::Cancel,
+            KeyCode::Enter if !self.items.is_empty() => {
+                return Outcome::Submit(Reply::Choice(self.selected))
+            }
+            KeyCode::Char(c) if self.actions.iter().any(|(k, _)| *k == c) => {
+                return Outcome::Submit(Reply::Action(c))
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.items.len().saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            _ => (),
+        }
+        Outcome::Continue
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let mut area = area;
+        if !self.actions.is_empty() {
+            let hint = self
+                .actions
+                .iter()
+                .map(|(key, name)| format!("{key}: {name}"))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            let footer = Rect {
+                y: area.bottom().saturating_sub(1),
+                height: area.height.min(1),
+                ..area
+            };
+            Paragraph::new(Span::styled(hint, Style::default().fg(theme.hint))).render(footer, buf);
+            area.height = area.height.saturating_sub(1);
+        }
+        if self.items.is_empty() {
+            Paragraph::new(Span::styled(
+                "nothing to pick",
+                Style::default().fg(theme.hint),
+            ))
+            .render(area, buf);
+            return;
+        }
+        // keep the selected item in view
+        let skip = (self.selected + 1).saturating_sub(area.height as usize);
+        let lines = self
+            .items
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .map(|(i, item)| {
+                if i == self.selected {
+                    Spans::from(Span::styled(
+                        format!("> {item}"),
+                        Style::default().add_modifier(Modifier::REVERSED),
+                    ))
+                } else {
+                    Spans::from(format!("  {item}"))
+                }
+            })
+            .collect::<Vec<_>>();
+        Paragraph::new(lines).render(area, buf);
+    }
+}
+
+/// Finds the choices of a field again
+pub type Refresh = fn() -> Result<Vec<String>, String>;
+
+/// One value of a `Form`, picked from choices or typed in
+pub struct Field {
+    label: String,
+    value: String,
+    choices: Vec<String>,
+    /// characters that can be typed, `None` for fields that only have choices
+    accept: Option<fn(char) -> bool>,
+    /// finds the choices again, for ones that change like the ports there are
+    refresh: Option<Refresh>,
+}
+
+impl Field {
+    pub fn new(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            value: value.into(),
+            choices: Vec::new(),
+            accept: None,
+            refresh: None,
+        }
+    }
+    pub fn choices(mut self, choices: Vec<String>) -> Self {
+        self.choices = choices;
+        self
+    }
+    pub fn accept(mut self, accept: fn(char) -> bool) -> Self {
+        self.accept = Some(accept);
+        self
+    }
+    pub fn refresh(mut self, refresh: Refresh) -> Self {
+        self.refresh = Some(refresh);
+        self
+    }
+    /// moves to the next or previous choice, or the first one when the value
+    /// was typed in
+    fn cycle(&mut self, delta: isize) {
+        if self.choices.is_empty() {
+            return;
+        }
+        let n = self.choices.len() as isize;
+        let i = match self.choices.iter().position(|c| *c == self.value) {
+            Some(i) => (i as isize + delta).rem_euclid(n),
+            None => 0,
+        };
+        self.value = self.choices[i as usize].clone();
+    }
+}
+
+/// Several fields edited together, `TAB`/`↑`/`↓` move between them, `←`/`→`
+/// go through the choices, `F5` finds the choices of refreshable fields again
+/// and `ENTER` submits them all
+pub struct Form {
+    title: String,
+    fields: Vec<Field>,
+    selected: usize,
+    error: Option<String>,
+}
+
+impl Form {
+    pub fn new(title: impl Into<String>, fields: Vec<Field>) -> Self {
+        Self {
+            title: title.into(),
+            fields,
+            selected: 0,
+            error: None,
+        }
+    }
+}
+
+impl Overlay for Form {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn size(&self) -> (u16, u16) {
+        (50, 40)
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let last = self.fields.len().saturating_sub(1);
+        let field = &mut self.fields[self.selected];
+        match key.code {
+            KeyCode::Esc => return Outcome::Cancel,
+            KeyCode::Enter => {
+                return Outcome::Submit(Reply::Fields(
+                    self.fields.iter().map(|f| f.value.clone()).collect(),
+                ))
+            }
+            KeyCode::Tab | KeyCode::Down => self.selected = (self.selected + 1).min(last),
+            KeyCode::BackTab | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Right => field.cycle(1),
+            KeyCode::Left => field.cycle(-1),
+            KeyCode::F(5) => {
+                self.error = None;
+                for field in &mut self.fields {
+                    let Some(refresh) = field.refresh else {
+                        continue;
+                    };
+                    match refresh() {
+                        // the value stays a choice even if it's gone
+                        Ok(mut choices) => {
+                            if !field.value.is_empty() && !choices.contains(&field.value) {
+                                choices.insert(0, field.value.clone());
+                            }
+                            field.choices = choices;
+                        }
+                        Err(e) => self.error = Some(e),
+                    }
+                }
+                return Outcome::Continue;
+            }
+            KeyCode::Char(c) if field.accept.is_some_and(|accept| accept(c)) => field.value.push(c),
+            KeyCode::Backspace if field.accept.is_some() => {
+                field.value.pop();
+            }
+            _ => (),
+        }
+        self.error = None;
+        Outcome::Continue
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let width = self.fields.iter().map(|f| f.label.len()).max().unwrap_or(0);
+        let hint = match self.fields.iter().any(|f| f.refresh.is_some()) {
+            true => "←/→: change | TAB: next | F5: refresh | ENTER: apply",
+            false => "←/→: change | TAB: next | ENTER: apply",
+        };
+        let mut lines = vec![Spans::from(Span::styled(
+            hint,
+            Style::default().fg(theme.hint),
+        ))];
+        for (i, field) in self.fields.iter().enumerate() {
+            let text = format!("{:>width$}: {}", field.label, field.value);
+            lines.push(Spans::from(if i == self.selected {
+                Span::styled(text, Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                Span::raw(text)
+            }));
+        }
+        if let Some(e) = &self.error {
+            lines.push(Spans::from(Span::styled(
+                format!("error: {e}"),
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .render(area, buf);
+    }
+    fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+}
+
+/// Read-only scrolling text, opened at the end, with optional single key actions
+pub struct Pager {
+    title: String,
+    lines: Vec<String>,
+    /// lines scrolled back from the end
+    back: usize,
+    actions: Vec<(char, &'static str)>,
+}
+
+impl Pager {
+    pub fn new(title: impl Into<String>, lines: Vec<String>) -> Self {
+        Self {
+            title: title.into(),
+            lines,
+            back: 0,
+            actions: Vec::new(),
+        }
+    }
+    /// offers an action submitted as `Reply::Action(key)`
+    pub fn action(mut self, key: char, name: &'static str) -> Self {
+        self.actions.push((key, name));
+        self
+    }
+}
+
+impl Overlay for Pager {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn size(&self) -> (u16, u16) {
+        (80, 60)
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let max = self.lines.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Outcome::Cancel,
+            KeyCode::Char(c) if self.actions.iter().any(|(k, _)| *k == c) => {
+                return Outcome::Submit(Reply::Action(c))
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.back = (self.back + 1).min(max),
+            KeyCode::Char('j') | KeyCode::Down => self.back = self.back.saturating_sub(1),
+            KeyCode::Char('g') | KeyCode::Home => self.back = max,
+            KeyCode::Char('G') | KeyCode::End => self.back = 0,
+            _ => (),
+        }
+        Outcome::Continue
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let hint = self
+            .actions
+            .iter()
+            .map(|(key, name)| format!("{key}: {name}"))
+            .chain([String::from("q: close")])
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let height = area.height.saturating_sub(1) as usize;
+        let end = self.lines.len() - self.back.min(self.lines.len());
+        let mut lines = self.lines[end.saturating_sub(height)..end]
+            .iter()
+            .map(|l| Spans::from(l.as_str()))
+            .collect::<Vec<_>>();
+        if self.lines.is_empty() {
+            lines.push(Spans::from(Span::styled(
+                "nothing here",
+                Style::default().fg(theme.hint),
+            )));
+        }
+        let body = Rect {
+            height: height as u16,
+            ..area
+        };
+        Paragraph::new(lines).render(body, buf);
+        let footer = Rect {
+            y: area.bottom().saturating_sub(1),
+            height: area.height.min(1),
+            ..area
+        };
+        Paragraph::new(Span::styled(hint, Style::default().fg(theme.hint))).render(footer, buf);
+    }
+}
+
+/// Several lines of text edited in place, like notes: `ENTER` breaks the line
+/// and the arrows move the cursor. `ESC` closes it, submitting the text
+pub struct TextArea {
+    title: String,
+    lines: Vec<String>,
+    /// line and character of the cursor
+    cursor: (usize, usize),
+}
+
+impl TextArea {
+    pub fn new(title: impl Into<String>, text: &str) -> Self {
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let last = lines.len() - 1;
+        let cursor = (last, lines[last].chars().count());
+        Self {
+            title: title.into(),
+            lines,
+            cursor,
+        }
+    }
+    /// byte offset of the cursor in its line
+    fn offset(&self) -> usize {
+        let (row, col) = self.cursor;
+        let line = &self.lines[row];
+        line.char_indices().nth(col).map_or(line.len(), |(i, _)| i)
+    }
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+}
+
+impl Overlay for TextArea {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn size(&self) -> (u16, u16) {
+        (70, 60)
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let (row, col) = self.cursor;
+        let last = self.lines.len() - 1;
+        match key.code {
+            KeyCode::Esc => {
+                let text = self.lines.join("\n");
+                return Outcome::Submit(Reply::Text(text.trim_end().to_owned()));
+            }
+            KeyCode::Char(c) => {
+                let i = self.offset();
+                self.lines[row].insert(i, c);
+                self.cursor.1 += 1;
+            }
+            KeyCode::Tab => {
+                let i = self.offset();
+                self.lines[row].insert_str(i, "    ");
+                self.cursor.1 += 4;
+            }
+            KeyCode::Enter => {
+                let i = self.offset();
+                let rest = self.lines[row].split_off(i);
+                self.lines.insert(row + 1, rest);
+                self.cursor = (row + 1, 0);
+            }
+            KeyCode::Backspace if col > 0 => {
+                self.cursor.1 -= 1;
+                let i = self.offset();
+                self.lines[row].remove(i);
+            }
+            KeyCode::Backspace if row > 0 => {
+                let line = self.lines.remove(row);
+                self.cursor = (row - 1, self.line_len(row - 1));
+                self.lines[row - 1].push_str(&line);
+            }
+            KeyCode::Delete if col < self.line_len(row) => {
+                let i = self.offset();
+                self.lines[row].remove(i);
+            }
+            KeyCode::Delete if row < last => {
+                let line = self.lines.remove(row + 1);
+                self.lines[row].push_str(&line);
+            }
+            KeyCode::Left if col > 0 => self.cursor.1 -= 1,
+            KeyCode::Left if row > 0 => self.cursor = (row - 1, self.line_len(row - 1)),
+            KeyCode::Right if col < self.line_len(row) => self.cursor.1 += 1,
+            KeyCode::Right if row < last => self.cursor = (row + 1, 0),
+            KeyCode::Up if row > 0 => self.cursor = (row - 1, col.min(self.line_len(row - 1))),
+            KeyCode::Down if row < last => self.cursor = (row + 1, col.min(self.line_len(row + 1))),
+            KeyCode::Home => self.cursor.1 = 0,
+            KeyCode::End => self.cursor.1 = self.line_len(row),
+            _ => (),
+        }
+        Outcome::Continue
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let height = area.height.saturating_sub(1) as usize;
+        let (row, col) = self.cursor;
+        // the cursor stays on the last visible line when it's further down
+        let top = (row + 1).saturating_sub(height);
+        let cursor = Style::default().add_modifier(Modifier::REVERSED);
+        let lines = self.lines[top..(top + height).min(self.lines.len())]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                if top + i != row {
+                    return Spans::from(line.as_str());
+                }
+                let i = self.offset();
+                let (before, after) = line.split_at(i);
+                let mut chars = after.chars();
+                let under = chars.next().map_or(String::from(" "), String::from);
+                Spans::from(vec![
+                    Span::raw(before),
+                    Span::styled(under, cursor),
+                    Span::raw(chars.as_str()),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let body = Rect {
+            height: height as u16,
+            ..area
+        };
+        Paragraph::new(lines).render(body, buf);
+        let footer = Rect {
+            y: area.bottom().saturating_sub(1),
+            height: area.height.min(1),
+            ..area
+        };
+        let hint = format!("line {}, col {} | ESC: close", row + 1, col + 1);
+        Paragraph::new(Span::styled(hint, Style::default().fg(theme.hint))).render(footer, buf);
+    }
+}
+
+/// Bytes edited a nibble at a time: hex digits overwrite the nibble under the
+/// cursor, or insert a byte in insert mode, `←`/`→` move it and `DEL`/
+/// `BACKSPACE` delete a byte. `TAB` goes through the checksums appended to
+/// the bytes, recomputed as they change
+pub struct HexEditor {
+    title: String,
+    bytes: Vec<u8>,
+    /// nibble the cursor is on, `bytes.len() * 2` past the end
+    cursor: usize,
+    insert: bool,
+    check: Option<(Check, Endian)>,
+    /// bytes that were a checksum when one was picked, given back when it's not
+    stripped: Vec<u8>,
+}
+
+impl HexEditor {
+    const PER_ROW: usize = 16;
+
+    pub fn new(title: impl Into<String>, bytes: Vec<u8>) -> Self {
+        Self {
+            title: title.into(),
+            bytes,
+            cursor: 0,
+            insert: false,
+            check: None,
+            stripped: Vec::new(),
+        }
+    }
+    /// appends `check`, taking the last bytes as the old checksum if they are one
+    pub fn check(mut self, check: Option<(Check, Endian)>) -> Self {
+        self.set_check(check);
+        self
+    }
+    fn set_check(&mut self, check: Option<(Check, Endian)>) {
+        self.bytes.append(&mut self.stripped);
+        self.check = check;
+        let Some((check, endian)) = check else {
+            return;
+        };
+        let width = check.compute(&[]).1;
+        let Some(body) = self.bytes.len().checked_sub(width).filter(|&n| n > 0) else {
+            return;
+        };
+        let (data, sum) = self.bytes.split_at(body);
+        // a captured frame gives away its byte order
+        let found = [endian, endian.flip()]
+            .into_iter()
+            .find(|&e| check.bytes(data, e) == sum);
+        if let Some(endian) = found {
+            self.stripped = self.bytes.split_off(body);
+            self.check = Some((check, endian));
+        }
+        self.cursor = self.cursor.min(self.bytes.len() * 2);
+    }
+    /// the next checksum after the current one, none after the last
+    fn next_check(&self) -> Option<(Check, Endian)> {
+        let endian = self.check.map_or(Endian::default(), |(_, e)| e);
+        let next = match self.check {
+            None => 0,
+            Some((check, _)) => Check::ALL.iter().position(|(_, c)| *c == check)? + 1,
+        };
+        Check::ALL.get(next).map(|&(_, c)| (c, endian))
+    }
+    /// the bytes followed by their checksum
+    fn frame(&self) -> Vec<u8> {
+        let mut frame = self.bytes.clone();
+        if let Some((check, endian)) = self.check {
+            frame.extend(check.bytes(&self.bytes, endian));
+        }
+        frame
+    }
+}
+
+impl Overlay for HexEditor {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn size(&self) -> (u16, u16) {
+        (70, 50)
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let end = self.bytes.len() * 2;
+        match key.code {
+            KeyCode::Esc => return Outcome::Cancel,
+            KeyCode::Enter => {
+                return Outcome::Submit(Reply::Frame {
+                    bytes: self.bytes.clone(),
+                    check: self.check,
+                    frame: self.frame(),
+                })
+            }
+            KeyCode::Char(c) if c.is_ascii_hexdigit() => {
+                let nibble = c.to_digit(16).unwrap_or(0) as u8;
+                if self.cursor == end || (self.insert && self.cursor.is_multiple_of(2)) {
+                    self.bytes.insert(self.cursor / 2, 0);
+                }
+                let byte = &mut self.bytes[self.cursor / 2];
+                *byte = match self.cursor % 2 {
+                    0 => (*byte & 0x0F) | nibble << 4,
+                    _ => (*byte & 0xF0) | nibble,
+                };
+                self.cursor += 1;
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(end),
+            KeyCode::Up => self.cursor = self.cursor.saturating_sub(Self::PER_ROW * 2),
+            KeyCode::Down => self.cursor = (self.cursor + Self::PER_ROW * 2).min(end),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = end,
+            KeyCode::Insert => self.insert = !self.insert,
+            KeyCode::Delete if self.cursor < end => {
+                self.bytes.remove(self.cursor / 2);
+                self.cursor -= self.cursor % 2;
+            }
+            // the byte before the cursor, or the one it's in past its high nibble
+            KeyCode::Backspace if self.cursor > 0 => {
+                let i = (self.cursor - 1) / 2;
+                self.bytes.remove(i);
+                self.cursor = i * 2;
+            }
+            KeyCode::Tab => self.set_check(self.next_check()),
+            KeyCode::BackTab => {
+                if let Some((check, endian)) = self.check {
+                    self.check = Some((check, endian.flip()));
+                }
+            }
+            _ => (),
+        }
+        Outcome::Continue
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let hint = Style::default().fg(theme.hint);
+        let mut lines = vec![
+            Spans::from(Span::styled(
+                "0-f: edit | ←/→: move | INS: insert/overwrite | DEL: delete | ENTER: send",
+                hint,
+            )),
+            Spans::from(Span::styled(
+                "TAB: next checksum | Shift+TAB: byte order",
+                hint,
+            )),
+        ];
+        let cursor = Style::default().add_modifier(Modifier::REVERSED);
+        let rows = (self.bytes.len() / Self::PER_ROW) + 1;
+        for row in 0..rows {
+            let start = row * Self::PER_ROW;
+            let mut spans = vec![Span::styled(format!("{start:04X}  "), hint)];
+            for i in start..start + Self::PER_ROW {
+                let hex = match self.bytes.get(i) {
+                    Some(b) => format!("{b:02X}"),
+                    // where typing appends
+                    None if i == self.bytes.len() => String::from("__"),
+                    None => String::from("  "),
+                };
+                for (n, c) in hex.chars().enumerate() {
+                    let text = c.to_string();
+                    spans.push(match i * 2 + n == self.cursor {
+                        true => Span::styled(text, cursor),
+                        false => Span::raw(text),
+                    });
+                }
+                spans.push(Span::raw(" "));
+            }
+            spans.push(Span::raw(" "));
+            let end = (start + Self::PER_ROW).min(self.bytes.len());
+            for (i, &b) in self.bytes[start.min(end)..end].iter().enumerate() {
+                let c = match b {
+                    0x20..=0x7E => b as char,
+                    _ => '.',
+                };
+                spans.push(match (start + i) == self.cursor / 2 {
+                    true => Span::styled(c.to_string(), cursor),
+                    false => Span::raw(c.to_string()),
+                });
+            }
+            lines.push(Spans::from(spans));
+        }
+        let mut status = format!(
+            "{} bytes, {}",
+            self.bytes.len(),
+            if self.insert { "insert" } else { "overwrite" }
+        );
+        if let Some((check, endian)) = self.check {
+            let sum = check.bytes(&self.bytes, endian);
+            status += &format!(
+                ", {} {} {}",
+                check.name(),
+                endian.name(),
+                crate::decode::hex_string(&sum)
+            );
+        }
+        lines.push(Spans::from(Span::styled(status, hint)));
+        Paragraph::new(lines).render(area, buf);
+    }
+}
@@ -0,0 +1,70 @@
+//! `/` and `?` search through RX, `less` style: the matching line is scrolled to the top
+use regex::Regex;
+
+pub struct Search {
+    pub regex: Regex,
+    /// searching towards the start of the buffer, as started with `?`
+    pub backward: bool,
+    /// line of the current match
+    pub line: Option<usize>,
+    /// the last search found nothing
+    pub not_found: bool,
+}
+
+impl Search {
+    pub fn new(regex: Regex, backward: bool) -> Self {
+        Self {
+            regex,
+            backward,
+            line: None,
+            not_found: false,
+        }
+    }
+    /// finds the `count`th next matching line in the search direction, or the opposite one if
+    /// `reverse`, wrapping around the buffer. Without a current match the search starts at the
+    /// first visible line going forward, or the last one going backward. The buffer is walked
+    /// at most once whatever the count.
+    pub fn next(
+        &mut self,
+        lines: &[&str],
+        visible: (usize, usize),
+        reverse: bool,
+        count: usize,
+    ) -> Option<usize> {
+        let n = lines.len();
+        let backward = self.backward != reverse;
+        let found = if n == 0 {
+            None
+        } else {
+            // the line after `from` in the search direction is tried first
+            let from = match (self.line, backward) {
+                (Some(line), _) => line.min(n - 1),
+                (None, false) => (visible.0 + n - 1) % n,
+                (None, true) => (visible.1 + 1) % n,
+            };
+            let count = count.max(1);
+            let matches: Vec<usize> = (1..=n)
+                .map(|i| {
+                    if backward {
+                        (from + n - i) % n
+                    } else {
+                        (from + i) % n
+                    }
+                })
+                .filter(|&line| self.regex.is_match(lines[line]))
+                .take(count)
+                .collect();
+            // fewer matches than the count means the whole buffer was walked,
+            // going around it again lands on them in the same order
+            match matches.len() {
+                0 => None,
+                m => Some(matches[(count - 1) % m]),
+            }
+        };
+        self.not_found = found.is_none();
+        if found.is_some() {
+            self.line = found;
+        }
+        found
+    }
+}
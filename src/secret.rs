@@ -0,0 +1,147 @@
+//! Secrets like login passwords: kept in the OS keyring or asked for when
+//! they're needed, and masked in everything written to files, so captures can
+//! be shared. Redaction rules mask whatever else they match, like serial
+//! numbers or keys the device prints
+use regex::bytes::Regex;
+use std::borrow::Cow;
+
+/// service the keyring entries are filed under
+const SERVICE: &str = "tuicom";
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| e.to_string())
+}
+
+/// the secret stored in the keyring as `name`, if there is one
+pub fn get(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn set(name: &str, secret: &str) -> Result<(), String> {
+    entry(name)?.set_password(secret).map_err(|e| e.to_string())
+}
+
+/// removes `name` from the keyring, like a password that turned out wrong
+pub fn forget(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Secrets sent this run and redaction rules, masked with a `*` per byte
+/// wherever RX or TX is written to a file. The length stays, so offsets into
+/// the data still hold
+#[derive(Debug, Default)]
+pub struct Secrets {
+    secrets: Vec<Vec<u8>>,
+    /// matched a line at a time, masking the capture groups if there are any
+    /// or else the whole match
+    rules: Vec<Regex>,
+}
+
+impl Secrets {
+    /// longest line held back from a log for the rules, a line that goes on
+    /// longer is written as it comes
+    const MAX_LINE: usize = 4096;
+
+    pub fn add(&mut self, secret: &str) {
+        let secret = secret.as_bytes();
+        if !secret.is_empty() && !self.secrets.iter().any(|s| s == secret) {
+            self.secrets.push(secret.to_vec());
+        }
+    }
+    pub fn rule(&mut self, rule: Regex) {
+        self.rules.push(rule);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty() && self.rules.is_empty()
+    }
+    pub fn redact<'a>(&self, bytes: &'a [u8]) -> Cow<'a, [u8]> {
+        let mut out = Cow::Borrowed(bytes);
+        for secret in &self.secrets {
+            let mut from = 0;
+            while let Some(i) = find(&out[from..], secret) {
+                let at = from + i;
+                out.to_mut()[at..at + secret.len()].fill(b'*');
+                from = at + secret.len();
+            }
+        }
+        if self.rules.is_empty() {
+            return out;
+        }
+        let mut masks = Vec::new();
+        let mut start = 0;
+        for line in bytes.split_inclusive(|&b| b == b'\n') {
+            let end = line.len() - line.ends_with(b"\n") as usize;
+            let end = end - line[..end].ends_with(b"\r") as usize;
+            for rule in &self.rules {
+                for caps in rule.captures_iter(&line[..end]) {
+                    let groups: Vec<_> = match caps.len() {
+                        1 => vec![caps.get(0)],
+                        _ => caps.iter().skip(1).collect(),
+                    };
+                    let groups = groups.into_iter().flatten();
+                    masks.extend(groups.map(|m| start + m.start()..start + m.end()));
+                }
+            }
+            start += line.len();
+        }
+        for mask in masks {
+            out.to_mut()[mask].fill(b'*');
+        }
+        out
+    }
+    /// length of the longest end of `bytes` that could still turn out to be
+    /// a secret or, with rules, the line they haven't seen the end of
+    fn partial(&self, bytes: &[u8]) -> usize {
+        let line = match self.rules.is_empty() {
+            true => 0,
+            false => match bytes.iter().rposition(|&b| b == b'\n') {
+                Some(i) => bytes.len() - i - 1,
+                None => bytes.len(),
+            },
+        };
+        let line = if line > Self::MAX_LINE { 0 } else { line };
+        self.secrets
+            .iter()
+            .flat_map(|s| (1..s.len().min(bytes.len() + 1)).filter(|&n| bytes.ends_with(&s[..n])))
+            .max()
+            .unwrap_or(0)
+            .max(line)
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Masks secrets in data arriving a read at a time, holding back the end of a
+/// read while it could be the start of a secret
+#[derive(Debug, Default)]
+pub struct Redacting {
+    held: Vec<u8>,
+}
+
+impl Redacting {
+    /// `bytes` masked, with what was held back before them
+    pub fn push(&mut self, secrets: &Secrets, bytes: &[u8]) -> Vec<u8> {
+        if secrets.is_empty() {
+            return [std::mem::take(&mut self.held).as_slice(), bytes].concat();
+        }
+        self.held.extend_from_slice(bytes);
+        let keep = secrets.partial(&self.held);
+        let held = self.held.split_off(self.held.len() - keep);
+        secrets
+            .redact(&std::mem::replace(&mut self.held, held))
+            .into_owned()
+    }
+    /// what's held back, masked as far as it goes, for when the log is closed
+    pub fn finish(&mut self, secrets: &Secrets) -> Vec<u8> {
+        secrets.redact(&std::mem::take(&mut self.held)).into_owned()
+    }
+}
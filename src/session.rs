@@ -0,0 +1,84 @@
+//! Session files: what was received and sent, with the settings and the view,
+//! saved to a `.tuicom` file so an investigation can be reopened as it was
+use crate::profile::Layout;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Extension given to session files named without one
+pub const EXTENSION: &str = "tuicom";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// port the session was recorded on
+    pub port: String,
+    pub baud_rate: u32,
+    pub data_bits: String,
+    pub parity: String,
+    pub stop_bits: String,
+    pub flow_control: String,
+    pub layout: Layout,
+    /// everything received, as hex
+    pub rx: String,
+    /// offsets in `rx` where lines start and when they arrived, in
+    /// milliseconds since 1970
+    pub rx_times: Vec<(usize, u64)>,
+    /// the TX pane, as hex
+    pub tx: String,
+    /// what was sent, oldest first, as hex
+    pub history: Vec<String>,
+    /// what the user sent, the lines line mode recalls, as hex
+    pub sent: Vec<String>,
+    pub registers: BTreeMap<String, String>,
+    /// written with `:notes`
+    pub notes: String,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let s = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&s).map_err(|e| e.to_string())
+    }
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let s = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, s).map_err(|e| e.to_string())
+    }
+}
+
+/// `path`, with the session extension if it has none
+pub fn file_name(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    match path.extension() {
+        Some(_) => path,
+        None => path.with_extension(EXTENSION),
+    }
+}
+
+/// bytes as hex digits without spaces, as they are stored
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 * bytes.len());
+    for b in bytes {
+        write!(hex, "{b:02X}").unwrap();
+    }
+    hex
+}
+
+/// milliseconds since 1970 at `time`
+pub fn to_millis(time: Instant) -> u64 {
+    SystemTime::now()
+        .checked_sub(time.elapsed())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// `millis` since 1970 as an instant, now if it's too far back to be one
+pub fn from_millis(millis: u64) -> Instant {
+    let age = SystemTime::now()
+        .duration_since(UNIX_EPOCH + Duration::from_millis(millis))
+        .unwrap_or_default();
+    Instant::now().checked_sub(age).unwrap_or_else(Instant::now)
+}
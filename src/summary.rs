@@ -0,0 +1,45 @@
+//! Report of a session written on exit, an artifact for automated test runs
+use crate::app::App;
+use crate::watch::ago;
+
+/// the session as `key: value` lines
+pub fn report(app: &App) -> String {
+    let secs = app.started.elapsed().as_secs();
+    let mut lines = vec![
+        format!("port: {}", app.link.port),
+        format!(
+            "duration: {:02}:{:02}:{:02}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        ),
+        format!("bytes sent: {}", app.tx.bytes_sent),
+        format!("bytes received: {}", app.rx.bytes_received),
+        format!("disconnects: {}", app.link.disconnects),
+        format!("reconnects: {}", app.link.reconnects),
+    ];
+    let totals = app.errors.totals();
+    let errors = totals.values().sum::<usize>();
+    if errors == 0 {
+        lines.push(String::from("errors: 0"));
+    } else {
+        let by_source = totals
+            .iter()
+            .map(|(source, n)| format!("{source} {n}"))
+            .collect::<Vec<_>>();
+        lines.push(format!("errors: {errors} ({})", by_source.join(", ")));
+    }
+    for watch in &app.watches.watches {
+        let mut line = format!("watch {}: {} hits", watch.label, watch.hits);
+        if let Some(last) = watch.last_seen {
+            line.push_str(&format!(", last {} before exit", ago(last.elapsed())));
+        }
+        lines.push(line);
+    }
+    for (kind, path) in &app.files {
+        lines.push(format!("{kind}: {}", path.display()));
+    }
+    let mut report = lines.join("\n");
+    report.push('\n');
+    report
+}
@@ -0,0 +1,141 @@
+//! Finding an unknown baud rate by eye: every candidate is held for a moment
+//! while what arrives at it is previewed and scored by how much of it is
+//! printable, and the one that looks right is kept
+use std::time::{Duration, Instant};
+
+/// A candidate rate and what arrived the last time the port was set to it
+pub struct Rate {
+    pub baud: u32,
+    /// the latest bytes received at this rate
+    sample: Vec<u8>,
+}
+
+impl Rate {
+    /// share of the sample that's printable text, `None` while nothing arrived
+    pub fn score(&self) -> Option<f32> {
+        if self.sample.is_empty() {
+            return None;
+        }
+        let printable = self
+            .sample
+            .iter()
+            .filter(|&&b| matches!(b, 0x20..=0x7E | b'\t' | b'\r' | b'\n'))
+            .count();
+        Some(printable as f32 / self.sample.len() as f32)
+    }
+    /// the sample as text on one line, anything unprintable shown as `·`
+    pub fn preview(&self) -> String {
+        self.sample
+            .iter()
+            .map(|&b| match b {
+                0x20..=0x7E => b as char,
+                _ => '·',
+            })
+            .collect()
+    }
+}
+
+pub struct Sweep {
+    pub rates: Vec<Rate>,
+    /// index of the rate the port is set to
+    pub current: usize,
+    /// index of the rate under the cursor
+    pub selected: usize,
+    /// the port stays at the current rate rather than moving on
+    pub held: bool,
+    /// when the port was set to the current rate
+    since: Instant,
+    /// the current rate's sample is from its last turn, replaced once bytes
+    /// arrive, so quiet turns don't wipe it
+    stale: bool,
+    /// the rate before the sweep, set again when it's cancelled
+    pub original: u32,
+}
+
+impl Sweep {
+    /// how long every rate is listened to
+    pub const HOLD: Duration = Duration::from_secs(1);
+    /// bytes kept per rate, enough for a preview and a fair score
+    const SAMPLE: usize = 256;
+
+    /// a sweep through `rates`, the port set to the first one
+    pub fn new(rates: &[u32], original: u32) -> Self {
+        Self {
+            rates: rates
+                .iter()
+                .map(|&baud| Rate {
+                    baud,
+                    sample: Vec::new(),
+                })
+                .collect(),
+            current: 0,
+            selected: 0,
+            held: false,
+            since: Instant::now(),
+            stale: false,
+            original,
+        }
+    }
+    /// the rate the port is set to
+    pub fn baud(&self) -> u32 {
+        self.rates[self.current].baud
+    }
+    /// adds newly received `bytes` to the sample of the current rate
+    pub fn push(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        let sample = &mut self.rates[self.current].sample;
+        if std::mem::take(&mut self.stale) {
+            sample.clear();
+        }
+        sample.extend_from_slice(bytes);
+        let keep = sample.len().saturating_sub(Self::SAMPLE);
+        sample.drain(..keep);
+    }
+    /// the next rate to set the port to, once the current one was listened to
+    /// long enough
+    pub fn poll(&mut self) -> Option<u32> {
+        if self.held || self.since.elapsed() < Self::HOLD {
+            return None;
+        }
+        self.switch((self.current + 1) % self.rates.len());
+        Some(self.baud())
+    }
+    /// stays at the selected rate, or moves on again if it's held already;
+    /// the rate to set the port to if that changes
+    pub fn hold(&mut self) -> Option<u32> {
+        if self.held && self.current == self.selected {
+            self.held = false;
+            return None;
+        }
+        self.held = true;
+        if self.current == self.selected {
+            return None;
+        }
+        self.switch(self.selected);
+        Some(self.baud())
+    }
+    pub fn select(&mut self, up: bool) {
+        let n = self.rates.len();
+        self.selected = match up {
+            true => (self.selected + n - 1) % n,
+            false => (self.selected + 1) % n,
+        };
+    }
+    /// index of the rate that looks most like text so far
+    pub fn best(&self) -> Option<usize> {
+        self.rates
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| Some((i, r.score()?)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+    /// listens to rate `i` from now on
+    fn switch(&mut self, i: usize) {
+        self.current = i;
+        self.since = Instant::now();
+        self.stale = true;
+    }
+}
@@ -0,0 +1,334 @@
+//! Frame templates: binary requests described by their fields, filled in a
+//! form and sent without hand-crafting the bytes
+//!
+//! Templates are read from a TOML file like
+//! ```toml
+//! [[template]]
+//! name = "modbus read holding registers"
+//! endian = "be"
+//! fields = [
+//!     { name = "unit", type = "u8", default = "1" },
+//!     { name = "function", type = "u8", value = "0x03" },
+//!     { name = "start", type = "u16" },
+//!     { name = "count", type = "u16", default = "1" },
+//!     { name = "crc", type = "crc16-modbus-le" },
+//! ]
+//! ```
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Templates {
+    #[serde(default, rename = "template")]
+    pub templates: Vec<FrameTemplate>,
+}
+
+impl Templates {
+    /// loads templates from `path`, none if it doesn't exist
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(s) => {
+                let templates: Self = toml::from_str(&s).map_err(|e| e.to_string())?;
+                // bad types are caught now rather than when the template is used
+                for template in &templates.templates {
+                    for field in &template.fields {
+                        field
+                            .kind()
+                            .map_err(|e| format!("template `{}`, {e}", template.name))?;
+                    }
+                }
+                Ok(templates)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// `<config dir>/tuicom/templates.toml`, where templates are read from by default
+pub fn default_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("tuicom");
+    path.push("templates.toml");
+    Some(path)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FrameTemplate {
+    pub name: String,
+    /// byte order of fields that don't give their own
+    #[serde(default)]
+    pub endian: Endian,
+    pub fields: Vec<FieldSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// fixed value, the field isn't asked for
+    pub value: Option<String>,
+    /// value the form starts with
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    #[default]
+    Be,
+    Le,
+}
+
+impl Endian {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Be => "be",
+            Self::Le => "le",
+        }
+    }
+    pub fn flip(self) -> Self {
+        match self {
+            Self::Be => Self::Le,
+            Self::Le => Self::Be,
+        }
+    }
+}
+
+/// What a field holds, from its `type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// integer of `width` bytes, like `u8`, `i16` or `u32le`
+    Int {
+        width: usize,
+        signed: bool,
+        endian: Option<Endian>,
+    },
+    /// `hex`: bytes given as hex digits
+    Hex,
+    /// `ascii`: text sent as it is
+    Ascii,
+    /// a checksum over all bytes before it, filled in when the frame is built
+    Check(Check, Option<Endian>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    Sum8,
+    Xor8,
+    Crc8,
+    Crc16Modbus,
+    Crc16Ccitt,
+    Crc16Xmodem,
+    Crc32,
+}
+
+impl Check {
+    pub const ALL: &'static [(&'static str, Self)] = &[
+        ("sum8", Self::Sum8),
+        ("xor8", Self::Xor8),
+        ("crc8", Self::Crc8),
+        ("crc16-modbus", Self::Crc16Modbus),
+        ("crc16-ccitt", Self::Crc16Ccitt),
+        ("crc16-xmodem", Self::Crc16Xmodem),
+        ("crc32", Self::Crc32),
+    ];
+
+    pub fn name(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(_, c)| *c == self)
+            .map_or("", |(name, _)| name)
+    }
+    /// the checksum of `bytes` as it's sent
+    pub fn bytes(self, bytes: &[u8], endian: Endian) -> Vec<u8> {
+        let (sum, width) = self.compute(bytes);
+        let mut out = Vec::with_capacity(width);
+        push_int(&mut out, sum, width, endian);
+        out
+    }
+    /// checksum of `bytes` and its width in bytes
+    pub fn compute(self, bytes: &[u8]) -> (u64, usize) {
+        match self {
+            Self::Sum8 => (bytes.iter().fold(0u8, |s, &b| s.wrapping_add(b)) as u64, 1),
+            Self::Xor8 => (bytes.iter().fold(0u8, |s, &b| s ^ b) as u64, 1),
+            Self::Crc8 => (crc(bytes, 8, 0x07, 0, false, 0), 1),
+            Self::Crc16Modbus => (crc(bytes, 16, 0x8005, 0xFFFF, true, 0), 2),
+            Self::Crc16Ccitt => (crc(bytes, 16, 0x1021, 0xFFFF, false, 0), 2),
+            Self::Crc16Xmodem => (crc(bytes, 16, 0x1021, 0, false, 0), 2),
+            Self::Crc32 => (
+                crc(bytes, 32, 0x04C1_1DB7, 0xFFFF_FFFF, true, 0xFFFF_FFFF),
+                4,
+            ),
+        }
+    }
+}
+
+/// bitwise CRC of `width` bits, reflected in and out when `reflect`
+fn crc(bytes: &[u8], width: u32, poly: u64, init: u64, reflect: bool, xor_out: u64) -> u64 {
+    let top = 1u64 << (width - 1);
+    let mask = (top << 1) - 1;
+    let mut crc = init;
+    for &b in bytes {
+        let b = if reflect { b.reverse_bits() } else { b };
+        crc ^= (b as u64) << (width - 8);
+        for _ in 0..8 {
+            crc = if crc & top != 0 {
+                (crc << 1) ^ poly
+            } else {
+                crc << 1
+            } & mask;
+        }
+    }
+    if reflect {
+        crc = crc.reverse_bits() >> (64 - width);
+    }
+    (crc ^ xor_out) & mask
+}
+
+/// splits a `le`/`be` suffix off a type name
+fn split_endian(s: &str) -> (&str, Option<Endian>) {
+    let s = s.trim_end_matches('-');
+    if let Some(s) = s.strip_suffix("le") {
+        (s.trim_end_matches('-'), Some(Endian::Le))
+    } else if let Some(s) = s.strip_suffix("be") {
+        (s.trim_end_matches('-'), Some(Endian::Be))
+    } else {
+        (s, None)
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "hex" => return Ok(Self::Hex),
+            "ascii" => return Ok(Self::Ascii),
+            _ => (),
+        }
+        let (base, endian) = split_endian(s);
+        if let Some(&(_, check)) = Check::ALL.iter().find(|(name, _)| *name == base) {
+            return Ok(Self::Check(check, endian));
+        }
+        let signed = match base.chars().next() {
+            Some('u') => false,
+            Some('i') => true,
+            _ => return Err(format!("unknown field type `{s}`")),
+        };
+        let width = match &base[1..] {
+            "8" => 1,
+            "16" => 2,
+            "32" => 4,
+            "64" => 8,
+            _ => return Err(format!("unknown field type `{s}`")),
+        };
+        Ok(Self::Int {
+            width,
+            signed,
+            endian,
+        })
+    }
+}
+
+impl FieldSpec {
+    pub fn kind(&self) -> Result<Kind, String> {
+        self.kind
+            .parse()
+            .map_err(|e| format!("field `{}`: {e}", self.name))
+    }
+    /// whether the form asks for it, rather than it being fixed or computed
+    pub fn is_input(&self) -> bool {
+        self.value.is_none() && !matches!(self.kind(), Ok(Kind::Check(..)))
+    }
+}
+
+impl FrameTemplate {
+    /// fields filled in the form
+    pub fn inputs(&self) -> impl Iterator<Item = &FieldSpec> {
+        self.fields.iter().filter(|f| f.is_input())
+    }
+    /// the bytes of the frame, with `values` for the fields the form asks for
+    pub fn build(&self, values: &[String]) -> Result<Vec<u8>, String> {
+        let mut values = values.iter();
+        let mut out = Vec::new();
+        for field in &self.fields {
+            let kind = field.kind()?;
+            let value = match (&field.value, kind) {
+                (Some(value), _) => value.as_str(),
+                (None, Kind::Check(..)) => "",
+                (None, _) => values.next().map_or("", String::as_str),
+            };
+            let value = value.trim();
+            let err = |e: String| format!("{}: {e}", field.name);
+            match kind {
+                Kind::Int {
+                    width,
+                    signed,
+                    endian,
+                } => {
+                    let n = parse_int(value, width, signed).map_err(err)?;
+                    push_int(&mut out, n, width, endian.unwrap_or(self.endian));
+                }
+                Kind::Hex => out.extend(crate::decode::parse_hex(value).map_err(err)?),
+                Kind::Ascii => out.extend_from_slice(value.as_bytes()),
+                Kind::Check(check, endian) => {
+                    let (sum, width) = check.compute(&out);
+                    push_int(&mut out, sum, width, endian.unwrap_or(self.endian));
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// parses a decimal, `0x` hex or `0b` binary number fitting in `width` bytes,
+/// returning its two's complement bits. Hex and binary give the bits
+/// themselves, so `0xFF` fits an `i8`
+fn parse_int(s: &str, width: usize, signed: bool) -> Result<u64, String> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let (digits, radix, raw) = if let Some(hex) = digits.strip_prefix("0x") {
+        (hex, 16, true)
+    } else if let Some(bin) = digits.strip_prefix("0b") {
+        (bin, 2, true)
+    } else {
+        (digits, 10, false)
+    };
+    // from_str_radix takes a sign too
+    let n = match digits.chars().all(|c| c.is_digit(radix)) {
+        true => u64::from_str_radix(digits, radix).ok(),
+        false => None,
+    };
+    let n = n.ok_or_else(|| match s {
+        "" => String::from("missing value"),
+        _ => format!("invalid number `{s}`"),
+    })?;
+    let bits = width as u32 * 8;
+    let mask = u64::MAX >> (64 - bits);
+    let fits = match (negative, signed && !raw) {
+        (true, _) if !signed => false,
+        (true, _) => n <= 1 << (bits - 1),
+        (false, true) => n < 1 << (bits - 1),
+        (false, false) => n <= mask,
+    };
+    match fits {
+        true if negative => Ok(n.wrapping_neg() & mask),
+        true => Ok(n),
+        false => Err(format!("`{s}` doesn't fit in {bits} bits")),
+    }
+}
+
+fn push_int(out: &mut Vec<u8>, n: u64, width: usize, endian: Endian) {
+    let bytes = n.to_be_bytes();
+    let bytes = &bytes[8 - width..];
+    match endian {
+        Endian::Be => out.extend_from_slice(bytes),
+        Endian::Le => out.extend(bytes.iter().rev()),
+    }
+}
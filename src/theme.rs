@@ -0,0 +1,276 @@
+use ratatui::style::Color;
+use std::fmt;
+use std::str::FromStr;
+
+/// Colors the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// guesses the color support from `COLORTERM` and `TERM`
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        if colorterm.contains("truecolor") || colorterm.contains("24bit") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+    /// converts an RGB color to the closest color the terminal can show
+    pub fn adapt(self, color: Color) -> Color {
+        let Color::Rgb(r, g, b) = color else {
+            return color;
+        };
+        match self {
+            Self::TrueColor => color,
+            Self::Ansi256 => Color::Indexed(nearest_256(r, g, b)),
+            Self::Ansi16 => nearest_16(r, g, b),
+        }
+    }
+}
+
+impl FromStr for ColorDepth {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "truecolor" | "24bit" => Ok(Self::TrueColor),
+            "256" => Ok(Self::Ansi256),
+            "16" => Ok(Self::Ansi16),
+            _ => Err(format!("unknown color depth `{s}`")),
+        }
+    }
+}
+
+/// Colors used by the UI, defined in RGB and adapted to the terminal's color depth
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// background of the status line and bindings bar
+    pub bar_bg: Color,
+    pub bar_fg: Color,
+    /// malformed frames and other errors
+    pub error: Color,
+    /// status line flash when a breakpoint hits, and its color while RX is quiet
+    pub flash: Color,
+    /// secondary text like input hints
+    pub hint: Color,
+    /// lines only RX has in a diff, and only the other capture has
+    pub added: Color,
+    pub removed: Color,
+    /// just received text, brightest first, with `--fade`
+    pub fresh: [Color; 3],
+    /// TX and RX activity LEDs while lit
+    pub led: Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeName {
+    Default,
+    SolarizedDark,
+    Gruvbox,
+    HighContrast,
+    Deuteranopia,
+    Protanopia,
+}
+
+impl ThemeName {
+    pub const ALL: &'static [Self] = &[
+        Self::Default,
+        Self::SolarizedDark,
+        Self::Gruvbox,
+        Self::HighContrast,
+        Self::Deuteranopia,
+        Self::Protanopia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::SolarizedDark => "solarized-dark",
+            Self::Gruvbox => "gruvbox",
+            Self::HighContrast => "high-contrast",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Protanopia => "protanopia",
+        }
+    }
+    fn theme(self) -> Theme {
+        match self {
+            Self::Default => Theme {
+                bar_bg: Color::Rgb(0x4E, 0x4E, 0x4E),
+                bar_fg: Color::Rgb(0xE4, 0xE4, 0xE4),
+                error: Color::Rgb(0xE0, 0x40, 0x40),
+                flash: Color::Rgb(0xC0, 0x20, 0x20),
+                hint: Color::Rgb(0x8A, 0x8A, 0x8A),
+                added: Color::Rgb(0x5F, 0xD7, 0x5F),
+                removed: Color::Rgb(0xD7, 0x5F, 0x5F),
+                fresh: fading(0xFF, 0xD7, 0x00),
+                led: Color::Rgb(0x5F, 0xFF, 0x5F),
+            },
+            Self::SolarizedDark => Theme {
+                bar_bg: Color::Rgb(0x07, 0x36, 0x42),
+                bar_fg: Color::Rgb(0x93, 0xA1, 0xA1),
+                error: Color::Rgb(0xDC, 0x32, 0x2F),
+                flash: Color::Rgb(0xCB, 0x4B, 0x16),
+                hint: Color::Rgb(0x58, 0x6E, 0x75),
+                added: Color::Rgb(0x85, 0x99, 0x00),
+                removed: Color::Rgb(0xDC, 0x32, 0x2F),
+                fresh: fading(0xB5, 0x89, 0x00),
+                led: Color::Rgb(0x2A, 0xA1, 0x98),
+            },
+            Self::Gruvbox => Theme {
+                bar_bg: Color::Rgb(0x3C, 0x38, 0x36),
+                bar_fg: Color::Rgb(0xEB, 0xDB, 0xB2),
+                error: Color::Rgb(0xFB, 0x49, 0x34),
+                flash: Color::Rgb(0xCC, 0x24, 0x1D),
+                hint: Color::Rgb(0x92, 0x83, 0x74),
+                added: Color::Rgb(0xB8, 0xBB, 0x26),
+                removed: Color::Rgb(0xFB, 0x49, 0x34),
+                fresh: fading(0xFA, 0xBD, 0x2F),
+                led: Color::Rgb(0xB8, 0xBB, 0x26),
+            },
+            // pure black and white, errors in yellow which stays readable on both
+            Self::HighContrast => Theme {
+                bar_bg: Color::Rgb(0xFF, 0xFF, 0xFF),
+                bar_fg: Color::Rgb(0x00, 0x00, 0x00),
+                error: Color::Rgb(0xFF, 0xFF, 0x00),
+                flash: Color::Rgb(0xFF, 0xFF, 0x00),
+                hint: Color::Rgb(0xFF, 0xFF, 0xFF),
+                added: Color::Rgb(0x00, 0xFF, 0xFF),
+                removed: Color::Rgb(0xFF, 0xFF, 0x00),
+                fresh: fading(0x00, 0xFF, 0xFF),
+                led: Color::Rgb(0x00, 0xFF, 0x00),
+            },
+            // Okabe-Ito colors, avoiding red/green distinctions
+            Self::Deuteranopia => Theme {
+                bar_bg: Color::Rgb(0x30, 0x30, 0x30),
+                bar_fg: Color::Rgb(0xF0, 0xF0, 0xF0),
+                error: Color::Rgb(0xE6, 0x9F, 0x00),
+                flash: Color::Rgb(0x00, 0x72, 0xB2),
+                hint: Color::Rgb(0x56, 0xB4, 0xE9),
+                added: Color::Rgb(0x56, 0xB4, 0xE9),
+                removed: Color::Rgb(0xE6, 0x9F, 0x00),
+                fresh: fading(0xF0, 0xE4, 0x42),
+                led: Color::Rgb(0x00, 0x9E, 0x73),
+            },
+            // like deuteranopia, but without reddish orange which protanopes see darkened
+            Self::Protanopia => Theme {
+                bar_bg: Color::Rgb(0x30, 0x30, 0x30),
+                bar_fg: Color::Rgb(0xF0, 0xF0, 0xF0),
+                error: Color::Rgb(0xF0, 0xE4, 0x42),
+                flash: Color::Rgb(0x00, 0x72, 0xB2),
+                hint: Color::Rgb(0x56, 0xB4, 0xE9),
+                added: Color::Rgb(0x56, 0xB4, 0xE9),
+                removed: Color::Rgb(0xF0, 0xE4, 0x42),
+                fresh: fading(0x00, 0x9E, 0x73),
+                led: Color::Rgb(0x00, 0x9E, 0x73),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ThemeName {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|t| t.name() == s)
+            .ok_or_else(|| format!("unknown theme `{s}`"))
+    }
+}
+
+impl Theme {
+    pub fn new(name: ThemeName, depth: ColorDepth) -> Self {
+        let t = name.theme();
+        Self {
+            bar_bg: depth.adapt(t.bar_bg),
+            bar_fg: depth.adapt(t.bar_fg),
+            error: depth.adapt(t.error),
+            flash: depth.adapt(t.flash),
+            hint: depth.adapt(t.hint),
+            added: depth.adapt(t.added),
+            removed: depth.adapt(t.removed),
+            fresh: t.fresh.map(|c| depth.adapt(c)),
+            led: depth.adapt(t.led),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemeName::Default, ColorDepth::detect())
+    }
+}
+
+/// `color` fading toward the light gray of plain text in three steps
+fn fading(r: u8, g: u8, b: u8) -> [Color; 3] {
+    let step = |c: u8, i: u16| ((c as u16 * (3 - i) + 0xD0 * i) / 3) as u8;
+    [0, 1, 2].map(|i| Color::Rgb(step(r, i), step(g, i), step(b, i)))
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// closest color of the xterm 6x6x6 cube or grayscale ramp
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |c: u8| {
+        (0..6)
+            .min_by_key(|&i| (LEVELS[i] as i32 - c as i32).abs())
+            .unwrap()
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let cube_idx = 16 + 36 * ri + 6 * gi + bi;
+
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_i = ((avg.saturating_sub(8)) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_i;
+    let gray_idx = 232 + gray_i;
+
+    if distance((r, g, b), (gray, gray, gray)) < distance((r, g, b), cube) {
+        gray_idx
+    } else {
+        cube_idx as u8
+    }
+}
+
+/// closest of the 16 standard ANSI colors, using the usual xterm values
+fn nearest_16(r: u8, g: u8, b: u8) -> Color {
+    const ANSI: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (205, 0, 0)),
+        (Color::Green, (0, 205, 0)),
+        (Color::Yellow, (205, 205, 0)),
+        (Color::Blue, (0, 0, 238)),
+        (Color::Magenta, (205, 0, 205)),
+        (Color::Cyan, (0, 205, 205)),
+        (Color::Gray, (229, 229, 229)),
+        (Color::DarkGray, (127, 127, 127)),
+        (Color::LightRed, (255, 0, 0)),
+        (Color::LightGreen, (0, 255, 0)),
+        (Color::LightYellow, (255, 255, 0)),
+        (Color::LightBlue, (92, 92, 255)),
+        (Color::LightMagenta, (255, 0, 255)),
+        (Color::LightCyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+    ANSI.iter()
+        .min_by_key(|(_, rgb)| distance((r, g, b), *rgb))
+        .map(|(c, _)| *c)
+        .unwrap()
+}
@@ -0,0 +1,185 @@
+//! RX and another capture on one time axis, like the debug console of a board
+//! against its modem link recorded by a second tuicom, to see what one side
+//! sent around the time the other did something
+use crate::capture::clock;
+use crate::decode::parse_hex;
+use crate::screen::overlay::{Outcome, Overlay};
+use crate::session::{self, Session};
+use crate::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Paragraph, Widget},
+};
+use std::cell::Cell;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// lines of `bytes` and when they arrived, in milliseconds since 1970, from
+/// the offsets where lines start and their times
+pub fn lines(bytes: &[u8], arrivals: &[(usize, u64)]) -> Vec<(u64, String)> {
+    let mut out = Vec::new();
+    for (i, &(start, at)) in arrivals.iter().enumerate() {
+        let end = arrivals.get(i + 1).map_or(bytes.len(), |&(o, _)| o);
+        let Some(line) = bytes.get(start..end.min(bytes.len())) else {
+            continue;
+        };
+        out.extend(
+            String::from_utf8_lossy(line)
+                .lines()
+                .map(|l| (at, l.to_owned())),
+        );
+    }
+    out
+}
+
+/// the lines of a session file and when they arrived. Other captures don't
+/// have the times
+pub fn load(path: &Path) -> Result<Vec<(u64, String)>, String> {
+    if path.extension().is_none_or(|e| e != session::EXTENSION) {
+        return Err(String::from(
+            "only sessions have the times lines arrived, save one with :mksession",
+        ));
+    }
+    let session = Session::load(path)?;
+    Ok(lines(&parse_hex(&session.rx)?, &session.rx_times))
+}
+
+/// A line of either capture
+struct Row {
+    at: u64,
+    /// from the other capture rather than RX
+    other: bool,
+    text: String,
+}
+
+/// The lines of both captures in the order they arrived, RX on the left and
+/// the other one on the right
+pub struct TimelineView {
+    title: String,
+    name: String,
+    rows: Vec<Row>,
+    /// first row shown
+    top: usize,
+    /// rows shown on the last draw, for paging
+    height: Cell<usize>,
+}
+
+impl TimelineView {
+    /// puts the lines of `other`, named `name`, next to the ones of `rx`
+    pub fn new(name: &str, rx: Vec<(u64, String)>, other: Vec<(u64, String)>) -> Self {
+        let mut rows = rx
+            .into_iter()
+            .map(|(at, text)| (at, false, text))
+            .chain(other.into_iter().map(|(at, text)| (at, true, text)))
+            .map(|(at, other, text)| Row { at, other, text })
+            .collect::<Vec<_>>();
+        // stable, so lines arriving at the same time keep their order
+        rows.sort_by_key(|r| r.at);
+        Self {
+            title: format!("Timeline: RX against {name}"),
+            name: name.to_owned(),
+            rows,
+            top: 0,
+            height: Cell::new(1),
+        }
+    }
+    /// the next row from the other side than the one at the top, to go from
+    /// something happening on one port to what the other did next
+    fn next_switch(&self) -> Option<usize> {
+        let side = self.rows.get(self.top)?.other;
+        let n = self.rows[self.top..].iter().position(|r| r.other != side)?;
+        Some(self.top + n)
+    }
+}
+
+impl Overlay for TimelineView {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn size(&self) -> (u16, u16) {
+        (90, 80)
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        let last = self.rows.len().saturating_sub(1);
+        let page = self.height.get().max(1);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Outcome::Cancel,
+            KeyCode::Char('d') if ctrl => self.top += page / 2,
+            KeyCode::Char('u') if ctrl => self.top = self.top.saturating_sub(page / 2),
+            KeyCode::Char('j') | KeyCode::Down => self.top += 1,
+            KeyCode::Char('k') | KeyCode::Up => self.top = self.top.saturating_sub(1),
+            KeyCode::PageDown => self.top += page,
+            KeyCode::PageUp => self.top = self.top.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => self.top = 0,
+            KeyCode::Char('G') | KeyCode::End => self.top = last,
+            KeyCode::Char('n') => self.top = self.next_switch().unwrap_or(self.top),
+            _ => (),
+        }
+        self.top = self.top.min(last);
+        Outcome::Continue
+    }
+    fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        const TIME: u16 = 13;
+        let hint = Style::default().fg(theme.hint);
+        let height = area.height.saturating_sub(2) as usize;
+        self.height.set(height);
+        let half = area.width.saturating_sub(TIME + 1) / 2;
+        let cell = |text: &str| text.chars().take(half as usize).collect::<String>();
+        let pad = |text: String| format!("{text:<width$}", width = half as usize);
+        let mut lines = vec![Spans::from(Span::styled(
+            format!(
+                "{:<time$}{}│{}",
+                "UTC",
+                pad(String::from("RX")),
+                self.name,
+                time = TIME as usize
+            ),
+            hint,
+        ))];
+        for row in self.rows.iter().skip(self.top).take(height) {
+            let time = clock(UNIX_EPOCH + Duration::from_millis(row.at));
+            let (left, right) = match row.other {
+                false => (cell(&row.text), String::new()),
+                true => (String::new(), cell(&row.text)),
+            };
+            lines.push(Spans::from(vec![
+                Span::styled(format!("{time} "), hint),
+                Span::raw(pad(left)),
+                Span::raw("│"),
+                Span::raw(right),
+            ]));
+        }
+        if self.rows.is_empty() {
+            lines.push(Spans::from(Span::styled("nothing here", hint)));
+        }
+        Paragraph::new(lines).render(
+            Rect {
+                height: area.height.saturating_sub(1),
+                ..area
+            },
+            buf,
+        );
+        let (rx, other) = self
+            .rows
+            .iter()
+            .fold((0, 0), |(rx, other), r| match r.other {
+                false => (rx + 1, other),
+                true => (rx, other + 1),
+            });
+        let footer = Rect {
+            y: area.bottom().saturating_sub(1),
+            height: area.height.min(1),
+            ..area
+        };
+        let summary = format!(
+            "{rx} lines of RX, {other} of {} | n: other side | q: close",
+            self.name
+        );
+        Paragraph::new(Span::styled(summary, hint)).render(footer, buf);
+    }
+}
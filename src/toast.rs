@@ -0,0 +1,42 @@
+//! Short lived notifications for things happening in the background
+use std::time::{Duration, Instant};
+
+pub struct Toast {
+    pub text: String,
+    pub error: bool,
+    at: Instant,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    /// how long a toast stays on screen
+    const TTL: Duration = Duration::from_secs(4);
+    /// older toasts are dropped early when more pile up
+    const MAX: usize = 5;
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(text.into(), false);
+    }
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(text.into(), true);
+    }
+    fn push(&mut self, text: String, error: bool) {
+        self.toasts.push(Toast {
+            text,
+            error,
+            at: Instant::now(),
+        });
+        if self.toasts.len() > Self::MAX {
+            self.toasts.remove(0);
+        }
+    }
+    /// toasts still on screen, oldest first
+    pub fn visible(&mut self) -> &[Toast] {
+        self.toasts.retain(|t| t.at.elapsed() < Self::TTL);
+        &self.toasts
+    }
+}
@@ -0,0 +1,111 @@
+//! What the app itself went through, like ports opened and lost, errors, mode
+//! changes and slow frames, for `:trace` and for attaching to bug reports
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// a port was opened, at startup, after a switch or a reconnect
+    Open,
+    /// the port went away
+    Lost,
+    /// reopening a lost port, whether it worked or not
+    Reconnect,
+    /// something logged to the error panel
+    Error,
+    Mode,
+    /// updating and drawing took longer than frames are apart
+    Frame,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Lost => "lost",
+            Self::Reconnect => "reconnect",
+            Self::Error => "error",
+            Self::Mode => "mode",
+            Self::Frame => "frame",
+        }
+    }
+}
+
+pub struct Entry {
+    /// time since the session started
+    pub at: Duration,
+    pub kind: Kind,
+    pub text: String,
+}
+
+pub struct Trace {
+    entries: VecDeque<Entry>,
+    start: Instant,
+}
+
+impl Trace {
+    /// oldest entries are dropped beyond this
+    const MAX: usize = 5000;
+
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            start: Instant::now(),
+        }
+    }
+    pub fn record(&mut self, kind: Kind, text: impl Into<String>) {
+        if self.entries.len() == Self::MAX {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            at: self.start.elapsed(),
+            kind,
+            text: text.into(),
+        });
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    pub fn lines(&self) -> Vec<String> {
+        self.entries.iter().map(Entry::to_string).collect()
+    }
+    /// writes the entries to `path` after the `header` lines, like the
+    /// version and the port
+    pub fn export(&self, path: &Path, header: &[String]) -> Result<(), io::Error> {
+        let mut out = BufWriter::new(File::create(path)?);
+        for line in header {
+            writeln!(out, "# {line}")?;
+        }
+        for entry in &self.entries {
+            writeln!(out, "{entry}")?;
+        }
+        out.flush()
+    }
+}
+
+impl Default for Trace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// formatted as `+hh:mm:ss.mmm kind text`
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.at.as_secs();
+        write!(
+            f,
+            "+{:02}:{:02}:{:02}.{:03} {:<9} {}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            self.at.subsec_millis(),
+            self.kind.name(),
+            self.text
+        )
+    }
+}
@@ -1,102 +1,782 @@
-use crate::app::{App, Mode};
+use crate::app::{App, Focus, Mode, ScreenPos, Scroll, Selection};
+use crate::capture::BootLog;
+use crate::decode::{Decoder, Record};
+use crate::highlight::Highlights;
+use crate::screen::overlay::Overlay;
+use crate::screen::{rows_for, size, wrapped_rows, Display};
+use crate::sweep::Sweep;
+use crate::theme::{ColorDepth, Theme};
+use crate::watch::ago;
+use crate::watchdog::Watchdog;
+use crate::waveform::Trace;
+use crate::window::Window;
 use itertools::Itertools;
 use ratatui::{
     backend::Backend,
+    buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Span, Spans},
+    text::{Span, Spans, Text},
     widgets::Clear,
-    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Paragraph, Widget, Wrap},
     Frame,
 };
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::time::{Duration, Instant};
 
 pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let bindings = bindings_line();
+    let bindings_height = (bindings.width() as u16).div_ceil(f.size().width.max(1));
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage(48),
-            Constraint::Percentage(48),
-            Constraint::Min(1),
+            Constraint::Min(0),
+            Constraint::Length(bindings_height),
             Constraint::Length(1),
         ])
         .split(f.size());
+    let mut main = chunks[0];
+    if app.link.is_down() && main.height > 1 {
+        draw_disconnected(f, app, Rect { height: 1, ..main });
+        main.y += 1;
+        main.height -= 1;
+    }
+    let panes = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(app.split),
+            Constraint::Percentage(100 - app.split),
+        ])
+        .split(main);
 
     // tx
-    draw_tx(f, app, chunks[0]);
+    draw_tx(f, app, panes[0]);
 
     // rx
-    draw_rx(f, app, chunks[1]);
+    let mut rx_rect = panes[1];
+    if app.waveform.is_some() {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Percentage(40)])
+            .split(rx_rect);
+        rx_rect = parts[0];
+        draw_waveform(f, app, parts[1]);
+    }
+    if app.watches.shown && !app.watches.is_empty() {
+        let width = app
+            .watches
+            .watches
+            .iter()
+            .map(|w| w.label.chars().count() as u16)
+            .max()
+            .unwrap_or(0)
+            + 16;
+        let parts = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(20), Constraint::Length(width)])
+            .split(rx_rect);
+        rx_rect = parts[0];
+        draw_watches(f, app, parts[1]);
+    }
+    draw_rx(f, app, rx_rect);
+    if let Some(sel) = &mut app.selection {
+        f.render_widget(Highlight(sel), app.rx_area);
+    }
+    draw_visual(f, app);
 
     // bindings
-    draw_bindings(f, chunks[2]);
+    let p = Paragraph::new(bindings)
+        .style(Style::default().fg(app.theme.bar_fg).bg(app.theme.bar_bg))
+        .wrap(Wrap { trim: true });
+    f.render_widget(p, chunks[1]);
 
     // status line
-    draw_status(f, app, chunks[3]);
-
     match &app.mode {
-        Mode::WannaQuit => draw_quit_popup(f),
-        Mode::BaudInput(s) => draw_baud_popup(f, s),
-        _ => (),
+        Mode::SearchInput {
+            input,
+            backward,
+            error,
+        } => {
+            let prefix = if *backward { "?" } else { "/" };
+            draw_prompt(f, app, prefix, input, error.as_deref(), chunks[2]);
+        }
+        Mode::CommandInput { input, error } => {
+            draw_prompt(f, app, ":", input, error.as_deref(), chunks[2])
+        }
+        _ => draw_status(f, app, chunks[2]),
+    }
+
+    draw_toasts(f, app, chunks[0]);
+    if app.frame_times.shown {
+        draw_frame_times(f, app, chunks[0]);
+    }
+
+    if let Some(sweep) = &app.sweep {
+        draw_sweep(f, sweep, &app.theme);
+    }
+    for overlay in app.overlays.iter() {
+        draw_overlay(f, overlay, &app.theme);
+    }
+    if app.is_flashing() {
+        f.render_widget(VisualBell, f.size());
+    }
+}
+
+/// banner over the panes while the port is gone
+fn draw_disconnected<B: Backend>(f: &mut Frame<B>, app: &App, rect: Rect) {
+    let Some(down) = &app.link.down else {
+        return;
     };
+    let now = Instant::now();
+    let downtime = now.duration_since(down.since).as_secs();
+    let retry = down
+        .retry_at
+        .saturating_duration_since(now)
+        .as_secs_f32()
+        .ceil();
+    let text = format!(
+        " DISCONNECTED {} for {}:{:02} | retrying in {retry}s | R: retry now | P: switch port | {}",
+        app.link.port,
+        downtime / 60,
+        downtime % 60,
+        down.error,
+    );
+    let style = Style::default()
+        .fg(app.theme.error)
+        .add_modifier(Modifier::BOLD | Modifier::REVERSED);
+    f.render_widget(Paragraph::new(text).style(style), rect);
 }
 
 fn draw_tx<B: Backend>(f: &mut Frame<B>, app: &mut App, rect: Rect) {
-    let title = if app.tx.is_ascii() {
-        "[TX]"
-    } else {
-        "[TX - Hex]"
-    };
-    let block = Block::default()
-        .title(title)
-        .borders(Borders::all())
-        .border_type(if app.mode.is_insert() {
-            BorderType::Thick
-        } else {
-            BorderType::Plain
-        });
+    let mut title = vec!["TX"];
+    if app.tx.is_hex() {
+        title.push("Hex");
+    }
+    if !app.tx.display.wrap {
+        title.push("No Wrap");
+    }
+    let block = pane_block(format!("[{}]", title.join(" - ")), app.focus == Focus::Tx);
     let inner = block.inner(rect);
-    let tx = app.tx.with_cursor(app.cursor());
-    let txt = Paragraph::new(tx.as_ref())
+    app.tx_area = inner;
+    let wrap = app.tx.display.wrap;
+    let mut lines = app.tx.lines();
+    let cursor = app.cursor();
+    // in line mode the cursor can be inside the line, on a character shown reversed
+    let mut cursor_at = None;
+    if let Some(last) = lines.last_mut() {
+        if app.line_mode {
+            let edit = &app.line_edit;
+            last.push_str(&edit.text);
+            if edit.cursor() < edit.text.len() {
+                cursor_at = Some(last.len() - edit.text.len() + edit.cursor());
+            }
+        }
+        if cursor_at.is_none() {
+            last.push(cursor);
+        }
+    }
+    let scroll = &mut app.tx_scroll;
+    scroll.lines = lines_rows(&lines, inner, wrap);
+    scroll.bar = scroll_bar_area(inner);
+    let selected = Style::default().add_modifier(Modifier::REVERSED);
+    let n = lines.len();
+    let text = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| match (app.tx_selected == Some(i), cursor_at) {
+            (true, _) => Spans::from(Span::styled(line, selected)),
+            (false, Some(at)) if i + 1 == n => {
+                let len = line[at..].chars().next().map_or(0, char::len_utf8);
+                let style = match cursor {
+                    ' ' => Style::default(),
+                    _ => selected,
+                };
+                Spans::from(vec![
+                    Span::raw(line[..at].to_owned()),
+                    Span::styled(line[at..at + len].to_owned(), style),
+                    Span::raw(line[at + len..].to_owned()),
+                ])
+            }
+            (false, _) => Spans::from(line),
+        })
+        .collect::<Vec<_>>();
+    let mut txt = Paragraph::new(text)
         .block(block)
-        .scroll((scroll_amount(tx.as_ref(), inner), 0))
-        .wrap(Wrap { trim: false });
+        .scroll((scroll.offset().min(u16::MAX as usize) as u16, 0));
+    if wrap {
+        txt = txt.wrap(Wrap { trim: false });
+    }
 
     f.render_widget(txt, rect);
+    f.render_widget(ScrollBar(scroll), scroll.bar);
 }
 
-fn draw_rx<B: Backend>(f: &mut Frame<B>, app: &mut App, rect: Rect) {
-    let title = if app.rx.is_ascii() {
-        "[RX]"
+/// pane border, thick with a highlighted title when focused
+fn pane_block(title: String, focused: bool) -> Block<'static> {
+    let block = Block::default().borders(Borders::all());
+    if focused {
+        block.border_type(BorderType::Thick).title(Span::styled(
+            format!("{title} ●"),
+            Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        ))
     } else {
-        "[RX - Hex]"
-    };
-    let block = Block::default().title(title).borders(Borders::all());
+        block.border_type(BorderType::Plain).title(title)
+    }
+}
+
+fn draw_rx<B: Backend>(f: &mut Frame<B>, app: &mut App, rect: Rect) {
+    let mut title = vec![String::from("RX")];
+    if let Some(i) = app.window {
+        title.push(format!(
+            "{} ({}/{})",
+            app.windows[i].kind,
+            i + 1,
+            app.windows.len()
+        ));
+    } else if let Some(kind) = app.rx.decoder_kind() {
+        title.push(kind.to_string());
+    } else if app.rx.term.is_some() {
+        title.push(String::from("VT100"));
+    } else if app.rx.is_hex() {
+        title.push(String::from("Hex"));
+    }
+    if !app.rx.display.wrap && app.rx.decoder_kind().is_none() && app.rx.term.is_none() {
+        title.push(String::from("No Wrap"));
+    }
+    if app.rx.is_paused() {
+        title.push(format!("PAUSED, {} held", app.rx.held()));
+    }
+    let block = pane_block(format!("[{}]", title.join(" - ")), app.focus == Focus::Rx);
     let inner = block.inner(rect);
+    app.rx_area = inner;
+
+    if let Some(i) = app.window {
+        f.render_widget(block, rect);
+        let window = &mut app.windows[i];
+        window.refresh(&app.rx.display);
+        draw_window(
+            f,
+            window,
+            &app.rx.display,
+            &app.theme,
+            &mut app.rx_scroll,
+            inner,
+        );
+        draw_new_badge(f, app, rect);
+        return;
+    }
+
+    if let Some(decoder) = app.rx.decoder() {
+        f.render_widget(block, rect);
+        let scroll = &mut app.rx_scroll;
+        let highlights = &app.highlights;
+        draw_decoded(
+            f,
+            decoder,
+            &app.rx.records,
+            highlights,
+            &app.theme,
+            scroll,
+            inner,
+        );
+        f.render_widget(ScrollBar(scroll), scroll.bar);
+        draw_new_badge(f, app, rect);
+        return;
+    }
+    if let Some(term) = &mut app.rx.term {
+        f.render_widget(block, rect);
+        // the emulated screen has no scrollback
+        app.rx_scroll.bar = Rect::default();
+        draw_term(f, term, app.color_depth, inner);
+        return;
+    }
+
+    let cursor = app.cursor();
+    let byte_selection = app.byte_selection();
+    let display = &app.rx.display;
+    let wrap = display.wrap;
+    let rows = display.rows(inner.width, wrap);
+    let n = display.line_count();
+    // the cursor goes after the last character, which may take another row
+    let cursor_rows = match n.checked_sub(1) {
+        Some(_) if display.text().ends_with('\n') => 1,
+        Some(last) if wrap => {
+            let width = display.line_width(last);
+            rows_for(width + 1, inner.width) - rows_for(width, inner.width)
+        }
+        Some(_) => 0,
+        None => 1,
+    };
+    let scroll = &mut app.rx_scroll;
+    scroll.lines = rows[n] + cursor_rows;
+    scroll.bar = scroll_bar_area(inner);
 
-    let rx = app.rx.with_cursor(app.cursor());
-    let txt = Paragraph::new(rx.as_ref())
+    // only the rows in view are handed to the paragraph
+    let view = (scroll.offset(), inner.height as usize);
+    let (range, skip) = display.visible(&rows, view, inner.width);
+    let shown = range.len();
+    let shown_before = range.start;
+    let mut text = display.text()[range.clone()].to_owned();
+    if range.end == display.text().len() {
+        text.push(cursor);
+    }
+    let cursor_byte = byte_selection
+        .and_then(|bytes| {
+            let (first, last) = (
+                display.hex_range(*bytes.start())?,
+                display.hex_range(*bytes.end())?,
+            );
+            Some(first.start.saturating_sub(shown_before)..last.end.checked_sub(shown_before)?)
+        })
+        .filter(|r| r.end <= text.len());
+    let text = match cursor_byte {
+        // hex text has no line breaks, it all goes on one line
+        Some(r) => Text::from(Spans::from(vec![
+            Span::raw(&text[..r.start]),
+            Span::styled(
+                &text[r.clone()],
+                Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            ),
+            Span::raw(&text[r.end..]),
+        ])),
+        None => match display.fade {
+            Some(fade) if !display.fresh().is_empty() => {
+                let fresh = (display.fresh(), shown_before..shown_before + shown);
+                aged_text(&text, fresh, fade, &app.theme)
+            }
+            _ => Text::from(text.as_str()),
+        },
+    };
+    let mut txt = Paragraph::new(text)
         .block(block)
-        .scroll((scroll_amount(rx.as_ref(), inner), 0))
-        .wrap(Wrap { trim: false });
+        .scroll((skip.min(u16::MAX as usize) as u16, 0));
+    if wrap {
+        txt = txt.wrap(Wrap { trim: false });
+    }
     f.render_widget(txt, rect);
+    f.render_widget(ScrollBar(scroll), scroll.bar);
+    draw_new_badge(f, app, rect);
+}
+
+/// `text` with what was received within `fade` colored by how long ago,
+/// `fresh` holding where that starts in the RX text and where in it `text` is
+fn aged_text<'a>(
+    text: &'a str,
+    (fresh, range): (&VecDeque<(usize, Instant)>, Range<usize>),
+    fade: Duration,
+    theme: &Theme,
+) -> Text<'a> {
+    let offset = |o: usize| o.clamp(range.start, range.end) - range.start;
+    let mut pieces = Vec::new();
+    let mut at = 0;
+    for (i, &(start, when)) in fresh.iter().enumerate() {
+        let (start, end) = (
+            offset(start),
+            fresh.get(i + 1).map_or(range.len(), |&(o, _)| offset(o)),
+        );
+        let age = when.elapsed();
+        if age >= fade || start >= end {
+            continue;
+        }
+        pieces.push((&text[at..start], Style::default()));
+        let step = ((age.as_secs_f32() / fade.as_secs_f32() * 3.0) as usize).min(2);
+        let mut style = Style::default().fg(theme.fresh[step]);
+        if step == 0 {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        pieces.push((&text[start..end], style));
+        at = end;
+    }
+    pieces.push((&text[at..], Style::default()));
+
+    let mut lines: Vec<Vec<(&str, Style)>> = vec![Vec::new()];
+    for (piece, style) in pieces {
+        for (i, part) in piece.split('\n').enumerate() {
+            if i > 0 {
+                // the `\r` of a line break isn't shown, whichever piece it's in
+                let line = lines.last_mut().unwrap();
+                if let Some((last, _)) = line.last_mut() {
+                    *last = last.strip_suffix('\r').unwrap_or(last);
+                }
+                lines.push(Vec::new());
+            }
+            if !part.is_empty() {
+                lines.last_mut().unwrap().push((part, style));
+            }
+        }
+    }
+    let lines = lines.into_iter().map(|line| {
+        Spans::from(
+            line.into_iter()
+                .map(|(s, style)| Span::styled(s, style))
+                .collect::<Vec<_>>(),
+        )
+    });
+    Text::from(lines.collect::<Vec<_>>())
+}
+
+/// `+327 new` on the bottom border of RX while scrolled up or since the mark
+fn draw_new_badge<B: Backend>(f: &mut Frame<B>, app: &App, rect: Rect) {
+    let text = match (app.rx_marked, app.rx_scroll.is_following()) {
+        (true, _) => format!(" +{} since mark ", app.rx_new),
+        (false, false) if app.rx_new > 0 => format!(" +{} new ", app.rx_new),
+        _ => return,
+    };
+    let width = (text.chars().count() as u16).min(rect.width.saturating_sub(4));
+    let area = Rect {
+        x: rect.right().saturating_sub(width + 2),
+        y: rect.bottom().saturating_sub(1),
+        width,
+        height: 1,
+    };
+    let style = Style::default()
+        .fg(app.theme.bar_fg)
+        .bg(app.theme.flash)
+        .add_modifier(Modifier::BOLD);
+    f.render_widget(Paragraph::new(Span::styled(text, style)), area);
+}
+
+/// hit count and time since the last hit of every watch pattern
+fn draw_watches<B: Backend>(f: &mut Frame<B>, app: &App, rect: Rect) {
+    let label_width = app
+        .watches
+        .watches
+        .iter()
+        .map(|w| w.label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines = app
+        .watches
+        .watches
+        .iter()
+        .map(|w| {
+            let seen = w
+                .last_seen
+                .map_or(String::from("-"), |t| format!("{} ago", ago(t.elapsed())));
+            let style = if w.hits > 0 {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.hint)
+            };
+            Spans::from(vec![
+                Span::styled(format!("{:label_width$} ", w.label), style),
+                Span::styled(format!("{:>5}", w.hits), style),
+                Span::raw(format!(" {seen:>7}")),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let block = Block::default().borders(Borders::all()).title("[Watches]");
+    f.render_widget(Paragraph::new(lines).block(block), rect);
+}
+
+fn draw_waveform<B: Backend>(f: &mut Frame<B>, app: &App, rect: Rect) {
+    let Some(waveform) = &app.waveform else {
+        return;
+    };
+    let block = Block::default().borders(Borders::all());
+    let inner = block.inner(rect);
+    let title = match (waveform.last(), waveform.range(inner.width)) {
+        (Some(last), Some((lo, hi))) => {
+            format!(
+                "[Waveform - field {} - {last} - {lo} to {hi}]",
+                waveform.field
+            )
+        }
+        _ => format!("[Waveform - field {} - no samples]", waveform.field),
+    };
+    f.render_widget(block.title(title), rect);
+    f.render_widget(
+        Trace {
+            waveform,
+            style: Style::default(),
+        },
+        inner,
+    );
+}
+
+/// debug overlay in the top left corner of `area`
+fn draw_frame_times<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let text = app.frame_times.to_string();
+    let rect = Rect {
+        width: (text.len() as u16 + 2).min(area.width),
+        height: 1.min(area.height),
+        ..area
+    };
+    let style = Style::default().fg(app.theme.bar_fg).bg(app.theme.bar_bg);
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(format!(" {text} ")).style(style), rect);
+}
+
+/// the scroll bar is drawn over the right border of a pane, next to its text
+fn scroll_bar_area(inner: Rect) -> Rect {
+    Rect {
+        x: inner.right(),
+        width: 1,
+        ..inner
+    }
+}
+
+/// scroll bar thumb sized and placed after the visible part of the buffer,
+/// nothing is drawn while everything fits
+struct ScrollBar<'a>(&'a Scroll);
+
+impl Widget for ScrollBar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (lines, height) = (self.0.lines as u64, area.height as u64);
+        if lines <= height || height == 0 {
+            return;
+        }
+        let thumb = (height * height / lines).max(1);
+        let max = lines - height;
+        let start = self.0.offset() as u64 * (height - thumb) / max;
+        for y in start..start + thumb {
+            buf.get_mut(area.x, area.y + y as u16).set_symbol("┃");
+        }
+    }
+}
+
+/// the whole screen reversed for a moment, for a BEL with `--bell flash`
+struct VisualBell;
+
+impl Widget for VisualBell {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, Style::default().add_modifier(Modifier::REVERSED));
+    }
+}
+
+/// reverses the selected cells and captures their text, like a terminal selection
+struct Highlight<'a>(&'a mut Selection);
+
+impl Widget for Highlight<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let ((x0, y0), (x1, y1)) = self.0.bounds();
+        let text = &mut self.0.text;
+        text.clear();
+        if area.area() == 0 {
+            return;
+        }
+        for y in y0.max(area.top())..=y1.min(area.bottom() - 1) {
+            let from = if y == y0 { x0 } else { area.left() };
+            let to = if y == y1 { x1 } else { area.right() - 1 };
+            let mut line = String::new();
+            for x in from.max(area.left())..=to.min(area.right() - 1) {
+                let cell = buf.get_mut(x, y);
+                line.push_str(&cell.symbol);
+                cell.set_style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+            if y > y0 {
+                text.push('\n');
+            }
+            text.push_str(line.trim_end());
+        }
+    }
+}
+
+fn draw_visual<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let (cells, cursor) = app.visual_cells();
+    f.render_widget(VisualCells(cells, cursor), f.size());
+}
+
+/// reverses the cells of a visual selection, the cursor is underlined instead
+struct VisualCells(Vec<ScreenPos>, Option<ScreenPos>);
+
+impl Widget for VisualCells {
+    fn render(self, _area: Rect, buf: &mut Buffer) {
+        for (x, y) in self.0 {
+            buf.get_mut(x, y)
+                .set_style(Style::default().add_modifier(Modifier::REVERSED));
+        }
+        if let Some((x, y)) = self.1 {
+            buf.get_mut(x, y).set_style(
+                Style::default()
+                    .remove_modifier(Modifier::REVERSED)
+                    .add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
+            );
+        }
+    }
+}
+
+/// decoded RX view: header and live state pinned at the top, records scrolling below
+fn draw_decoded<B: Backend>(
+    f: &mut Frame<B>,
+    decoder: &dyn Decoder,
+    records: &[Record],
+    highlights: &Highlights,
+    theme: &Theme,
+    scroll: &mut Scroll,
+    rect: Rect,
+) {
+    // errors are marked with `!` and bold, not only by color
+    let record_line = |r: &Record| {
+        let highlight = highlights.color(&r.fields);
+        if r.error {
+            let style = Style::default()
+                .fg(highlight.unwrap_or(theme.error))
+                .add_modifier(Modifier::BOLD);
+            Spans::from(Span::styled(format!("! {}", r.text), style))
+        } else if let Some(color) = highlight {
+            Spans::from(Span::styled(
+                format!("  {}", r.text),
+                Style::default().fg(color),
+            ))
+        } else {
+            Spans::from(Span::raw(format!("  {}", r.text)))
+        }
+    };
+    let pinned = decoder
+        .header()
+        .map(|h| {
+            Spans::from(Span::styled(
+                format!("  {h}"),
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+        })
+        .into_iter()
+        .chain(decoder.live().iter().map(record_line))
+        .collect::<Vec<_>>();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(pinned.len() as u16), Constraint::Min(0)])
+        .split(rect);
+    f.render_widget(Paragraph::new(pinned), chunks[0]);
+
+    scroll.lines = records.len();
+    scroll.bar = scroll_bar_area(chunks[1]);
+    // records are a row each, so only the ones in view are drawn
+    let lines = records
+        .iter()
+        .skip(scroll.offset())
+        .take(chunks[1].height as usize)
+        .map(record_line)
+        .collect::<Vec<_>>();
+    f.render_widget(Paragraph::new(lines), chunks[1]);
+}
+
+/// a window in place of the RX text, its lines a row each
+fn draw_window<B: Backend>(
+    f: &mut Frame<B>,
+    window: &Window,
+    display: &Display,
+    theme: &Theme,
+    scroll: &mut Scroll,
+    rect: Rect,
+) {
+    scroll.lines = window.len(display);
+    scroll.bar = scroll_bar_area(rect);
+    if window.is_empty(display) {
+        let hint = Span::styled("nothing yet", Style::default().fg(theme.hint));
+        f.render_widget(Paragraph::new(hint), rect);
+        return;
+    }
+    // only the lines in view are made
+    let top = scroll.offset();
+    let end = (top + rect.height as usize).min(scroll.lines);
+    let lines = window
+        .lines(display, top..end)
+        .into_iter()
+        .map(Spans::from)
+        .collect::<Vec<_>>();
+    f.render_widget(Paragraph::new(lines), rect);
+    f.render_widget(ScrollBar(scroll), scroll.bar);
+}
+
+/// RX pane as an emulated terminal screen
+fn draw_term<B: Backend>(
+    f: &mut Frame<B>,
+    term: &mut vt100::Parser,
+    depth: ColorDepth,
+    rect: Rect,
+) {
+    // vt100 can't take a screen without rows or columns
+    if rect.height == 0 || rect.width == 0 {
+        return;
+    }
+    if term.screen().size() != (rect.height, rect.width) {
+        term.set_size(rect.height, rect.width);
+    }
+    let screen = term.screen();
+    let cursor = (!screen.hide_cursor()).then(|| screen.cursor_position());
+    let lines = (0..rect.height)
+        .map(|row| {
+            let mut spans: Vec<Span> = Vec::new();
+            for col in 0..rect.width {
+                let Some(cell) = screen.cell(row, col) else {
+                    continue;
+                };
+                if cell.is_wide_continuation() {
+                    continue;
+                }
+                let mut style = Style::default()
+                    .fg(depth.adapt(term_color(cell.fgcolor())))
+                    .bg(depth.adapt(term_color(cell.bgcolor())));
+                if cell.bold() {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                if cell.italic() {
+                    style = style.add_modifier(Modifier::ITALIC);
+                }
+                if cell.underline() {
+                    style = style.add_modifier(Modifier::UNDERLINED);
+                }
+                if cell.inverse() != (cursor == Some((row, col))) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                let contents = if cell.has_contents() {
+                    cell.contents()
+                } else {
+                    String::from(" ")
+                };
+                // merge runs of equally styled cells into one span
+                match spans.last_mut() {
+                    Some(last) if last.style == style => last.content.to_mut().push_str(&contents),
+                    _ => spans.push(Span::styled(contents, style)),
+                }
+            }
+            Spans::from(spans)
+        })
+        .collect::<Vec<_>>();
+    f.render_widget(Paragraph::new(lines), rect);
+}
+
+fn term_color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(i) => Color::Indexed(i),
+        vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
 }
 
 fn draw_status<B: Backend>(f: &mut Frame<B>, app: &App, rect: Rect) {
     let bold = Style::default().add_modifier(Modifier::BOLD);
-    let port_name = app.serial.name().unwrap_or_else(|| String::from("serial"));
+    let port_name = match &app.link.description {
+        Some(description) => format!("{} ({description})", app.link.port),
+        None => app.link.port.clone(),
+    };
 
     let baud_rate = app
         .serial
         .baud_rate()
         .map(|b| b.to_string())
         .unwrap_or_else(|_| String::from("<baud>"));
-    let mode = if app.mode.is_insert() {
-        "INSERT"
+    let mode = match &app.mode {
+        Mode::Insert => "INSERT",
+        Mode::Visual(v) if v.linewise => "VISUAL LINE",
+        Mode::Visual(_) => "VISUAL",
+        _ => "NORMAL",
+    };
+    let crlf = match (app.line_mode, app.tx.lf_crlf) {
+        (true, _) => format!("LINE {}", app.tx.line_ending.name().to_uppercase()),
+        (false, true) => String::from("CR + LF"),
+        (false, false) => String::from("LF"),
+    };
+    let keys = app.pending_keys();
+    let mode = if keys.is_empty() {
+        mode.to_owned()
     } else {
-        "NORMAL"
+        format!("{mode} {keys}")
     };
-    let crlf = if app.tx.lf_crlf { "CR + LF" } else { "LF" };
-    let spans = Spans::from(vec![
+    let mut spans = vec![
         Span::styled(mode, bold),
         Span::raw(" | "),
         Span::styled(port_name, bold),
@@ -104,85 +784,393 @@ fn draw_status<B: Backend>(f: &mut Frame<B>, app: &App, rect: Rect) {
         Span::styled(baud_rate, bold),
         Span::raw(" | "),
         Span::styled(crlf, bold),
-    ]);
-    let p = Paragraph::new(spans).style(Style::default().bg(Color::DarkGray));
+        Span::raw(" | "),
+    ];
+    spans.extend(led("TX", app.tx.bytes_sent, app.tx.last_write, &app.theme));
+    spans.push(Span::raw(" "));
+    spans.extend(led(
+        "RX",
+        app.rx.bytes_received,
+        app.rx.last_recv,
+        &app.theme,
+    ));
+    if let Some(ending) = app.rx.line_stats.detected() {
+        spans.push(Span::raw(" | "));
+        let ending = ending.name().to_uppercase();
+        spans.push(Span::styled(format!("EOL {ending}"), bold));
+    }
+    if app.rx.cr_lines {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled("RX CR", bold));
+    }
+    if app.hex_paste {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled("HEX PASTE", bold));
+    }
+    if let Some(periodic) = app.periodic.as_ref().filter(|p| p.enabled) {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!(
+                "PERIODIC {}ms #{}",
+                periodic.interval.as_millis(),
+                periodic.counter()
+            ),
+            bold,
+        ));
+    }
+    if let Some(prompt) = &app.prompt {
+        spans.push(Span::raw(" | "));
+        spans.push(match prompt.is_ready(app.tx.last_write) {
+            true => Span::styled("READY", bold),
+            false => Span::styled("BUSY", Style::default().fg(app.theme.hint)),
+        });
+    }
+    if app.fuzzer.enabled {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("FUZZ seed {} #{}", app.fuzzer.seed, app.fuzzer.cases()),
+            bold,
+        ));
+    }
+    if let Some(search) = &app.search {
+        let dir = if search.backward { '?' } else { '/' };
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(format!("{dir}{}", search.regex), bold));
+        if search.not_found {
+            spans.push(Span::styled(
+                " not found",
+                Style::default().fg(app.theme.error),
+            ));
+        }
+    }
+    if let Some(offset) = app.byte_cursor {
+        if let Some(&byte) = app.rx.display.bytes().get(offset) {
+            let ch = match byte {
+                0x20..=0x7E => format!("'{}'", byte as char),
+                _ => String::from("-"),
+            };
+            spans.push(Span::raw(" | "));
+            spans.push(Span::styled(
+                format!("@{offset:#06X} ({offset}) {byte:#04X} {byte} {byte:#010b} {ch}"),
+                bold,
+            ));
+            if let Some(bytes) = app.byte_selection().filter(|_| app.byte_anchor.is_some()) {
+                spans.push(Span::raw(" | "));
+                spans.push(Span::styled(
+                    format!("{} BYTES SELECTED", bytes.count()),
+                    bold,
+                ));
+            }
+        }
+    }
+    if !app.rx.filter.is_empty() {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(format!("DROP {}", app.rx.filter), bold));
+    }
+    if !app.active_presets.is_empty() {
+        spans.push(Span::raw(" | "));
+        let presets = app.active_presets.join(" ");
+        spans.push(Span::styled(format!("PRESET {presets}"), bold));
+    }
+    if let Some(log) = &app.log {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("LOG {}", log.format.name().to_uppercase()),
+            bold,
+        ));
+    }
+    if let Some(remaining) = app.boot_log.as_ref().and_then(BootLog::remaining) {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("BOOT LOG {}s", remaining.as_secs_f32().ceil()),
+            bold,
+        ));
+    }
+    if app.rx.backlog() > 0 {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("RX BACKLOG {}", app.rx.backlog()),
+            bold,
+        ));
+    }
+    if let Some(job) = &app.gcode {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!(
+                "GCODE {} {}/{} {}%",
+                job.name,
+                job.acked(),
+                job.total(),
+                job.acked() * 100 / job.total()
+            ),
+            bold,
+        ));
+    }
+    if let Some(paste) = &app.paste {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!(
+                "{} {}/{}",
+                if paste.startup { "STARTUP" } else { "PASTE" },
+                paste.sent(),
+                paste.total
+            ),
+            bold,
+        ));
+    }
+    let sending = app.paste.is_some() || app.tx.progress().is_some();
+    if sending && app.tx.ack.as_ref().is_some_and(|a| a.is_waiting()) {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled("WAIT ACK", bold));
+    }
+    if let Some(progress) = app.tx.progress() {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(progress.to_string(), bold));
+    } else if app.tx.pending() > 0 {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(format!("TX QUEUE {}", app.tx.pending()), bold));
+    }
+    if app.errors.unseen() > 0 {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("ERR {}", app.errors.unseen()),
+            bold.fg(app.theme.error),
+        ));
+    }
+    if let Some(message) = &app.message {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::raw(message.as_str()));
+    }
+    let mut bg = app.theme.bar_bg;
+    if let Some(silence) = app.watchdog.as_ref().and_then(Watchdog::silence) {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("SILENT {}s", silence.as_secs()),
+            bold.add_modifier(Modifier::REVERSED),
+        ));
+        bg = app.theme.flash;
+    }
+    if let Some(hit) = &app.rx.hit {
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("BREAK /{}/", app.rx.breakpoints[hit.breakpoint]),
+            bold.add_modifier(Modifier::REVERSED),
+        ));
+        // flash for a couple of seconds after the hit
+        let elapsed = hit.at.elapsed();
+        if elapsed < BREAK_FLASH && (elapsed.as_millis() / 250) % 2 == 0 {
+            bg = app.theme.flash;
+        }
+    }
+    let p = Paragraph::new(Spans::from(spans)).style(Style::default().fg(app.theme.bar_fg).bg(bg));
     f.render_widget(p, rect);
 }
 
+/// `TX ● 5.0 KiB`, the LED lit for a moment after traffic like the ones on a
+/// USB serial adapter
+fn led(name: &'static str, bytes: u64, last: Instant, theme: &Theme) -> [Span<'static>; 3] {
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let light = match bytes > 0 && last.elapsed() < LED_LIT {
+        true => Span::styled("● ", bold.fg(theme.led)),
+        false => Span::styled("○ ", Style::default().fg(theme.hint)),
+    };
+    [
+        Span::styled(format!("{name} "), bold),
+        light,
+        Span::styled(size(bytes as f64), bold),
+    ]
+}
+
+/// search or command input replacing the status line, like vim's command line
+fn draw_prompt<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    prefix: &'static str,
+    input: &str,
+    error: Option<&str>,
+    rect: Rect,
+) {
+    use ratatui::symbols::block::SEVEN_EIGHTHS as cursor;
+    let mut spans = vec![
+        Span::raw(prefix),
+        Span::raw(input),
+        Span::styled(cursor, Style::default().add_modifier(Modifier::SLOW_BLINK)),
+    ];
+    if let Some(e) = error {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!("error: {}", e.lines().last().unwrap_or(e)),
+            Style::default()
+                .fg(app.theme.error)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    let p = Paragraph::new(Spans::from(spans))
+        .style(Style::default().fg(app.theme.bar_fg).bg(app.theme.bar_bg));
+    f.render_widget(p, rect);
+}
+
+/// how long an activity LED stays lit after a read or write
+const LED_LIT: Duration = Duration::from_millis(100);
+
+const BREAK_FLASH: Duration = Duration::from_secs(2);
+
 static BINDINGS: &[(&str, &str)] = &[
     ("q", "quit"),
     ("H", "TX hex"),
     ("h", "RX hex"),
     ("C", "clear TX"),
     ("c", "clear RX"),
+    ("m/M", "set/remove mark"),
+    ("B", "byte cursor in RX hex"),
     ("l", "map LF to CR + LF"),
+    ("L", "line mode"),
     ("b", "change baud rate"),
+    ("p", "periodic send"),
+    ("F", "fuzz"),
+    ("E", "errors"),
+    ("X", "abort TX"),
+    ("P", "switch port"),
+    ("S", "serial settings"),
+    ("O", "log RX"),
+    ("U", "send file"),
+    ("I", "device info"),
+    ("W", "waveform"),
+    ("D", "watches"),
+    ("w", "wrap"),
+    ("↑/↓", "TX history"),
+    ("r", "re-send last"),
+    ("SPACE", "resume after break"),
+    ("d", "decoder"),
+    ("T", "terminal emulation"),
+    ("f", "send frame"),
+    ("t", "frame templates"),
     ("i", "insert mode"),
+    (".", "repeat"),
+    ("TAB", "focus"),
+    ("C-UP/C-DOWN", "resize"),
+    ("j/k", "scroll"),
+    ("C-D/C-U/C-F/C-B", "page"),
+    ("gg/G", "RX top/bottom"),
+    ("gt/gT", "RX windows"),
+    ("/ ?", "search"),
+    ("n/N", "next/previous match"),
+    ("yy", "yank line"),
+    (":", "command"),
+    ("v/V", "visual"),
     ("ESC", "normal mode"),
 ];
-fn draw_bindings<B: Backend>(f: &mut Frame<B>, rect: Rect) {
-    let spans = Spans::from(
-        BINDINGS
-            .iter()
-            .map(|(key, action)| {
-                [
-                    Span::styled(*key, Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(": "),
-                    Span::raw(*action),
-                ]
-            })
-            .intersperse([Span::raw(" "), Span::raw("|"), Span::raw(" ")])
+fn bindings_line() -> Spans<'static> {
+    let bindings = BINDINGS.iter().map(|(key, action)| {
+        [
+            Span::styled(*key, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(": "),
+            Span::raw(*action),
+        ]
+    });
+    Spans::from(
+        Itertools::intersperse(bindings, [Span::raw(" "), Span::raw("|"), Span::raw(" ")])
             .flatten()
             .collect::<Vec<_>>(),
-    );
-    let p = Paragraph::new(spans).style(Style::default().bg(Color::DarkGray));
-    f.render_widget(p, rect);
+    )
 }
 
-fn draw_quit_popup<B: Backend>(f: &mut Frame<B>) {
-    let block = Block::default().title("Quit").borders(Borders::all());
-    let area = centered_rect(30, 20, f.size());
-    let txt = Paragraph::new("Are you sure you want to quit (y/n)?")
-        .block(block)
-        .wrap(Wrap { trim: true });
-    f.render_widget(Clear, area);
-    f.render_widget(txt, area);
+/// toasts stacked in the top right corner, newest at the bottom
+fn draw_toasts<B: Backend>(f: &mut Frame<B>, app: &mut App, rect: Rect) {
+    let theme = app.theme;
+    for (i, toast) in app.toasts.visible().iter().enumerate() {
+        let width = (toast.text.chars().count() as u16 + 2).min(rect.width);
+        let y = rect.y + 1 + i as u16;
+        if y >= rect.bottom() {
+            break;
+        }
+        let area = Rect::new(rect.right().saturating_sub(width + 1), y, width, 1);
+        let mut style = Style::default().fg(theme.bar_fg).bg(theme.bar_bg);
+        if toast.error {
+            style = style.fg(theme.error).add_modifier(Modifier::BOLD);
+        }
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(format!(" {} ", toast.text)).style(style),
+            area,
+        );
+    }
 }
 
-fn draw_baud_popup<B: Backend>(f: &mut Frame<B>, baud_input: &str) {
-    use ratatui::symbols::block::SEVEN_EIGHTHS as cursor;
-    let block = Block::default().title("Baud Rate").borders(Borders::all());
-    let area = centered_rect(20, 20, f.size());
-    let spans = Spans::from(vec![
-        Span::raw("Baud rate: "),
-        Span::raw(baud_input),
-        Span::styled(cursor, Style::default().add_modifier(Modifier::SLOW_BLINK)),
-    ]);
-    let txt = Paragraph::new(spans).block(block).wrap(Wrap { trim: true });
+pub fn draw_overlay<B: Backend>(f: &mut Frame<B>, overlay: &dyn Overlay, theme: &Theme) {
+    let (width, height) = overlay.size();
+    let area = centered_rect(width, height, f.size());
+    let block = Block::default()
+        .title(overlay.title())
+        .borders(Borders::all());
+    let inner = block.inner(area);
     f.render_widget(Clear, area);
-    f.render_widget(txt, area);
+    f.render_widget(block, area);
+    f.render_widget(OverlayContents(overlay, theme), inner);
 }
 
-// functions for autoscrolling the text areas
-fn scroll_amount(s: &str, area: Rect) -> u16 {
-    let lines = lines(s, area);
-    if lines > area.height {
-        lines - area.height
-    } else {
-        0
+struct OverlayContents<'a>(&'a dyn Overlay, &'a Theme);
+
+impl Widget for OverlayContents<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.0.render(area, buf, self.1);
     }
 }
-fn lines(s: &str, area: Rect) -> u16 {
-    let mut line_count = 0;
-    for line in s.lines() {
-        line_count += 1;
-        let mut line_len = line.len();
-        while line_len > area.width as usize {
-            line_count += 1;
-            line_len -= area.width as usize;
-        }
+
+/// number of rows `lines` take in `area`, wrapped or not
+fn lines_rows(lines: &[String], area: Rect, wrap: bool) -> usize {
+    match wrap {
+        true => lines.iter().map(|l| wrapped_rows(l, area.width)).sum(),
+        false => lines.len(),
     }
-    line_count as u16
+}
+
+/// every rate of a baud sweep with its score and what arrived at it, the one
+/// the port is at marked `▶`
+fn draw_sweep<B: Backend>(f: &mut Frame<B>, sweep: &Sweep, theme: &Theme) {
+    let area = centered_rect(70, 60, f.size());
+    let title = match sweep.held {
+        true => format!("[Baud Sweep - HOLD {}]", sweep.baud()),
+        false => String::from("[Baud Sweep]"),
+    };
+    let block = Block::default().title(title).borders(Borders::all());
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let best = sweep.best();
+    let width = (inner.width as usize).saturating_sub(15);
+    let mut lines = sweep
+        .rates
+        .iter()
+        .enumerate()
+        .map(|(i, rate)| {
+            let marker = if i == sweep.current { '▶' } else { ' ' };
+            let score = rate
+                .score()
+                .map_or(String::from("   -"), |s| format!("{:>3.0}%", s * 100.0));
+            let mut style = Style::default();
+            if Some(i) == best {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if i == sweep.selected {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            // the latest bytes, as many as fit
+            let preview = rate.preview();
+            let skip = preview.chars().count().saturating_sub(width);
+            Spans::from(vec![
+                Span::styled(format!("{marker}{:>8} {score} ", rate.baud), style),
+                Span::raw(preview.chars().skip(skip).collect::<String>()),
+            ])
+        })
+        .collect::<Vec<_>>();
+    lines.push(Spans::default());
+    lines.push(Spans::from(Span::styled(
+        "↑/↓: select | SPACE: hold | ENTER: use | ESC: cancel",
+        Style::default().fg(theme.hint),
+    )));
+    f.render_widget(Paragraph::new(lines), inner);
 }
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
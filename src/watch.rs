@@ -0,0 +1,91 @@
+//! Counting matches of watch patterns in RX, a lightweight way to monitor soak tests
+use regex::bytes::Regex;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct Watch {
+    pub label: String,
+    pub pattern: Regex,
+    pub hits: usize,
+    pub last_seen: Option<Instant>,
+}
+
+/// parses `label=regex`, or just `regex` labeled with itself
+impl FromStr for Watch {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (label, pattern) = s.split_once('=').unwrap_or((s, s));
+        if pattern.is_empty() {
+            return Err(format!("missing pattern in `{s}`"));
+        }
+        Ok(Self {
+            label: label.to_owned(),
+            pattern: Regex::new(pattern).map_err(|e| e.to_string())?,
+            hits: 0,
+            last_seen: None,
+        })
+    }
+}
+
+pub struct Watches {
+    pub watches: Vec<Watch>,
+    /// whether the dashboard is on screen
+    pub shown: bool,
+    /// end of the RX data scanned so far, so matches across reads are found
+    tail: Vec<u8>,
+}
+
+impl Watches {
+    /// RX bytes kept to match patterns spanning several reads
+    const LOOKBACK: usize = 256;
+
+    pub fn new(watches: Vec<Watch>) -> Self {
+        Self {
+            watches,
+            shown: true,
+            tail: Vec::new(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+    /// counts the matches in newly received `bytes`
+    pub fn scan(&mut self, bytes: &[u8]) {
+        if self.watches.is_empty() {
+            return;
+        }
+        let old = self.tail.len();
+        self.tail.extend_from_slice(bytes);
+        let now = Instant::now();
+        for watch in &mut self.watches {
+            // only matches ending in the new data, earlier ones are counted already
+            let hits = watch
+                .pattern
+                .find_iter(&self.tail)
+                .filter(|m| m.end() > old)
+                .count();
+            if hits > 0 {
+                watch.hits += hits;
+                watch.last_seen = Some(now);
+            }
+        }
+        let keep = self.tail.len().saturating_sub(Self::LOOKBACK);
+        self.tail.drain(..keep);
+    }
+}
+
+impl Default for Watches {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+/// short age like `5s`, `3m` or `2h`
+pub fn ago(age: Duration) -> String {
+    match age.as_secs() {
+        s if s < 60 => format!("{s}s"),
+        s if s < 3600 => format!("{}m", s / 60),
+        s => format!("{}h", s / 3600),
+    }
+}
@@ -0,0 +1,39 @@
+//! Warns when no RX data arrived for a while, catching crashed or hung devices
+use std::time::{Duration, Instant};
+
+pub struct Watchdog {
+    pub timeout: Duration,
+    /// also raise a desktop notification when RX goes quiet
+    pub notify: bool,
+    last: Instant,
+    silent: bool,
+}
+
+impl Watchdog {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            notify: false,
+            last: Instant::now(),
+            silent: false,
+        }
+    }
+    /// RX data arrived, returns how long it was quiet if the watchdog had fired
+    pub fn feed(&mut self) -> Option<Duration> {
+        let quiet = self.last.elapsed();
+        self.last = Instant::now();
+        std::mem::take(&mut self.silent).then_some(quiet)
+    }
+    /// returns true once when RX has been quiet for longer than the timeout
+    pub fn poll(&mut self) -> bool {
+        if self.silent || self.last.elapsed() < self.timeout {
+            return false;
+        }
+        self.silent = true;
+        true
+    }
+    /// how long RX has been quiet, while the watchdog has fired
+    pub fn silence(&self) -> Option<Duration> {
+        self.silent.then(|| self.last.elapsed())
+    }
+}
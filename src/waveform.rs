@@ -0,0 +1,125 @@
+//! Oscilloscope-like view of numbers in RX lines, drawn with braille dots
+//!
+//! Each terminal cell holds a 2x4 grid of braille dots, giving twice the
+//! horizontal and four times the vertical resolution of plain characters.
+use ratatui::{buffer::Buffer, layout::Rect, style::Style, widgets::Widget};
+use regex::bytes::Regex;
+use std::collections::VecDeque;
+
+pub struct Waveform {
+    samples: VecDeque<f64>,
+    /// which number of a line is plotted, 0 for the first
+    pub field: usize,
+    /// part of a line received so far
+    line: Vec<u8>,
+    number: Regex,
+}
+
+impl Waveform {
+    /// samples kept, more than fit on any screen
+    const MAX_SAMPLES: usize = 4096;
+
+    pub fn new(field: usize) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            field,
+            line: Vec::new(),
+            number: Regex::new(r"-?\d+(\.\d+)?([eE][-+]?\d+)?").unwrap(),
+        }
+    }
+    /// feeds received bytes, taking a sample from every completed line with enough numbers
+    pub fn push(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            if b != b'\n' {
+                self.line.push(b);
+                continue;
+            }
+            let sample = self
+                .number
+                .find_iter(&self.line)
+                .nth(self.field)
+                .and_then(|m| std::str::from_utf8(m.as_bytes()).ok()?.parse().ok());
+            if let Some(sample) = sample {
+                if self.samples.len() == Self::MAX_SAMPLES {
+                    self.samples.pop_front();
+                }
+                self.samples.push_back(sample);
+            }
+            self.line.clear();
+        }
+    }
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.line.clear();
+    }
+    pub fn last(&self) -> Option<f64> {
+        self.samples.back().copied()
+    }
+    /// the newest samples fitting in `width` cells, oldest first
+    fn visible(&self, width: u16) -> impl Iterator<Item = f64> + Clone + '_ {
+        let n = width as usize * 2;
+        self.samples
+            .iter()
+            .copied()
+            .skip(self.samples.len().saturating_sub(n))
+    }
+    /// lowest and highest sample shown in `width` cells
+    pub fn range(&self, width: u16) -> Option<(f64, f64)> {
+        self.visible(width).fold(None, |range, s| match range {
+            None => Some((s, s)),
+            Some((lo, hi)) => Some((s.min(lo), s.max(hi))),
+        })
+    }
+}
+
+/// draws the samples scaled to fill the area, newest on the right
+pub struct Trace<'a> {
+    pub waveform: &'a Waveform,
+    pub style: Style,
+}
+
+impl Widget for Trace<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // dot bits of a braille cell by row, left column then right column
+        const DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+        let Some((lo, hi)) = self.waveform.range(area.width) else {
+            return;
+        };
+        if area.area() == 0 {
+            return;
+        }
+        let rows = area.height as usize * 4;
+        let span = if hi > lo { hi - lo } else { 1.0 };
+        // dot row of a sample, 0 at the top
+        let row = |s: f64| ((hi - s) / span * (rows - 1) as f64).round() as usize;
+
+        let mut cells = vec![0u8; area.area() as usize];
+        let samples = self.waveform.visible(area.width);
+        // right align so the trace scrolls in from the right
+        let skip = area.width as usize * 2 - samples.clone().count();
+        let mut prev = None;
+        for (i, s) in samples.enumerate() {
+            let x = skip + i;
+            let y = row(s);
+            // join the dots vertically so steep edges stay connected
+            let (from, to) = match prev {
+                Some(p) => (y.min(p), y.max(p)),
+                None => (y, y),
+            };
+            for y in from..=to {
+                cells[y / 4 * area.width as usize + x / 2] |= DOTS[y % 4][x % 2];
+            }
+            prev = Some(y);
+        }
+        for (i, &bits) in cells.iter().enumerate() {
+            if bits == 0 {
+                continue;
+            }
+            let (x, y) = (i % area.width as usize, i / area.width as usize);
+            let symbol = char::from_u32(0x2800 + bits as u32).unwrap_or(' ');
+            buf.get_mut(area.x + x as u16, area.y + y as u16)
+                .set_symbol(symbol.encode_utf8(&mut [0; 4]))
+                .set_style(self.style);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Other views of the same RX, shown in the RX pane in its place and switched
+//! between like tmux windows, kept up to date as data arrives
+use crate::region;
+use crate::screen::Display;
+use regex::Regex;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+pub enum Kind {
+    /// the RX lines matching a pattern, or not matching it
+    Grep { pattern: Regex, invert: bool },
+    /// the received bytes as a hexdump
+    Hex,
+}
+
+/// patterns compare by how they were written
+impl PartialEq for Kind {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for Kind {}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Grep { pattern, invert } => {
+                let name = if *invert { "grep!" } else { "grep" };
+                write!(f, "{name} {pattern}")
+            }
+            Self::Hex => f.write_str("hex"),
+        }
+    }
+}
+
+/// parses `grep <regex>`, `grep! <regex>` for lines that don't match, or `hex`
+impl FromStr for Kind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (name, arg) = s.split_once(' ').unwrap_or((s, ""));
+        let arg = arg.trim();
+        match name {
+            "grep" | "grep!" if arg.is_empty() => Err(String::from("missing pattern")),
+            "grep" | "grep!" => Ok(Self::Grep {
+                pattern: Regex::new(arg).map_err(|e| e.to_string())?,
+                invert: name == "grep!",
+            }),
+            "hex" if arg.is_empty() => Ok(Self::Hex),
+            "hex" => Err(String::from("hex windows take no argument")),
+            "" => Err(String::from("missing window, expected grep, grep! or hex")),
+            _ => Err(format!(
+                "unknown window `{name}`, expected grep, grep! or hex"
+            )),
+        }
+    }
+}
+
+pub struct Window {
+    pub kind: Kind,
+    /// RX lines a grep window shows
+    matches: Vec<usize>,
+    /// RX lines looked at so far, the last one again next time as it may grow
+    scanned: usize,
+    /// of the RX lines looked at
+    generation: u64,
+}
+
+impl Window {
+    pub fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            matches: Vec::new(),
+            scanned: 0,
+            generation: 0,
+        }
+    }
+    /// looks at the lines of `display` that are new since the last time
+    pub fn refresh(&mut self, display: &Display) {
+        let Kind::Grep { pattern, invert } = &self.kind else {
+            return;
+        };
+        if display.generation() != self.generation {
+            self.generation = display.generation();
+            self.scanned = 0;
+        }
+        let n = display.line_count();
+        // the last line may have grown, or lines were deleted
+        let from = self.scanned.min(n).saturating_sub(1);
+        let keep = self.matches.partition_point(|&i| i < from);
+        self.matches.truncate(keep);
+        self.matches
+            .extend((from..n).filter(|&i| pattern.is_match(display.line(i)) != *invert));
+        self.scanned = n;
+    }
+    /// lines the window has
+    pub fn len(&self, display: &Display) -> usize {
+        match self.kind {
+            Kind::Grep { .. } => self.matches.len(),
+            Kind::Hex => display.bytes().len().div_ceil(region::HEX_ROW),
+        }
+    }
+    pub fn is_empty(&self, display: &Display) -> bool {
+        self.len(display) == 0
+    }
+    /// the lines in `range`, as they're shown
+    pub fn lines(&self, display: &Display, range: Range<usize>) -> Vec<String> {
+        match self.kind {
+            Kind::Grep { .. } => self.matches[range]
+                .iter()
+                .map(|&i| display.line(i).to_owned())
+                .collect(),
+            Kind::Hex => region::hex_lines(display.bytes(), range),
+        }
+    }
+}